@@ -0,0 +1,64 @@
+// fixed search over a set of positions, the node count is a signature of the search: a change
+// that doesnt change the node count doesnt change how the engine plays
+use std::{
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+use game::Board;
+
+use crate::Engine;
+
+pub const BENCH_DEPTH: u8 = 5;
+
+// openings, middlegames and endgames, most of them from the perft suites
+pub const BENCH_POSITIONS: [&str; 12] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP1B1PPP/R2QKB1R w KQ - 1 8",
+    "2r3k1/pp3ppp/4p3/3p4/3P4/P3PN2/1P3PPP/2R3K1 b - - 0 24",
+    "8/5pk1/6p1/3R4/7P/6P1/r4PK1/8 w - - 0 40",
+    "8/8/3k4/8/3PK3/8/8/8 w - - 0 1",
+    "6k1/5p2/6p1/8/7p/8/6PP/6K1 b - - 0 1",
+];
+
+pub struct BenchResult {
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+impl BenchResult {
+    pub fn nps(&self) -> u64 {
+        (1_000_000.0 * self.nodes as f64 / self.time.as_micros().max(1) as f64) as u64
+    }
+}
+
+impl Display for BenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes {} nps", self.nodes, self.nps())
+    }
+}
+
+/// searches every bench position to the given depth, each with a fresh engine (and so a fresh
+/// transposition table)
+pub fn bench(depth: u8) -> BenchResult {
+    let mut nodes = 0;
+    let mut time = Duration::ZERO;
+
+    for fen in BENCH_POSITIONS {
+        let mut engine = Engine::new(Board::from_fen(fen).unwrap());
+        engine.quiet = true;
+
+        let start = Instant::now();
+        engine.iterative_deepening_search(depth, false, start, Duration::from_secs(0), None);
+        time += start.elapsed();
+        nodes += engine.nodes_searched;
+    }
+
+    BenchResult { nodes, time }
+}
@@ -1,18 +1,20 @@
-use game::{get_piece_color, get_piece_type};
-use game::{BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
 
-use self::piecetables::{
-    BLACK_BISHOP_TABLE, BLACK_ENDGAME_KING_TABLE, BLACK_KNIGHT_TABLE, BLACK_MIDGAME_KING_TABLE,
-    BLACK_PAWN_TABLE, BLACK_QUEEN_TABLE, BLACK_ROOK_TABLE, WHITE_BISHOP_TABLE,
-    WHITE_ENDGAME_KING_TABLE, WHITE_KNIGHT_TABLE, WHITE_MIDGAME_KING_TABLE, WHITE_PAWN_TABLE,
-    WHITE_QUEEN_TABLE, WHITE_ROOK_TABLE,
-};
+use game::{
+    precomputed::{KING_MASKS, KNIGHT_MASKS},
+    Bitboard, Bitboards, Color, File, PieceType, Square};
+use movegen::{bishop_attacks, rook_attacks};
+
+pub use self::params::EvalParams;
 
 // probably doesnt need to be in its own file but whatever
 use super::Engine;
 
+pub mod params;
 mod piecetables;
+pub mod trace;
 
+// midgame piece values, also used by the search for move ordering and pruning
 pub const PAWN_VALUE: i32 = 100;
 pub const BISHOP_VALUE: i32 = 320;
 pub const KNIGHT_VALUE: i32 = 300;
@@ -20,12 +22,169 @@ pub const ROOK_VALUE: i32 = 500;
 pub const QUEEN_VALUE: i32 = 900;
 pub const KING_VALUE: i32 = 20000;
 
-// used to determine if in the endgame
-const ENDGAME_PIECE_COUNT: u32 = 15;
+// per square above (or below) the average number of squares the piece can go to
+const KNIGHT_AVERAGE_MOBILITY: i32 = 4;
+const BISHOP_AVERAGE_MOBILITY: i32 = 6;
+const ROOK_AVERAGE_MOBILITY: i32 = 6;
+const QUEEN_AVERAGE_MOBILITY: i32 = 12;
+
+// one piece attacking the king is rarely dangerous, so scale by the number of attackers
+const KING_ATTACKER_SCALE: [i32; 8] = [0, 0, 50, 75, 88, 94, 97, 99];
+
+const A_FILE: u64 = 0x8080808080808080;
+const H_FILE: u64 = 0x0101010101010101;
+
+/// midgame and endgame value of an evaluation term, interpolated using the game phase
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score(pub i32, pub i32);
+
+impl Score {
+    /// phase 0 is the opening, phase 256 is a bare endgame
+    pub fn taper(self, phase: i32) -> i32 {
+        (self.0 * (256 - phase) + self.1 * phase) / 256
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, rhs: Score) -> Score {
+        Score(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Score) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, rhs: Score) -> Score {
+        Score(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, rhs: Score) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, rhs: i32) -> Score {
+        Score(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score(-self.0, -self.1)
+    }
+}
+
+/// the separate terms making up the evaluation of one side, used to trace the evaluation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Term {
+    Material,
+    PawnTable,
+    KnightTable,
+    BishopTable,
+    RookTable,
+    QueenTable,
+    KingTable,
+    PawnStructure,
+    PassedPawns,
+    Pieces,
+    Mobility,
+    KingSafety,
+    Threats,
+    InHand,
+    Tempo,
+}
+
+pub const TERMS: [Term; 15] = [
+    Term::Material,
+    Term::PawnTable,
+    Term::KnightTable,
+    Term::BishopTable,
+    Term::RookTable,
+    Term::QueenTable,
+    Term::KingTable,
+    Term::PawnStructure,
+    Term::PassedPawns,
+    Term::Pieces,
+    Term::Mobility,
+    Term::KingSafety,
+    Term::Threats,
+    Term::InHand,
+    Term::Tempo,
+];
+
+impl Term {
+    pub fn name(self) -> &'static str {
+        match self {
+            Term::Material => "Material",
+            Term::PawnTable => "Pawn PST",
+            Term::KnightTable => "Knight PST",
+            Term::BishopTable => "Bishop PST",
+            Term::RookTable => "Rook PST",
+            Term::QueenTable => "Queen PST",
+            Term::KingTable => "King PST",
+            Term::PawnStructure => "Pawn structure",
+            Term::PassedPawns => "Passed pawns",
+            Term::Pieces => "Pieces",
+            Term::Mobility => "Mobility",
+            Term::KingSafety => "King safety",
+            Term::Threats => "Threats",
+            Term::InHand => "In hand",
+            Term::Tempo => "Tempo",
+        }
+    }
+
+    fn piece_square_table(piece_type: PieceType) -> Term {
+        match piece_type {
+            PieceType::Pawn => Term::PawnTable,
+            PieceType::Bishop => Term::BishopTable,
+            PieceType::Knight => Term::KnightTable,
+            PieceType::Rook => Term::RookTable,
+            PieceType::Queen => Term::QueenTable,
+            PieceType::King => Term::KingTable,
+        }
+    }
+}
+
+/// score of every term for one side
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Terms([Score; 15]);
+
+impl Terms {
+    pub fn total(&self) -> Score {
+        self.0.iter().fold(Score(0, 0), |total, &score| total + score)
+    }
+}
+
+impl Index<Term> for Terms {
+    type Output = Score;
+
+    fn index(&self, term: Term) -> &Score {
+        &self.0[term as usize]
+    }
+}
+
+impl IndexMut<Term> for Terms {
+    fn index_mut(&mut self, term: Term) -> &mut Score {
+        &mut self.0[term as usize]
+    }
+}
 
 struct MaterialInfo {
-    material: i32, // total value
-    num_pieces: i32,
     num_pawns: i32,
     num_bishops: i32,
     num_knights: i32,
@@ -42,8 +201,8 @@ struct MaterialInfo {
 ///
 /// example:
 /// Square: e4, color: white
-/// `pawn_blocker_mask(crate::game::square_from_uci("e4"), crate::game::WHITE);`
-/// ```
+/// `pawn_blocker_mask(game::Square::E4, game::Color::White);`
+/// ```text
 /// 00011100 8
 /// 00011100 7
 /// 00011100 6
@@ -54,29 +213,42 @@ struct MaterialInfo {
 /// 00000000 1
 /// abcdefgh
 /// ```
-pub fn pawn_blocker_mask(square: (u8, u8), color: u8) -> u64 {
-    assert!(color == WHITE || color == BLACK);
-    let a_file: u64 = 0x8080808080808080;
-    let direction_mask = if color == WHITE {
-        u64::MAX << (8 * (8 - square.0))
+pub fn pawn_blocker_mask(square: Square, color: Color) -> u64 {
+    let rank = square.rank().index();
+    let direction_mask = if color == Color::White {
+        u64::MAX << (8 * (rank + 1))
     } else {
-        u64::MAX >> (8 * (square.0 + 1))
+        u64::MAX >> (8 * (8 - rank))
     };
 
-    let mask: u64 = direction_mask
-        & (a_file >> square.1
-            | if square.1 > 0 {
-                a_file >> (square.1 - 1)
-            } else {
-                0
-            }
-            | if square.1 < 7 {
-                a_file >> (square.1 + 1)
-            } else {
-                0
-            });
+    direction_mask & (file_mask(square.file()) | adjacent_files(square.file()))
+}
+
+/// squares attacked by the given pawns
+pub fn pawn_attacks(pawns: u64, color: Color) -> u64 {
+    if color == Color::White {
+        ((pawns & !A_FILE) << 9) | ((pawns & !H_FILE) << 7)
+    } else {
+        ((pawns & !A_FILE) >> 7) | ((pawns & !H_FILE) >> 9)
+    }
+}
 
-    mask
+fn file_mask(file: File) -> u64 {
+    A_FILE >> file.index()
+}
+
+fn adjacent_files(file: File) -> u64 {
+    file.offset(-1).map_or(0, file_mask) | file.offset(1).map_or(0, file_mask)
+}
+
+// rank from the perspective of the given color, 0 is the first rank
+fn relative_rank(square: Square, color: Color) -> usize {
+    square.rank().relative(color.is_white()).index()
+}
+
+// runs the given function for the square of every piece on the bitboard
+fn for_each_square(bitboard: u64, f: impl FnMut(Square)) {
+    Bitboard(bitboard).for_each(f);
 }
 
 // evaluated from perspective of side to move
@@ -84,37 +256,211 @@ pub fn pawn_blocker_mask(square: (u8, u8), color: u8) -> u64 {
 // so each sub-function evaluates from white's perspective
 impl Engine {
     pub fn evaluate(&self) -> i32 {
-        let material = self.get_material_value();
-        let midgame_eval = self.midgame_eval(material.material);
-        let endgame_eval = self.endgame_eval(material.material);
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &self.nnue {
+            return nnue.evaluate(self.board.turn);
+        }
+
+        self.evaluate_hce()
+    }
+
+    /// hand crafted evaluation, used when no network is loaded
+    pub fn evaluate_hce(&self) -> i32 {
+        let phase = self.phase(self.count_material());
+        let score =
+            self.evaluate_side(Color::White).total() - self.evaluate_side(Color::Black).total();
 
-        let phase = self.phase(material);
-        let eval = ((midgame_eval * (256 - phase)) + (endgame_eval * phase)) / 256;
-        let perspective = if self.board.turn { 1 } else { -1 };
-        let tempo = 50; // bonus for right to move, helps avoid score oscillations
+        let eval = score.taper(phase);
+        let perspective = if self.board.turn.is_white() { 1 } else { -1 };
 
-        eval * perspective + tempo
+        eval * perspective + self.eval_params.tempo.taper(phase)
     }
 
-    fn midgame_eval(&self, material: i32) -> i32 {
-        material
+    // all terms for one side, the evaluation is the difference between both sides
+    fn evaluate_side(&self, color: Color) -> Terms {
+        let enemy = !color;
+        let (us, them) = (*self.board.bitboards(color), *self.board.bitboards(enemy));
+        let their_king = self.board.king_square(enemy);
+
+        let occupancy = us.all | them.all;
+        let our_pawn_attacks = pawn_attacks(us.pawns, color);
+        let their_pawn_attacks = pawn_attacks(them.pawns, enemy);
+        // squares attacked by enemy pawns are not worth counting for mobility
+        let mobility_area = !us.all & !their_pawn_attacks;
+        let (king_row, king_file) = their_king.mailbox();
+        // the horde has no king to attack
+        let their_king_zone = if them.king == 0 {
+            0
+        } else {
+            KING_MASKS[king_row][king_file] | them.king
+        };
+        let their_majors = them.rooks | them.queens;
+
+        let params = &self.eval_params;
+        let mut terms = Terms::default();
+        let mut king_attackers = 0;
+        let mut king_attack_weight = 0;
+
+        for piece_type in PieceType::ALL {
+            for_each_square(us.get(piece_type), |square| {
+                terms[Term::Material] += params.material(piece_type);
+                terms[Term::piece_square_table(piece_type)] +=
+                    params.piece_square(piece_type, color, square);
+            });
+        }
+
+        // pieces in hand (crazyhouse) are still material, and worth a bit more since they can
+        // be dropped wherever they are needed
+        for (piece_type, count) in self.board.pocket(color).pieces() {
+            terms[Term::Material] += params.material(piece_type) * count as i32;
+            terms[Term::InHand] += params.in_hand[piece_type.index()] * count as i32;
+        }
+
+        self.pawn_structure(color, us, them, &mut terms);
+
+        for_each_square(us.knights, |square| {
+            let (r, f) = square.mailbox();
+            let attacks = KNIGHT_MASKS[r][f];
+            terms[Term::Mobility] += params.knight_mobility
+                * ((attacks & mobility_area).count_ones() as i32 - KNIGHT_AVERAGE_MOBILITY);
+
+            if is_outpost(square, color, our_pawn_attacks, them.pawns) {
+                terms[Term::Pieces] += params.knight_outpost;
+            }
+
+            terms[Term::Threats] +=
+                params.threat_by_minor * (attacks & their_majors).count_ones() as i32;
+
+            if attacks & their_king_zone != 0 {
+                king_attackers += 1;
+                king_attack_weight +=
+                    params.knight_king_attack * (attacks & their_king_zone).count_ones() as i32;
+            }
+        });
+
+        for_each_square(us.bishops, |square| {
+            let attacks = bishop_attacks(square, occupancy);
+            terms[Term::Mobility] += params.bishop_mobility
+                * ((attacks & mobility_area).count_ones() as i32 - BISHOP_AVERAGE_MOBILITY);
+
+            if is_outpost(square, color, our_pawn_attacks, them.pawns) {
+                terms[Term::Pieces] += params.bishop_outpost;
+            }
+
+            terms[Term::Threats] +=
+                params.threat_by_minor * (attacks & their_majors).count_ones() as i32;
+
+            if attacks & their_king_zone != 0 {
+                king_attackers += 1;
+                king_attack_weight +=
+                    params.bishop_king_attack * (attacks & their_king_zone).count_ones() as i32;
+            }
+        });
+
+        for_each_square(us.rooks, |square| {
+            let attacks = rook_attacks(square, occupancy);
+            terms[Term::Mobility] += params.rook_mobility
+                * ((attacks & mobility_area).count_ones() as i32 - ROOK_AVERAGE_MOBILITY);
+
+            let file = file_mask(square.file());
+            if file & (us.pawns | them.pawns) == 0 {
+                terms[Term::Pieces] += params.rook_open_file;
+            } else if file & us.pawns == 0 {
+                terms[Term::Pieces] += params.rook_half_open_file;
+            }
+
+            terms[Term::Threats] +=
+                params.threat_by_rook * (attacks & them.queens).count_ones() as i32;
+
+            if attacks & their_king_zone != 0 {
+                king_attackers += 1;
+                king_attack_weight +=
+                    params.rook_king_attack * (attacks & their_king_zone).count_ones() as i32;
+            }
+        });
+
+        for_each_square(us.queens, |square| {
+            let attacks = bishop_attacks(square, occupancy) | rook_attacks(square, occupancy);
+            terms[Term::Mobility] += params.queen_mobility
+                * ((attacks & mobility_area).count_ones() as i32 - QUEEN_AVERAGE_MOBILITY);
+
+            if attacks & their_king_zone != 0 {
+                king_attackers += 1;
+                king_attack_weight +=
+                    params.queen_king_attack * (attacks & their_king_zone).count_ones() as i32;
+            }
+        });
+
+        if us.bishops.count_ones() >= 2 {
+            terms[Term::Pieces] += params.bishop_pair;
+        }
+
+        terms[Term::Threats] += params.threat_by_pawn
+            * (our_pawn_attacks & (them.bishops | them.knights | their_majors)).count_ones() as i32;
+
+        // attacks on the king matter much less in the endgame
+        let king_attack = king_attack_weight * KING_ATTACKER_SCALE[king_attackers.min(7)] / 100;
+        terms[Term::KingSafety] += Score(king_attack, king_attack / 4);
+
+        terms[Term::KingSafety] +=
+            params.pawn_shield * self.pawn_shield(color, us.pawns).count_ones() as i32;
+
+        terms
     }
 
-    fn endgame_eval(&self, material: i32) -> i32 {
-        let mut eval = material;
+    // doubled, isolated, backward and passed pawns
+    fn pawn_structure(&self, color: Color, us: Bitboards, them: Bitboards, terms: &mut Terms) {
+        let params = &self.eval_params;
+        let their_pawn_attacks = pawn_attacks(them.pawns, !color);
+
+        for_each_square(us.pawns, |square| {
+            let file = file_mask(square.file());
+            let ahead = pawn_blocker_mask(square, color);
 
-        for rank in 0..8 {
-            for (file, square) in self.board.board[rank].into_iter().enumerate() {
-                if get_piece_type!(square) == PAWN {
-                    eval += self.endgame_pawn_value_adjustment(
-                        (rank as u8, file as u8),
-                        get_piece_color!(square),
-                    )
+            // only the rear pawn counts as doubled
+            let doubled = ahead & file & us.pawns != 0;
+            if doubled {
+                terms[Term::PawnStructure] += params.doubled_pawn;
+            }
+
+            let neighbours = adjacent_files(square.file()) & us.pawns;
+            if neighbours == 0 {
+                terms[Term::PawnStructure] += params.isolated_pawn;
+            } else if neighbours & !ahead == 0 {
+                // all neighbouring pawns are ahead, so they can never defend this pawn
+                let bit = square.bitboard().0;
+                let stop_square = if color.is_white() { bit << 8 } else { bit >> 8 };
+
+                if stop_square & their_pawn_attacks != 0 {
+                    terms[Term::PawnStructure] += params.backward_pawn;
                 }
             }
-        }
 
-        eval
+            if ahead & them.pawns == 0 && !doubled {
+                terms[Term::PassedPawns] += params.passed_pawn[relative_rank(square, color)];
+            }
+        });
+    }
+
+    fn pawn_shield(&self, color: Color, our_pawns: u64) -> u64 {
+        let king = self.board.king_square(color);
+
+        // 0 is the first rank
+        let king_rank = king.rank().index() as i32;
+        let rank_mask = |rank: i32| {
+            if (0..8).contains(&rank) {
+                0xFFu64 << (8 * rank)
+            } else {
+                0
+            }
+        };
+        let ranks = if color == Color::White {
+            rank_mask(king_rank + 1) | rank_mask(king_rank + 2)
+        } else {
+            rank_mask(king_rank - 1) | rank_mask(king_rank - 2)
+        };
+
+        (file_mask(king.file()) | adjacent_files(king.file())) & ranks & our_pawns
     }
 
     // chessprogramming.org/TaperedEval
@@ -138,164 +484,51 @@ impl Engine {
         phase -= info.num_rooks * rook_phase;
         phase -= info.num_queens * queen_phase;
 
-        phase = (phase * 256 + total_phase / 2) / total_phase;
+        // promotions can push the phase below 0
+        phase = (phase.max(0) * 256 + total_phase / 2) / total_phase;
 
         phase
     }
 
-    // adjusts value of pawn for things like doubled pawns, backwards pawn, etc
-    // relative to side to move
-    fn endgame_pawn_value_adjustment(&self, square: (u8, u8), color: u8) -> i32 {
-        let our_blockers = pawn_blocker_mask(square, if self.board.turn { WHITE } else { BLACK });
-        let opponent_blockers =
-            pawn_blocker_mask(square, if self.board.turn { BLACK } else { WHITE });
-
-        let opponent_pawns = if self.board.turn {
-            self.board.black.pawns
-        } else {
-            self.board.white.pawns
-        };
-        let our_pawns = if self.board.turn {
-            self.board.white.pawns
-        } else {
-            self.board.black.pawns
+    // pieces in hand count as well, they can come back at any time
+    fn count_material(&self) -> MaterialInfo {
+        let (white, black) = (self.board.white, self.board.black);
+        let in_hand = |piece_type: PieceType| {
+            Color::ALL
+                .iter()
+                .map(|&color| self.board.pocket(color).count(piece_type) as i32)
+                .sum::<i32>()
         };
 
-        let mut adjustment: i32 = 0;
-
-        // passed pawn (if there are opposing pawns on the file or the adjacent ones)
-        if (color == WHITE) == self.board.turn {
-            if opponent_pawns & our_blockers == 0 {
-                adjustment += 45
-                    + 4 * if color == WHITE {
-                        7 - square.1 as i32
-                    } else if color == BLACK {
-                        square.1 as i32
-                    } else {
-                        panic!("wrong piece color!")
-                    };
-            }
-        } else if our_pawns & opponent_blockers == 0 {
-            adjustment -= 45
-                + 4 * if color == WHITE {
-                    7 - square.1 as i32
-                } else if color == BLACK {
-                    square.1 as i32
-                } else {
-                    panic!("wrong piece color!")
-                };
+        MaterialInfo {
+            num_pawns: (white.pawns | black.pawns).count_ones() as i32 + in_hand(PieceType::Pawn),
+            num_bishops: (white.bishops | black.bishops).count_ones() as i32
+                + in_hand(PieceType::Bishop),
+            num_knights: (white.knights | black.knights).count_ones() as i32
+                + in_hand(PieceType::Knight),
+            num_rooks: (white.rooks | black.rooks).count_ones() as i32 + in_hand(PieceType::Rook),
+            num_queens: (white.queens | black.queens).count_ones() as i32
+                + in_hand(PieceType::Queen),
         }
-
-        adjustment
     }
 
-    fn get_material_value(&self) -> MaterialInfo {
-        let mut info = MaterialInfo {
-            material: 0,
-            num_pieces: 0,
-            num_pawns: 0,
-            num_bishops: 0,
-            num_knights: 0,
-            num_rooks: 0,
-            num_queens: 0,
-        };
-
-        let mut r = 0;
-        let mut f = 0;
-
-        //used to determine which piece table to use
-        let total_piece_count =
-            self.board.white.all.count_ones() + self.board.black.all.count_ones();
-
-        for rank in self.board.board {
-            for piece in rank {
-                if get_piece_color!(piece) == WHITE {
-                    info.material += match get_piece_type!(piece) {
-                        PAWN => {
-                            info.num_pawns += 1;
-                            PAWN_VALUE + WHITE_PAWN_TABLE[r][f]
-                        }
-                        BISHOP => {
-                            info.num_bishops += 1;
-                            BISHOP_VALUE + WHITE_BISHOP_TABLE[r][f]
-                        }
-                        KNIGHT => {
-                            info.num_knights += 1;
-                            KNIGHT_VALUE + WHITE_KNIGHT_TABLE[r][f]
-                        }
-                        ROOK => {
-                            info.num_rooks += 1;
-                            ROOK_VALUE + WHITE_ROOK_TABLE[r][f]
-                        }
-                        QUEEN => {
-                            info.num_queens += 1;
-                            QUEEN_VALUE + WHITE_QUEEN_TABLE[r][f]
-                        }
-                        KING => {
-                            KING_VALUE
-                                + if total_piece_count > ENDGAME_PIECE_COUNT {
-                                    WHITE_MIDGAME_KING_TABLE[r][f]
-                                } else {
-                                    WHITE_ENDGAME_KING_TABLE[r][f]
-                                }
-                        }
-                        _ => 0,
-                    };
-                } else if get_piece_color!(piece) == BLACK {
-                    info.material -= match get_piece_type!(piece) {
-                        PAWN => {
-                            info.num_pawns += 1;
-                            PAWN_VALUE + BLACK_PAWN_TABLE[r][f]
-                        }
-                        BISHOP => {
-                            info.num_bishops += 1;
-                            BISHOP_VALUE + BLACK_BISHOP_TABLE[r][f]
-                        }
-                        KNIGHT => {
-                            info.num_knights += 1;
-                            KNIGHT_VALUE + BLACK_KNIGHT_TABLE[r][f]
-                        }
-                        ROOK => {
-                            info.num_rooks += 1;
-                            ROOK_VALUE + BLACK_ROOK_TABLE[r][f]
-                        }
-                        QUEEN => {
-                            info.num_queens += 1;
-                            QUEEN_VALUE + BLACK_QUEEN_TABLE[r][f]
-                        }
-                        KING => {
-                            KING_VALUE
-                                + if total_piece_count > ENDGAME_PIECE_COUNT {
-                                    BLACK_MIDGAME_KING_TABLE[r][f]
-                                } else {
-                                    BLACK_ENDGAME_KING_TABLE[r][f]
-                                }
-                        }
-                        _ => 0,
-                    };
-                }
-
-                f += 1;
-            }
-
-            r += 1;
-            f = 0;
-        }
-
-        info.num_pieces = total_piece_count as i32;
-
-        info
-    }
-
-    pub fn get_piece_value(piece_type: u8) -> i32 {
+    pub fn get_piece_value(piece_type: PieceType) -> i32 {
         match piece_type {
-            PAWN => PAWN_VALUE,
-            BISHOP => BISHOP_VALUE,
-            KNIGHT => KNIGHT_VALUE,
-            ROOK => ROOK_VALUE,
-            QUEEN => QUEEN_VALUE,
-            KING => 10_000,
-            _ => panic!("lihgoiwgeehiwg"),
+            PieceType::Pawn => PAWN_VALUE,
+            PieceType::Bishop => BISHOP_VALUE,
+            PieceType::Knight => KNIGHT_VALUE,
+            PieceType::Rook => ROOK_VALUE,
+            PieceType::Queen => QUEEN_VALUE,
+            PieceType::King => 10_000,
         }
     }
 }
+
+// minor piece on the 4th to 6th rank, protected by a pawn and out of reach of enemy pawns
+fn is_outpost(square: Square, color: Color, our_pawn_attacks: u64, their_pawns: u64) -> bool {
+    let rank = relative_rank(square, color);
+
+    (3..=5).contains(&rank)
+        && our_pawn_attacks & square.bitboard().0 != 0
+        && pawn_blocker_mask(square, color) & adjacent_files(square.file()) & their_pawns == 0
+}
@@ -0,0 +1,315 @@
+use std::{error::Error, fmt, fs, path::Path, str::FromStr};
+
+use game::{Color, PieceType, Square};
+
+use super::{
+    piecetables::{
+        WHITE_BISHOP_TABLE, WHITE_ENDGAME_BISHOP_TABLE, WHITE_ENDGAME_KING_TABLE,
+        WHITE_ENDGAME_KNIGHT_TABLE, WHITE_ENDGAME_PAWN_TABLE, WHITE_ENDGAME_QUEEN_TABLE,
+        WHITE_ENDGAME_ROOK_TABLE, WHITE_KNIGHT_TABLE, WHITE_MIDGAME_KING_TABLE, WHITE_PAWN_TABLE,
+        WHITE_QUEEN_TABLE, WHITE_ROOK_TABLE,
+    },
+    Score, BISHOP_VALUE, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE,
+};
+
+/// every tunable value of the hand crafted evaluation
+///
+/// the defaults are the hand picked values, tuned values can be loaded from a parameter file
+/// written by the tuner (see `EvalParams::load`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    pub pawn_value: Score,
+    pub knight_value: Score,
+    pub bishop_value: Score,
+    pub rook_value: Score,
+    pub queen_value: Score,
+
+    // piece square tables from white's perspective, indexed [rank][file] with rank 0 being the
+    // 8th rank. black uses the same tables flipped vertically
+    pub pawn_table: [[Score; 8]; 8],
+    pub knight_table: [[Score; 8]; 8],
+    pub bishop_table: [[Score; 8]; 8],
+    pub rook_table: [[Score; 8]; 8],
+    pub queen_table: [[Score; 8]; 8],
+    pub king_table: [[Score; 8]; 8],
+
+    // bonus for the side to move, helps avoid score oscillations
+    pub tempo: Score,
+
+    pub bishop_pair: Score,
+    pub rook_open_file: Score,
+    pub rook_half_open_file: Score,
+
+    pub doubled_pawn: Score,
+    pub isolated_pawn: Score,
+    pub backward_pawn: Score,
+    // indexed by rank relative to the color of the pawn (0 is the first rank)
+    pub passed_pawn: [Score; 8],
+
+    // minor piece protected by a pawn which cant be chased away by enemy pawns
+    pub knight_outpost: Score,
+    pub bishop_outpost: Score,
+
+    // per square above (or below) the average number of squares the piece can go to
+    pub knight_mobility: Score,
+    pub bishop_mobility: Score,
+    pub rook_mobility: Score,
+    pub queen_mobility: Score,
+
+    // per pawn on the three files around the king, one or two ranks in front of it
+    pub pawn_shield: Score,
+    // weight of every attacked square around the enemy king, by attacking piece type
+    // https://www.chessprogramming.org/King_Safety#Attacking_King_Zone
+    pub knight_king_attack: i32,
+    pub bishop_king_attack: i32,
+    pub rook_king_attack: i32,
+    pub queen_king_attack: i32,
+
+    pub threat_by_pawn: Score,
+    // minor piece attacking a rook or queen
+    pub threat_by_minor: Score,
+    // rook attacking a queen
+    pub threat_by_rook: Score,
+
+    // bonus for a piece in hand in crazyhouse on top of its material, indexed by piece type
+    // (pawn to queen)
+    pub in_hand: [Score; 5],
+}
+
+fn table(midgame: [[i32; 8]; 8], endgame: [[i32; 8]; 8]) -> [[Score; 8]; 8] {
+    let mut table = [[Score(0, 0); 8]; 8];
+    for r in 0..8 {
+        for f in 0..8 {
+            table[r][f] = Score(midgame[r][f], endgame[r][f]);
+        }
+    }
+
+    table
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        EvalParams {
+            pawn_value: Score(PAWN_VALUE, 125),
+            knight_value: Score(KNIGHT_VALUE, 290),
+            bishop_value: Score(BISHOP_VALUE, 330),
+            rook_value: Score(ROOK_VALUE, 540),
+            queen_value: Score(QUEEN_VALUE, 960),
+
+            pawn_table: table(WHITE_PAWN_TABLE, WHITE_ENDGAME_PAWN_TABLE),
+            knight_table: table(WHITE_KNIGHT_TABLE, WHITE_ENDGAME_KNIGHT_TABLE),
+            bishop_table: table(WHITE_BISHOP_TABLE, WHITE_ENDGAME_BISHOP_TABLE),
+            rook_table: table(WHITE_ROOK_TABLE, WHITE_ENDGAME_ROOK_TABLE),
+            queen_table: table(WHITE_QUEEN_TABLE, WHITE_ENDGAME_QUEEN_TABLE),
+            king_table: table(WHITE_MIDGAME_KING_TABLE, WHITE_ENDGAME_KING_TABLE),
+
+            tempo: Score(20, 10),
+
+            bishop_pair: Score(30, 50),
+            rook_open_file: Score(40, 15),
+            rook_half_open_file: Score(20, 10),
+
+            doubled_pawn: Score(-10, -25),
+            isolated_pawn: Score(-15, -15),
+            backward_pawn: Score(-10, -10),
+            passed_pawn: [
+                Score(0, 0),
+                Score(5, 10),
+                Score(5, 15),
+                Score(10, 25),
+                Score(20, 45),
+                Score(35, 75),
+                Score(60, 120),
+                Score(0, 0),
+            ],
+
+            knight_outpost: Score(30, 15),
+            bishop_outpost: Score(15, 10),
+
+            knight_mobility: Score(4, 4),
+            bishop_mobility: Score(5, 5),
+            rook_mobility: Score(2, 4),
+            queen_mobility: Score(1, 2),
+
+            pawn_shield: Score(12, 0),
+            knight_king_attack: 20,
+            bishop_king_attack: 20,
+            rook_king_attack: 40,
+            queen_king_attack: 80,
+
+            threat_by_pawn: Score(50, 35),
+            threat_by_minor: Score(30, 20),
+            threat_by_rook: Score(35, 15),
+
+            in_hand: [
+                Score(25, 10),
+                Score(40, 20),
+                Score(30, 20),
+                Score(20, 20),
+                Score(30, 30),
+            ],
+        }
+    }
+}
+
+fn score_values<'a>(scores: impl IntoIterator<Item = &'a mut Score>) -> Vec<&'a mut i32> {
+    scores
+        .into_iter()
+        .flat_map(|score| [&mut score.0, &mut score.1])
+        .collect()
+}
+
+impl EvalParams {
+    pub fn material(&self, piece_type: PieceType) -> Score {
+        match piece_type {
+            PieceType::Pawn => self.pawn_value,
+            PieceType::Bishop => self.bishop_value,
+            PieceType::Knight => self.knight_value,
+            PieceType::Rook => self.rook_value,
+            PieceType::Queen => self.queen_value,
+            PieceType::King => Score(0, 0),
+        }
+    }
+
+    pub fn piece_square(&self, piece_type: PieceType, color: Color, square: Square) -> Score {
+        let (row, f) = square.mailbox();
+        let r = if color == Color::White { row } else { 7 - row };
+
+        match piece_type {
+            PieceType::Pawn => self.pawn_table[r][f],
+            PieceType::Bishop => self.bishop_table[r][f],
+            PieceType::Knight => self.knight_table[r][f],
+            PieceType::Rook => self.rook_table[r][f],
+            PieceType::Queen => self.queen_table[r][f],
+            PieceType::King => self.king_table[r][f],
+        }
+    }
+
+    /// every parameter by name, scores are flattened into midgame and endgame values
+    ///
+    /// this is the order used by the parameter file and the tuner
+    pub fn entries_mut(&mut self) -> Vec<(&'static str, Vec<&mut i32>)> {
+        vec![
+            ("pawn_value", score_values([&mut self.pawn_value])),
+            ("knight_value", score_values([&mut self.knight_value])),
+            ("bishop_value", score_values([&mut self.bishop_value])),
+            ("rook_value", score_values([&mut self.rook_value])),
+            ("queen_value", score_values([&mut self.queen_value])),
+            ("pawn_table", score_values(self.pawn_table.iter_mut().flatten())),
+            ("knight_table", score_values(self.knight_table.iter_mut().flatten())),
+            ("bishop_table", score_values(self.bishop_table.iter_mut().flatten())),
+            ("rook_table", score_values(self.rook_table.iter_mut().flatten())),
+            ("queen_table", score_values(self.queen_table.iter_mut().flatten())),
+            ("king_table", score_values(self.king_table.iter_mut().flatten())),
+            ("tempo", score_values([&mut self.tempo])),
+            ("bishop_pair", score_values([&mut self.bishop_pair])),
+            ("rook_open_file", score_values([&mut self.rook_open_file])),
+            ("rook_half_open_file", score_values([&mut self.rook_half_open_file])),
+            ("doubled_pawn", score_values([&mut self.doubled_pawn])),
+            ("isolated_pawn", score_values([&mut self.isolated_pawn])),
+            ("backward_pawn", score_values([&mut self.backward_pawn])),
+            ("passed_pawn", score_values(&mut self.passed_pawn)),
+            ("knight_outpost", score_values([&mut self.knight_outpost])),
+            ("bishop_outpost", score_values([&mut self.bishop_outpost])),
+            ("knight_mobility", score_values([&mut self.knight_mobility])),
+            ("bishop_mobility", score_values([&mut self.bishop_mobility])),
+            ("rook_mobility", score_values([&mut self.rook_mobility])),
+            ("queen_mobility", score_values([&mut self.queen_mobility])),
+            ("pawn_shield", score_values([&mut self.pawn_shield])),
+            ("knight_king_attack", vec![&mut self.knight_king_attack]),
+            ("bishop_king_attack", vec![&mut self.bishop_king_attack]),
+            ("rook_king_attack", vec![&mut self.rook_king_attack]),
+            ("queen_king_attack", vec![&mut self.queen_king_attack]),
+            ("threat_by_pawn", score_values([&mut self.threat_by_pawn])),
+            ("threat_by_minor", score_values([&mut self.threat_by_minor])),
+            ("threat_by_rook", score_values([&mut self.threat_by_rook])),
+            ("in_hand", score_values(&mut self.in_hand)),
+        ]
+    }
+
+    /// all parameters as one flat list, in the order of `entries_mut`
+    pub fn values(&self) -> Vec<i32> {
+        let mut params = self.clone();
+        params
+            .entries_mut()
+            .into_iter()
+            .flat_map(|(_, values)| values.into_iter().map(|value| *value))
+            .collect()
+    }
+
+    pub fn set_values(&mut self, values: &[i32]) {
+        let mut values = values.iter();
+        for (_, entry) in self.entries_mut() {
+            for value in entry {
+                *value = *values.next().expect("not enough parameter values");
+            }
+        }
+    }
+
+    /// loads a parameter file, parameters missing from the file keep their default value
+    pub fn load(path: impl AsRef<Path>) -> Result<EvalParams, Box<dyn Error>> {
+        Ok(fs::read_to_string(path)?.parse()?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+/// parameter file format: the name of a parameter followed by its values, everything after a
+/// `#` is a comment
+impl FromStr for EvalParams {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<EvalParams, String> {
+        let mut params = EvalParams::default();
+        let mut entries = params.entries_mut();
+        let mut tokens = s
+            .lines()
+            .flat_map(|line| line.split('#').next().unwrap().split_whitespace());
+
+        while let Some(name) = tokens.next() {
+            let Some((_, values)) = entries.iter_mut().find(|(entry, _)| *entry == name) else {
+                return Err(format!("unknown parameter {}", name));
+            };
+
+            for value in values.iter_mut() {
+                let token = tokens
+                    .next()
+                    .ok_or(format!("missing values for parameter {}", name))?;
+                **value = token
+                    .parse()
+                    .map_err(|_| format!("invalid value {} for parameter {}", token, name))?;
+            }
+        }
+
+        Ok(params)
+    }
+}
+
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = self.clone();
+        for (name, values) in params.entries_mut() {
+            // tables are written as one rank per line
+            if values.len() == 128 {
+                writeln!(f, "{}", name)?;
+                for rank in values.chunks(16) {
+                    for score in rank.chunks(2) {
+                        write!(f, "  {:>4} {:>4}", score[0], score[1])?;
+                    }
+                    writeln!(f)?;
+                }
+            } else {
+                write!(f, "{}", name)?;
+                for value in values {
+                    write!(f, " {}", value)?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
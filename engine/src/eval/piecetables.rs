@@ -9,17 +9,6 @@ pub const WHITE_PAWN_TABLE: [[i32; 8]; 8] = [
     [0, 0, 0, 0, 0, 0, 0, 0],
 ];
 
-pub const BLACK_PAWN_TABLE: [[i32; 8]; 8] = [
-    [0, 0, 0, 0, 0, 0, 0, 0],
-    [5, 10, 10, -20, -20, 10, 10, 5],
-    [5, -5, -10, 0, 0, -10, -5, 5],
-    [0, 0, 0, 20, 20, 0, 0, 0],
-    [5, 5, 10, 25, 25, 10, 5, 5],
-    [10, 10, 20, 30, 30, 20, 10, 10],
-    [50, 50, 50, 50, 50, 50, 50, 50],
-    [0, 0, 0, 0, 0, 0, 0, 0],
-];
-
 pub const WHITE_KNIGHT_TABLE: [[i32; 8]; 8] = [
     [-50, -40, -30, -30, -30, -30, -40, -50],
     [-40, -20, 0, 0, 0, 0, -20, -40],
@@ -31,17 +20,6 @@ pub const WHITE_KNIGHT_TABLE: [[i32; 8]; 8] = [
     [-50, -40, -30, -30, -30, -30, -40, -50],
 ];
 
-pub const BLACK_KNIGHT_TABLE: [[i32; 8]; 8] = [
-    [-50, -40, -30, -30, -30, -30, -40, -50],
-    [-40, -20, 0, 0, 0, 0, -20, -40],
-    [-30, 5, 10, 15, 15, 10, 5, -30],
-    [-30, 0, 15, 20, 20, 15, 0, -30],
-    [-30, 5, 15, 20, 20, 15, 5, -30],
-    [-30, 0, 10, 15, 15, 10, 0, -30],
-    [-40, -20, 0, 5, 5, 0, -20, -40],
-    [-50, -40, -30, -30, -30, -30, -40, -50],
-];
-
 pub const WHITE_BISHOP_TABLE: [[i32; 8]; 8] = [
     [-20, -10, -10, -10, -10, -10, -10, -20],
     [-10, 0, 0, 0, 0, 0, 0, -10],
@@ -53,17 +31,6 @@ pub const WHITE_BISHOP_TABLE: [[i32; 8]; 8] = [
     [-20, -10, -10, -10, -10, -10, -10, -20],
 ];
 
-pub const BLACK_BISHOP_TABLE: [[i32; 8]; 8] = [
-    [-20, -10, -10, -10, -10, -10, -10, -20],
-    [-10, 5, 0, 0, 0, 0, 5, -10],
-    [-10, 10, 10, 10, 10, 10, 10, -10],
-    [-10, 0, 10, 10, 10, 10, 0, -10],
-    [-10, 5, 5, 10, 10, 5, 5, -10],
-    [-10, 0, 5, 10, 10, 5, 0, -10],
-    [-10, 0, 0, 0, 0, 0, 0, -10],
-    [-20, -10, -10, -10, -10, -10, -10, -20],
-];
-
 pub const WHITE_ROOK_TABLE: [[i32; 8]; 8] = [
     [0, 0, 0, 0, 0, 0, 0, 0],
     [5, 10, 10, 10, 10, 10, 10, 5],
@@ -75,17 +42,6 @@ pub const WHITE_ROOK_TABLE: [[i32; 8]; 8] = [
     [0, 0, 0, 5, 5, 0, 0, 0],
 ];
 
-pub const BLACK_ROOK_TABLE: [[i32; 8]; 8] = [
-    [0, 0, 0, 5, 5, 0, 0, 0],
-    [-5, 0, 0, 0, 0, 0, 0, -5],
-    [-5, 0, 0, 0, 0, 0, 0, -5],
-    [-5, 0, 0, 0, 0, 0, 0, -5],
-    [-5, 0, 0, 0, 0, 0, 0, -5],
-    [-5, 0, 0, 0, 0, 0, 0, -5],
-    [5, 10, 10, 10, 10, 10, 10, 5],
-    [0, 0, 0, 0, 0, 0, 0, 0],
-];
-
 pub const WHITE_QUEEN_TABLE: [[i32; 8]; 8] = [
     [-20, -10, -10, -5, -5, -10, -10, -20],
     [-10, 0, 0, 0, 0, 0, 0, -10],
@@ -97,17 +53,6 @@ pub const WHITE_QUEEN_TABLE: [[i32; 8]; 8] = [
     [-20, -10, -10, -5, -5, -10, -10, -20],
 ];
 
-pub const BLACK_QUEEN_TABLE: [[i32; 8]; 8] = [
-    [-20, -10, -10, -5, -5, -10, -10, -20],
-    [-10, 0, 5, 0, 0, 0, 0, -10],
-    [-10, 5, 5, 5, 5, 5, 0, -10],
-    [0, 0, 5, 5, 5, 5, 0, -5],
-    [-5, 0, 5, 5, 5, 5, 0, -5],
-    [-10, 0, 5, 5, 5, 5, 0, -10],
-    [-10, 0, 0, 0, 0, 0, 0, -10],
-    [-20, -10, -10, -5, -5, -10, -10, -20],
-];
-
 pub const WHITE_MIDGAME_KING_TABLE: [[i32; 8]; 8] = [
     [-30, -40, -40, -50, -50, -40, -40, -30],
     [-30, -40, -40, -50, -50, -40, -40, -30],
@@ -119,17 +64,6 @@ pub const WHITE_MIDGAME_KING_TABLE: [[i32; 8]; 8] = [
     [20, 30, 10, 0, 0, 10, 30, 20],
 ];
 
-pub const BLACK_MIDGAME_KING_TABLE: [[i32; 8]; 8] = [
-    [20, 30, 10, 0, 0, 10, 30, 20],
-    [20, 20, 0, 0, 0, 0, 20, 20],
-    [-10, -20, -20, -20, -20, -20, -20, -10],
-    [-20, -30, -30, -40, -40, -30, -30, -20],
-    [-30, -40, -40, -50, -50, -40, -40, -30],
-    [-30, -40, -40, -50, -50, -40, -40, -30],
-    [-30, -40, -40, -50, -50, -40, -40, -30],
-    [-30, -40, -40, -50, -50, -40, -40, -30],
-];
-
 pub const WHITE_ENDGAME_KING_TABLE: [[i32; 8]; 8] = [
     [-50, -40, -30, -20, -20, -30, -40, -50],
     [-30, -20, -10, 0, 0, -10, -20, -30],
@@ -141,13 +75,57 @@ pub const WHITE_ENDGAME_KING_TABLE: [[i32; 8]; 8] = [
     [-50, -30, -30, -30, -30, -30, -30, -50],
 ];
 
-pub const BLACK_ENDGAME_KING_TABLE: [[i32; 8]; 8] = [
-    [-50, -30, -30, -30, -30, -30, -30, -50],
-    [-30, -30, 0, 0, 0, 0, -30, -30],
-    [-30, -10, 20, 30, 30, 20, -10, -30],
-    [-30, -10, 30, 40, 40, 30, -10, -30],
-    [-30, -10, 30, 40, 40, 30, -10, -30],
-    [-30, -10, 20, 30, 30, 20, -10, -30],
-    [-30, -20, -10, 0, 0, -10, -20, -30],
-    [-50, -40, -30, -20, -20, -30, -40, -50],
+pub const WHITE_ENDGAME_PAWN_TABLE: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [60, 60, 55, 50, 50, 55, 60, 60],
+    [35, 35, 30, 25, 25, 30, 35, 35],
+    [15, 15, 10, 10, 10, 10, 15, 15],
+    [5, 5, 5, 0, 0, 5, 5, 5],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+pub const WHITE_ENDGAME_KNIGHT_TABLE: [[i32; 8]; 8] = [
+    [-40, -30, -20, -20, -20, -20, -30, -40],
+    [-30, -15, -5, 0, 0, -5, -15, -30],
+    [-20, -5, 5, 10, 10, 5, -5, -20],
+    [-20, 0, 10, 15, 15, 10, 0, -20],
+    [-20, 0, 10, 15, 15, 10, 0, -20],
+    [-20, -5, 5, 10, 10, 5, -5, -20],
+    [-30, -15, -5, 0, 0, -5, -15, -30],
+    [-40, -30, -20, -20, -20, -20, -30, -40],
+];
+
+pub const WHITE_ENDGAME_BISHOP_TABLE: [[i32; 8]; 8] = [
+    [-15, -10, -10, -10, -10, -10, -10, -15],
+    [-10, -5, 0, 0, 0, 0, -5, -10],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-10, 0, 5, 10, 10, 5, 0, -10],
+    [-10, 0, 5, 10, 10, 5, 0, -10],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-10, -5, 0, 0, 0, 0, -5, -10],
+    [-15, -10, -10, -10, -10, -10, -10, -15],
+];
+
+pub const WHITE_ENDGAME_ROOK_TABLE: [[i32; 8]; 8] = [
+    [5, 5, 5, 5, 5, 5, 5, 5],
+    [10, 10, 10, 10, 10, 10, 10, 10],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [-5, 0, 0, 0, 0, 0, 0, -5],
+];
+
+pub const WHITE_ENDGAME_QUEEN_TABLE: [[i32; 8]; 8] = [
+    [-20, -10, -10, -5, -5, -10, -10, -20],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-10, 5, 10, 10, 10, 10, 5, -10],
+    [-5, 5, 10, 15, 15, 10, 5, -5],
+    [-5, 5, 10, 15, 15, 10, 5, -5],
+    [-10, 5, 10, 10, 10, 10, 5, -10],
+    [-10, 0, 5, 5, 5, 5, 0, -10],
+    [-20, -10, -10, -5, -5, -10, -10, -20],
 ];
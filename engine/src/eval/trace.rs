@@ -0,0 +1,166 @@
+use std::fmt;
+
+use game::{Bitboard, Board, Color, Piece, PieceType, Square};
+
+use super::{Score, Term, Terms, TERMS};
+use crate::Engine;
+
+/// breakdown of the hand crafted evaluation, returned by `Engine::trace_evaluation`
+#[derive(Debug, Clone)]
+pub struct EvalTrace {
+    pub white: Terms,
+    pub black: Terms,
+    // 0 is the opening, 256 is a bare endgame
+    pub phase: i32,
+    // same as `Engine::evaluate_hce`, from the perspective of the side to move
+    pub eval: i32,
+    pub turn: Color,
+    pub pieces: [[Option<Piece>; 8]; 8],
+    // how much each piece adds to the evaluation from white's perspective, none for empty
+    // squares and kings
+    pub squares: [[Option<i32>; 8]; 8],
+}
+
+impl EvalTrace {
+    /// sum of the terms from white's perspective, before tapering
+    pub fn total(&self) -> Score {
+        self.white.total() - self.black.total()
+    }
+}
+
+impl Engine {
+    pub fn trace_evaluation(&self) -> EvalTrace {
+        let phase = self.phase(self.count_material());
+        let mut white = self.evaluate_side(Color::White);
+        let mut black = self.evaluate_side(Color::Black);
+        if self.board.turn == Color::White {
+            white[Term::Tempo] = self.eval_params.tempo;
+        } else {
+            black[Term::Tempo] = self.eval_params.tempo;
+        }
+
+        EvalTrace {
+            white,
+            black,
+            phase,
+            eval: self.evaluate_hce(),
+            turn: self.board.turn,
+            pieces: self.board.board,
+            squares: self.piece_contributions(),
+        }
+    }
+
+    // the difference in evaluation when each piece is taken off the board
+    fn piece_contributions(&self) -> [[Option<i32>; 8]; 8] {
+        let white_eval = |board: Board| {
+            let mut engine = Engine::new(board);
+            engine.eval_params = self.eval_params.clone();
+            let eval = engine.evaluate_hce();
+            if board.turn == Color::White {
+                eval
+            } else {
+                -eval
+            }
+        };
+        let base = white_eval(self.board);
+
+        let mut squares = [[None; 8]; 8];
+        for square in Bitboard(self.board.white.all | self.board.black.all) {
+            if self.board.piece_at(square).map(Piece::piece_type) == Some(PieceType::King) {
+                continue;
+            }
+
+            let mut board = self.board;
+            remove_piece(&mut board, square);
+            let (r, f) = square.mailbox();
+            squares[r][f] = Some(base - white_eval(board));
+        }
+
+        squares
+    }
+}
+
+// only updates what the evaluation looks at, so the hash is left as is
+fn remove_piece(board: &mut Board, square: Square) {
+    let Some(piece) = board.piece_at(square) else {
+        return;
+    };
+    let bitboards = if piece.color() == Color::White {
+        &mut board.white
+    } else {
+        &mut board.black
+    };
+    let mask = !square.bitboard().0;
+
+    *bitboards.get_mut(piece.piece_type()) &= mask;
+    bitboards.all &= mask;
+    let (r, f) = square.mailbox();
+    board.board[r][f] = None;
+}
+
+const BOARD_SEPARATOR: &str = " +-------+-------+-------+-------+-------+-------+-------+-------+";
+const FILES: &str = "     a       b       c       d       e       f       g       h";
+const TERMS_HEADER: &str = "      Term      |    White    |    Black    |    Total
+                |   MG    EG  |   MG    EG  |   MG    EG";
+const TERMS_SEPARATOR: &str = " ---------------+-------------+-------------+------------";
+
+fn piece_char(piece: Option<Piece>) -> char {
+    piece.map_or(' ', Piece::to_char)
+}
+
+fn pawns(cp: i32) -> String {
+    format!("{:+.2}", cp as f64 / 100.0)
+}
+
+// one line of the terms table, the total is white's score minus black's
+fn term_row(f: &mut fmt::Formatter<'_>, name: &str, white: Score, black: Score) -> fmt::Result {
+    let total = white - black;
+    let scores = [white, black, total].map(|score| format!("{:>5} {:>5}", score.0, score.1));
+    writeln!(f, " {:>14} | {}", name, scores.join(" | "))
+}
+
+impl fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Contributing pieces (in pawns, from white's perspective):\n"
+        )?;
+        writeln!(f, "{}", BOARD_SEPARATOR)?;
+        for r in 0..8 {
+            write!(f, " |")?;
+            for piece in self.pieces[r] {
+                write!(f, "   {}   |", piece_char(piece))?;
+            }
+            writeln!(f, " {}", 8 - r)?;
+
+            write!(f, " |")?;
+            for value in self.squares[r] {
+                let value = value.map_or(String::new(), pawns);
+                write!(f, "{:^7}|", value)?;
+            }
+            writeln!(f, "\n{}", BOARD_SEPARATOR)?;
+        }
+        writeln!(f, "{}\n", FILES)?;
+
+        writeln!(f, "{}\n{}", TERMS_HEADER, TERMS_SEPARATOR)?;
+        for term in TERMS {
+            term_row(f, term.name(), self.white[term], self.black[term])?;
+        }
+        writeln!(f, "{}", TERMS_SEPARATOR)?;
+        term_row(f, "Total", self.white.total(), self.black.total())?;
+        writeln!(f)?;
+
+        let white_eval = if self.turn.is_white() {
+            self.eval
+        } else {
+            -self.eval
+        };
+        writeln!(f, "Phase: {} (0 = opening, 256 = endgame)", self.phase)?;
+        writeln!(f, "Final evaluation: {} cp (white side)", white_eval)?;
+        write!(
+            f,
+            "Final evaluation: {} cp (side to move: {})",
+            self.eval, self.turn
+        )
+    }
+}
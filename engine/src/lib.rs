@@ -1,9 +1,18 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
-use game::{Board, Move};
+use eval::EvalParams;
+#[cfg(feature = "nnue")]
+use game::nnue::{AccumulatorStack, Network};
+use game::{move16::Move16, Board, Move};
+use search::Iteration;
 
+pub mod bench;
 pub mod eval;
 pub mod search;
+pub mod tune;
 
 #[derive(Debug, Clone)]
 pub struct PvNode {
@@ -34,30 +43,90 @@ pub struct TTEntry {
     pub eval: i32,
     pub depth: u8,
     pub flag: TTEntryFlag,
-    pub pv: PvNode,
+    // none if no move raised alpha
+    pub best_move: Option<Move16>,
+}
+
+// search features that can be toggled/tuned through uci options
+#[derive(Debug, Clone, Copy)]
+pub struct SearchOptions {
+    pub null_move_pruning: bool,
+    // null move reduction is base + depth / divisor
+    pub null_move_base_reduction: u8,
+    pub null_move_depth_divisor: u8,
+    pub principal_variation_search: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            null_move_pruning: true,
+            null_move_base_reduction: 2,
+            null_move_depth_divisor: 6,
+            principal_variation_search: true,
+        }
+    }
 }
 
 pub struct Engine {
     // none if move has not been found yet, otherwise Some()
     pub best_move: Option<Move>,
+    // score of the last completed iteration, from the perspective of the side to move
+    pub best_eval: i32,
     pub board: Board,
     pub transposition_table: HashMap<u64, TTEntry>,
     pub repetition_table: Vec<u64>,
     pub nodes_searched: u64,
     pub canceled: bool,
     pub highest_depth: u8,
+    pub options: SearchOptions,
+    pub eval_params: EvalParams,
+    // every completed iteration of the last search
+    pub iterations: Vec<Iteration>,
+    // two killer moves for every ply
+    pub killers: Vec<[Option<Move16>; 2]>,
+
+    // time control for the current search
+    pub time_limit: bool,
+    pub start_time: Instant,
+    pub alloted_time: Duration,
+    // the search stops after this many nodes
+    pub node_limit: Option<u64>,
+    // dont print search info, used when the engine isnt talking to a gui
+    pub quiet: bool,
+    // the accumulators of the search path, none to use the hand crafted evaluation
+    #[cfg(feature = "nnue")]
+    pub nnue: Option<AccumulatorStack>,
 }
 
 impl Engine {
     pub fn new(board: Board) -> Engine {
         Engine {
             best_move: None,
+            best_eval: 0,
             board,
             transposition_table: HashMap::new(),
             repetition_table: Vec::new(),
             nodes_searched: 0,
             canceled: false,
             highest_depth: 0,
+            options: SearchOptions::default(),
+            eval_params: EvalParams::default(),
+            iterations: Vec::new(),
+            killers: Vec::new(),
+            time_limit: false,
+            start_time: Instant::now(),
+            alloted_time: Duration::from_secs(0),
+            node_limit: None,
+            quiet: false,
+            #[cfg(feature = "nnue")]
+            nnue: None,
         }
     }
+
+    /// evaluates with the network from now on, none to go back to the hand crafted evaluation
+    #[cfg(feature = "nnue")]
+    pub fn set_network(&mut self, network: Option<&'static Network>) {
+        self.nnue = network.map(|network| AccumulatorStack::new(network, &self.board));
+    }
 }
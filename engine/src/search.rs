@@ -1,10 +1,9 @@
-use game::{get_piece_type, Move, BISHOP, BLACK, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+use game::{move16::Move16, Move, UndoInfo, Variant};
+use movegen::{has_moves, picker::MovePicker, Attacks, GenType};
 use std::{
     cmp::{max, min}, sync::mpsc::{Receiver, TryRecvError}, time::{Duration, Instant}
 };
 
-use crate::eval::{BISHOP_VALUE, KNIGHT_VALUE, PAWN_VALUE, QUEEN_VALUE, ROOK_VALUE};
-
 use super::{
     Engine, PvNode, TTEntry,
     TTEntryFlag::{Exact, LowerBound, UpperBound},
@@ -14,6 +13,104 @@ pub const MIN: i32 = -100_000_000;
 pub const MAX: i32 = 100_000_000;
 
 pub const CHECKMATE: i32 = -10_000_000;
+// any score above this (or below the negative) is a forced mate
+pub const MATE_THRESHOLD: i32 = -CHECKMATE - 1000;
+
+// https://www.chessprogramming.org/Aspiration_Windows
+pub const ASPIRATION_WINDOW: i32 = 25;
+// after this many failed searches, the next search uses the full window
+pub const ASPIRATION_MAX_FAILS: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // the search failed high, the real score is at least this
+    Lower,
+    // the search failed low, the real score is at most this
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AspirationWindow {
+    pub alpha: i32,
+    pub beta: i32,
+    delta: i32,
+    fails: u8,
+}
+
+impl AspirationWindow {
+    // centers the window around the score of the previous iteration, mate scores are unstable
+    // so they (and the first iteration) are searched with the full window
+    pub fn new(prev_eval: Option<i32>) -> AspirationWindow {
+        match prev_eval {
+            Some(eval) if eval.abs() < MATE_THRESHOLD => AspirationWindow {
+                alpha: max(eval - ASPIRATION_WINDOW, MIN),
+                beta: min(eval + ASPIRATION_WINDOW, MAX),
+                delta: ASPIRATION_WINDOW,
+                fails: 0,
+            },
+            _ => AspirationWindow::full(),
+        }
+    }
+
+    pub fn full() -> AspirationWindow {
+        AspirationWindow {
+            alpha: MIN,
+            beta: MAX,
+            delta: ASPIRATION_WINDOW,
+            fails: 0,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.alpha == MIN && self.beta == MAX
+    }
+
+    // classifies the result of a search with this window, and widens the window if the
+    // search has to be repeated
+    pub fn update(&mut self, eval: i32) -> Bound {
+        let bound = if eval <= self.alpha && self.alpha > MIN {
+            Bound::Upper
+        } else if eval >= self.beta && self.beta < MAX {
+            Bound::Lower
+        } else {
+            return Bound::Exact;
+        };
+
+        self.fails += 1;
+        self.delta += self.delta / 2;
+
+        if self.fails >= ASPIRATION_MAX_FAILS || eval.abs() >= MATE_THRESHOLD {
+            *self = AspirationWindow::full();
+            return bound;
+        }
+
+        match bound {
+            Bound::Upper => {
+                // pull beta down as well, the score is likely to keep dropping
+                self.beta = (self.alpha + self.beta) / 2;
+                self.alpha = max(eval - self.delta, MIN);
+            }
+            Bound::Lower => {
+                self.beta = min(eval + self.delta, MAX);
+            }
+            Bound::Exact => {}
+        }
+
+        bound
+    }
+}
+
+// result of one completed iteration of iterative deepening
+#[derive(Debug, Clone, Copy)]
+pub struct Iteration {
+    pub depth: u8,
+    pub best_move: Option<Move>,
+    pub eval: i32,
+    // since the start of the search
+    pub time: Duration,
+    pub nodes: u64,
+}
 
 impl Engine {
     pub fn iterative_deepening_search(
@@ -24,130 +121,66 @@ impl Engine {
         alloted_time: Duration,
         rx: Option<Receiver<bool>>,
     ) -> Option<Move> {
+        self.time_limit = time_limit;
+        self.start_time = start_time;
+        self.alloted_time = alloted_time;
+        self.nodes_searched = 0;
+        self.best_move = None;
+        self.iterations.clear();
+        self.killers.clear();
+        // the board may have been changed since the last search
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &mut self.nnue {
+            nnue.refresh(&self.board);
+        }
+
         let mut search_depth = 1;
-        let mut alpha = MIN;
-        let mut beta = MAX;
         let mut prev_eval = None;
-        let window = 25;
 
         while search_depth <= depth {
-            let start = Instant::now();
-            self.nodes_searched = 0;
             self.highest_depth = 0;
 
-            if let Some(eval) = prev_eval {
-                alpha = eval - window;
-                beta = eval + window;
-            }
-
-            let mut result = self.negamax(
-                search_depth,
-                0,
-                alpha,
-                beta,
-                PvNode::new(None),
-                time_limit,
-                start_time,
-                alloted_time,
-                &rx,
-            );
-            let end = Instant::now();
-            let mut dur = end - start;
-            let mut eval = result.0;
-
-            let mut delta = 20;
-            loop {
-                if eval <= alpha {
-                    alpha -= delta;
-                } else if eval >= beta {
-                    beta += delta;
-                } else {
-                    break;
-                }
-
-                result = self.negamax(
+            let mut window = AspirationWindow::new(prev_eval);
+            let (eval, pv) = loop {
+                let (eval, pv) = self.negamax(
                     search_depth,
                     0,
-                    alpha,
-                    beta,
+                    window.alpha,
+                    window.beta,
                     PvNode::new(None),
-                    time_limit,
-                    start_time,
-                    alloted_time,
                     &rx,
                 );
-                let end = Instant::now();
-                dur = end - start;
-                eval = result.0;
-
-                delta += delta / 3
-            }
-
-            prev_eval = Some(eval);
-
-            if eval > 1000000 {
-                print!(
-                    "info depth {} seldepth {} score mate {} time {} nodes {} nps {} pv",
-                    search_depth,
-                    self.highest_depth,
-                    -CHECKMATE - eval,
-                    dur.as_millis(),
-                    self.nodes_searched,
-                    (1_000_000.0 * self.nodes_searched as f64 / dur.as_micros() as f64) as u64
-                );
 
-                while let Some(pvn) = result.1.next {
-                    if let Some(m) = pvn.best_move {
-                        print!(" {}", m);
-                    } else {
-                        break;
-                    }
-
-                    result.1 = (*pvn).clone();
+                if self.should_stop(&rx) {
+                    break (eval, pv);
                 }
-                println!();
-                return self.best_move;
-            }
-
-            print!(
-                "info depth {} seldepth {} score cp {} time {} nodes {} nps {} pv",
-                search_depth,
-                self.highest_depth,
-                eval,
-                dur.as_millis(),
-                self.nodes_searched,
-                (1_000_000.0 * self.nodes_searched as f64 / dur.as_micros() as f64) as u64
-            );
 
-            while let Some(pvn) = result.1.next {
-                    if let Some(m) = pvn.best_move {
-                        print!(" {}", m);
-                    } else {
-                        break;
-                    }
-
-                    result.1 = (*pvn).clone();
+                match window.update(eval) {
+                    Bound::Exact => break (eval, pv),
+                    bound => self.print_info(search_depth, eval, bound, None),
                 }
-                println!();
+            };
 
-            if self.canceled {
+            // an unfinished iteration is not reliable
+            if self.should_stop(&rx) {
+                if search_depth == 1 {
+                    self.best_eval = eval;
+                }
                 return self.best_move;
             }
 
-            if let Some(ref rcv) = rx {
-                match rcv.try_recv() {
-                    Ok(canceled) => {
-                        if canceled {
-                            self.canceled = true;
-                            return self.best_move;
-                        }
-                    }
-                    Err(TryRecvError::Empty) => {}
-                    Err(TryRecvError::Disconnected) => panic!("disconnected"),
-                }
-            }
+            prev_eval = Some(eval);
+            self.best_eval = eval;
+            self.iterations.push(Iteration {
+                depth: search_depth,
+                best_move: self.best_move,
+                eval,
+                time: self.start_time.elapsed(),
+                nodes: self.nodes_searched,
+            });
+            self.print_info(search_depth, eval, Bound::Exact, Some(pv));
 
-            if time_limit && Instant::now() - start_time > alloted_time {
+            if eval >= MATE_THRESHOLD {
                 return self.best_move;
             }
 
@@ -157,6 +190,51 @@ impl Engine {
         self.best_move
     }
 
+    fn print_info(&self, depth: u8, eval: i32, bound: Bound, pv: Option<PvNode>) {
+        if self.quiet {
+            return;
+        }
+
+        let time = self.start_time.elapsed();
+        let score = if eval >= MATE_THRESHOLD {
+            // uci mate scores are in moves, not plies
+            format!("mate {}", (-CHECKMATE - eval + 1) / 2)
+        } else if eval <= -MATE_THRESHOLD {
+            format!("mate -{}", (eval - CHECKMATE + 1) / 2)
+        } else {
+            format!("cp {}", eval)
+        };
+
+        print!(
+            "info depth {} seldepth {} score {}{} time {} nodes {} nps {}",
+            depth,
+            self.highest_depth,
+            score,
+            match bound {
+                Bound::Exact => "",
+                Bound::Lower => " lowerbound",
+                Bound::Upper => " upperbound",
+            },
+            time.as_millis(),
+            self.nodes_searched,
+            (1_000_000.0 * self.nodes_searched as f64 / max(time.as_micros(), 1) as f64) as u64
+        );
+
+        if let Some(mut pv) = pv {
+            print!(" pv");
+            while let Some(pvn) = pv.next {
+                if let Some(m) = pvn.best_move {
+                    print!(" {}", m);
+                } else {
+                    break;
+                }
+
+                pv = *pvn;
+            }
+        }
+        println!();
+    }
+
     pub fn negamax(
         &mut self,
         depth: u8,
@@ -164,9 +242,6 @@ impl Engine {
         mut alpha: i32,
         mut beta: i32,
         mut pv: PvNode,
-        time_limit: bool,
-        start_time: Instant,
-        alloted_time: Duration,
         rx: &Option<Receiver<bool>>,
     ) -> (i32, PvNode) {
         self.nodes_searched += 1;
@@ -182,25 +257,13 @@ impl Engine {
             return (0, pv);
         }
 
-        if time_limit && Instant::now() - start_time > alloted_time {
+        if self.should_stop(rx) {
             return (0, pv);
         }
 
-        if self.canceled {
-            return (0, pv);
-        }
-
-        if let Some(rcv) = rx {
-            match rcv.try_recv() {
-                Ok(canceled) => {
-                    if canceled {
-                        self.canceled = true;
-                        return (0, pv);
-                    }
-                }
-                Err(TryRecvError::Empty) => {}
-                Err(TryRecvError::Disconnected) => panic!("disconnected"),
-            }
+        // the opponent already won by a rule of the variant (king of the hill, three checks)
+        if self.board.is_variant_loss() {
+            return (CHECKMATE + depth_from_root as i32, pv);
         }
 
         if depth_from_root > 0 {
@@ -215,6 +278,9 @@ impl Engine {
 
         let mut hash_move = None;
         if let Some(entry) = self.transposition_table.get(&self.board.hash) {
+            // the best move is worth searching first even if the entry is too shallow for a cutoff
+            hash_move = entry.best_move.and_then(|m| m.to_move(&self.board));
+
             if entry.depth >= depth {
                 match entry.flag {
                     Exact => {
@@ -227,12 +293,6 @@ impl Engine {
                 if alpha >= beta {
                     return (entry.eval, pv);
                 }
-
-                hash_move = entry.pv.best_move;
-            }
-
-            if depth_from_root == 0 {
-                hash_move = entry.pv.best_move;
             }
         }
 
@@ -240,45 +300,97 @@ impl Engine {
             return (self.quiet_search(alpha, beta, depth_from_root + 1), pv);
         }
 
-        let in_check = if self.board.turn {
-            movegen::is_in_check(&self.board, WHITE, self.board.white_king_position)
-        } else {
-            movegen::is_in_check(&self.board, BLACK, self.board.black_king_position)
-        };
-
-        let mut moves = movegen::generate_legal_moves(&mut self.board, false);
-
-        if moves.is_empty() {
-            if in_check {
-                return (CHECKMATE + depth_from_root as i32, pv);
-            }
+        let in_check = self.board.checkers() != 0;
 
-            return (0, pv);
-        }
+        let static_eval = self.evaluate();
 
         // reverse futility pruning
-        if !in_check && depth <= 8 && self.evaluate() >= beta + 120 * depth as i32 {
+        // both prunings are only tried with a legal move to play, a stalemate (or an antichess
+        // win) would otherwise be scored by its static eval
+        if !in_check
+            && depth <= 8
+            && static_eval >= beta + 120 * depth as i32
+            && has_moves(&mut self.board, GenType::All)
+        {
             return (beta, pv);
         }
 
-        self.order_moves(&mut moves, hash_move);
+        // null move pruning
+        // https://www.chessprogramming.org/Null_Move_Pruning
+        // children of a null move are created with an empty pv node, so pv.best_move being None
+        // (outside of the root) means the previous move was a null move, two in a row are not allowed
+        if self.options.null_move_pruning
+            && depth_from_root > 0
+            && pv.best_move.is_some()
+            && depth >= 3
+            && !in_check
+            && static_eval >= beta
+            && self.has_non_pawn_material()
+            // passing is never allowed when a capture is compulsory
+            && self.board.variant != Variant::Antichess
+            && has_moves(&mut self.board, GenType::All)
+        {
+            // adaptive null move reduction, reduce more at higher depths
+            let reduction = self.options.null_move_base_reduction
+                + depth / max(self.options.null_move_depth_divisor, 1);
 
-        let mut value = MIN;
-        for (pos, m) in moves.into_iter().enumerate() {
-            let undo = self.board.make_move(m);
+            let undo = self.board.make_null_move();
             self.repetition_table.push(self.board.hash);
 
+            let eval = self.negamax(
+                depth.saturating_sub(1 + reduction),
+                depth_from_root + 1,
+                -beta,
+                -beta + 1,
+                PvNode::new(None),
+                rx,
+            );
+
+            self.repetition_table.pop();
+            self.board.unmake_null_move(undo);
+
+            if self.should_stop(rx) {
+                return (0, pv);
+            }
+
+            // dont trust mate scores from a null move search
+            let score = -eval.0;
+            if score >= beta {
+                return (if score >= MATE_THRESHOLD { beta } else { score }, pv);
+            }
+        }
+
+        let killers = self
+            .killers
+            .get(depth_from_root as usize)
+            .copied()
+            .unwrap_or_default();
+        let mut picker = MovePicker::new(hash_move, killers);
+
+        let mut value = MIN;
+        let mut best_move = None;
+        let mut pos = 0;
+        while let Some(m) = picker.next(&mut self.board) {
             //determine search extensions
             let mut extensions = 0;
-            if if self.board.turn {
-                //check extension (if move is a check, extend search depth by 1)
-                movegen::is_in_check(&self.board, WHITE, self.board.white_king_position)
-            } else {
-                movegen::is_in_check(&self.board, BLACK, self.board.black_king_position)
-            } {
+            //check extension (if move is a check, extend search depth by 1)
+            if self.board.gives_check(m) {
                 extensions += 1;
             }
 
+            let undo = self.make_move(m);
+            self.repetition_table.push(self.board.hash);
+
+            // with principal variation search, every move after the first is searched with a
+            // zero window and only re-searched with the full window if it beats alpha
+            // https://www.chessprogramming.org/Principal_Variation_Search
+            let pvs = self.options.principal_variation_search && pos > 0;
+            let (reduced_alpha, reduced_beta) = if pvs {
+                (-alpha - 1, -alpha)
+            } else {
+                (-beta, -alpha)
+            };
+
             let mut eval = (MIN, PvNode::new(None));
             let mut full_search = true;
 
@@ -289,16 +401,28 @@ impl Engine {
                 eval = self.negamax(
                     depth - 1 - reduction,
                     depth_from_root + 1,
-                    -beta,
-                    -alpha,
+                    reduced_alpha,
+                    reduced_beta,
                     PvNode::new(Some(m)),
-                    time_limit,
-                    start_time,
-                    alloted_time,
                     rx,
                 );
 
-                full_search = eval.0 > alpha;
+                full_search = -eval.0 > alpha;
+            }
+
+            if full_search && pvs {
+                eval = self.negamax(
+                    depth - 1 + extensions,
+                    depth_from_root + 1,
+                    reduced_alpha,
+                    reduced_beta,
+                    PvNode::new(Some(m)),
+                    rx,
+                );
+
+                // the zero window search only proves the move is at least as good as alpha, the
+                // real score is needed if it may be a new best move
+                full_search = -eval.0 > alpha && -eval.0 < beta;
             }
 
             if full_search {
@@ -308,39 +432,20 @@ impl Engine {
                     -beta,
                     -alpha,
                     PvNode::new(Some(m)),
-                    time_limit,
-                    start_time,
-                    alloted_time,
                     rx,
                 );
             }
             self.repetition_table.pop();
-            undo(&mut self.board);
+            self.unmake_move(m, undo);
             value = max(value, -eval.0);
 
-            if time_limit && Instant::now() - start_time > alloted_time {
-                return (value, pv);
-            }
-
-            if self.canceled {
+            if self.should_stop(rx) {
                 return (value, pv);
             }
 
-            if let Some(rcv) = rx {
-                match rcv.try_recv() {
-                    Ok(canceled) => {
-                        if canceled {
-                            self.canceled = true;
-                            return (value, pv);
-                        }
-                    }
-                    Err(TryRecvError::Empty) => {}
-                    Err(TryRecvError::Disconnected) => panic!("disconnected"),
-                }
-            }
-
             if value > alpha {
                 alpha = value;
+                best_move = Some(m);
                 pv.next = Some(Box::new(eval.1));
 
                 if depth_from_root == 0 {
@@ -349,8 +454,26 @@ impl Engine {
             }
 
             if alpha >= beta {
+                if m.capture_piece.is_none() {
+                    self.store_killer(m.into(), depth_from_root);
+                }
                 break;
             }
+
+            pos += 1;
+        }
+
+        // no legal moves
+        if value == MIN {
+            // running out of moves (or pieces) wins in antichess
+            if self.board.variant == Variant::Antichess {
+                return (-CHECKMATE - depth_from_root as i32, pv);
+            }
+            if in_check {
+                return (CHECKMATE + depth_from_root as i32, pv);
+            }
+
+            return (0, pv);
         }
 
         self.transposition_table.insert(
@@ -365,52 +488,113 @@ impl Engine {
                 } else {
                     Exact
                 },
-                pv: pv.clone(),
+                best_move: best_move.map(Move16::from),
             },
         );
 
         (value, pv)
     }
 
-    pub fn quiet_search(&mut self, mut alpha: i32, beta: i32, _depth_from_root: u8) -> i32 {
-        let eval = self.evaluate();
-        if eval >= beta {
-            return eval;
+    // makes the move on the board and on the nnue accumulators
+    fn make_move(&mut self, m: Move) -> UndoInfo {
+        let undo = self.board.make_move(m);
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &mut self.nnue {
+            nnue.push(&self.board, &m, &undo);
         }
+        undo
+    }
 
-        alpha = max(alpha, eval);
+    fn unmake_move(&mut self, m: Move, undo: UndoInfo) {
+        self.board.unmake_move(m, undo);
+        #[cfg(feature = "nnue")]
+        if let Some(nnue) = &mut self.nnue {
+            nnue.pop();
+        }
+    }
 
-        let mut captures = movegen::generate_legal_moves(&mut self.board, true);
+    // checks for time running out and for the stop command
+    fn should_stop(&mut self, rx: &Option<Receiver<bool>>) -> bool {
+        if self.time_limit && Instant::now() - self.start_time > self.alloted_time {
+            return true;
+        }
 
-        if captures.is_empty() {
-            return eval;
+        if self.canceled {
+            return true;
+        }
+
+        if self
+            .node_limit
+            .is_some_and(|limit| self.nodes_searched >= limit)
+        {
+            return true;
+        }
+
+        if let Some(rcv) = rx {
+            match rcv.try_recv() {
+                Ok(canceled) => {
+                    if canceled {
+                        self.canceled = true;
+                        return true;
+                    }
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => panic!("disconnected"),
+            }
+        }
+
+        false
+    }
+
+    // null move pruning fails in zugzwang, which is mostly a problem in pawn endings
+    fn has_non_pawn_material(&self) -> bool {
+        let bitboards = self.board.bitboards(self.board.turn);
+
+        bitboards.bishops | bitboards.knights | bitboards.rooks | bitboards.queens != 0
+    }
+
+    pub fn quiet_search(&mut self, mut alpha: i32, beta: i32, depth_from_root: u8) -> i32 {
+        if self.board.is_variant_loss() {
+            return CHECKMATE + depth_from_root as i32;
         }
 
-        self.order_moves(&mut captures, None);
+        let eval = self.evaluate();
+        let antichess = self.board.variant == Variant::Antichess;
+        // captures are compulsory in antichess, the side to move can only stand pat without one.
+        // the picker is made after the stand pat cutoff otherwise, since making it isnt free
+        let mut picker = antichess.then(MovePicker::captures);
+        let forced = picker
+            .as_mut()
+            .is_some_and(|picker| picker.has_captures(&mut self.board));
+        if !forced {
+            if eval >= beta {
+                return eval;
+            }
+
+            alpha = max(alpha, eval);
+        }
+
+        let mut picker = picker.unwrap_or_else(MovePicker::captures);
+        let mut any_captures = false;
+
+        while let Some(m) = picker.next(&mut self.board) {
+            any_captures = true;
 
-        for m in &captures {
             // https://www.chessprogramming.org/Delta_Pruning
             let margin = 200;
-            let delta = match get_piece_type!(m.capture_piece.unwrap()) {
-                PAWN => PAWN_VALUE,
-                BISHOP => BISHOP_VALUE,
-                KNIGHT => KNIGHT_VALUE,
-                ROOK => ROOK_VALUE,
-                QUEEN => QUEEN_VALUE,
-                _ => {
-                    panic!("{}\nwrong piece type!\n{}", self.board, m.to_uci());
-                }
-            };
+            let delta = m
+                .capture_piece
+                .map_or(0, |piece| Engine::get_piece_value(piece.piece_type()));
 
-            if eval + delta + margin < alpha {
+            if !forced && eval + delta + margin < alpha {
                 continue;
             }
 
-            let undo = self.board.make_move(*m);
+            let undo = self.make_move(m);
 
-            let eval = -self.quiet_search(-beta, -alpha, _depth_from_root + 1);
+            let eval = -self.quiet_search(-beta, -alpha, depth_from_root + 1);
 
-            undo(&mut self.board);
+            self.unmake_move(m, undo);
 
             if eval >= beta {
                 return eval;
@@ -419,26 +603,31 @@ impl Engine {
             alpha = max(alpha, eval);
         }
 
+        if !any_captures {
+            // running out of moves (or pieces) wins in antichess
+            if antichess && !has_moves(&mut self.board, GenType::All) {
+                return -CHECKMATE - depth_from_root as i32;
+            }
+
+            return eval;
+        }
+
         alpha
     }
 
-    pub fn order_moves(&self, moves: &mut [Move], hash_move: Option<Move>) {
-        moves.sort_by_key(|a| {
-            // search hash move first
-            if let Some(m) = hash_move {
-                if m == *a {
-                    return -10000000;
-                }
-            }
+    // quiet moves that caused a beta cutoff, they are tried early at the same ply in other parts
+    // of the tree
+    // https://www.chessprogramming.org/Killer_Heuristic
+    fn store_killer(&mut self, m: Move16, depth_from_root: u8) {
+        let ply = depth_from_root as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
 
-            match a.capture_piece {
-                Some(piece) => {
-                    Engine::get_piece_value(get_piece_type!(a.piece))
-                        - Engine::get_piece_value(get_piece_type!(piece))
-                    // order is opposite because sort_by_key sorts in ascending order
-                }
-                None => 0,
-            }
-        });
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(m) {
+            killers[1] = killers[0];
+            killers[0] = Some(m);
+        }
     }
 }
@@ -0,0 +1,236 @@
+// texel tuning of the evaluation parameters
+// https://www.chessprogramming.org/Texel%27s_Tuning_Method
+use std::{error::Error, fs, path::Path, thread};
+
+use game::{Bitboards, Board, BoardBuilder, CastleState, Color, Piece, Square};
+
+use crate::{
+    eval::EvalParams,
+    search::{MAX, MIN},
+    Engine,
+};
+
+/// a position from the training data together with the result of the game it was played in
+///
+/// only the parts of the board the quiescence search reads are stored. the tuner loads them into
+/// the board of one engine per thread, so the hash, the move counters and the variant state are
+/// never needed
+#[derive(Debug, Clone)]
+pub struct TuningPosition {
+    board: [[Option<Piece>; 8]; 8],
+    white: Bitboards,
+    black: Bitboards,
+    turn: Color,
+    castle_state: CastleState,
+    en_passant_square: Option<Square>,
+    white_king_position: Square,
+    black_king_position: Square,
+    // 1.0 if white won, 0.5 for a draw and 0.0 if black won
+    pub result: f64,
+}
+
+impl TuningPosition {
+    /// parses a FEN or EPD line annotated with the game result, for example
+    /// `<fen> [1.0]`, `<fen> [1-0]`, `<epd> c9 "1/2-1/2";` or `<fen> | <score> | 0.5`
+    pub fn parse(line: &str) -> Option<TuningPosition> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() < 4 {
+            return None;
+        }
+
+        // epd lines have no move counters
+        let counters = tokens[4..]
+            .iter()
+            .take(2)
+            .take_while(|tok| tok.parse::<u16>().is_ok())
+            .count();
+        let fen = format!(
+            "{} {} {} {} {} {}",
+            tokens[0],
+            tokens[1],
+            tokens[2],
+            tokens[3],
+            if counters > 0 { tokens[4] } else { "0" },
+            if counters > 1 { tokens[5] } else { "1" },
+        );
+        let result = parse_result(&tokens[4 + counters..].join(" "))?;
+
+        let board = Board::from_fen(&fen).ok()?;
+
+        Some(TuningPosition {
+            board: board.board,
+            white: board.white,
+            black: board.black,
+            turn: board.turn,
+            castle_state: board.castle_state,
+            en_passant_square: board.en_passant_square,
+            white_king_position: board.white_king_position,
+            black_king_position: board.black_king_position,
+            result,
+        })
+    }
+
+    fn load(&self, board: &mut Board) {
+        board.board = self.board;
+        board.white = self.white;
+        board.black = self.black;
+        board.turn = self.turn;
+        board.castle_state = self.castle_state;
+        board.en_passant_square = self.en_passant_square;
+        board.white_king_position = self.white_king_position;
+        board.black_king_position = self.black_king_position;
+        board.check_info = None;
+    }
+}
+
+// result from white's perspective
+fn parse_result(annotation: &str) -> Option<f64> {
+    if annotation.contains("1/2-1/2") {
+        return Some(0.5);
+    }
+    if annotation.contains("1-0") {
+        return Some(1.0);
+    }
+    if annotation.contains("0-1") {
+        return Some(0.0);
+    }
+
+    // [1.0] or the last field of a "fen | score | result" line
+    let result = annotation
+        .trim_matches(|c: char| c == '[' || c == ']' || c == ';' || c.is_whitespace())
+        .rsplit(['|', '['])
+        .next()?
+        .trim_matches(|c: char| c == ']' || c.is_whitespace())
+        .parse::<f64>()
+        .ok()?;
+
+    (0.0..=1.0).contains(&result).then_some(result)
+}
+
+/// reads every position from a data file, lines that cant be parsed are skipped
+pub fn load_positions(path: impl AsRef<Path>) -> Result<Vec<TuningPosition>, Box<dyn Error>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(TuningPosition::parse)
+        .collect())
+}
+
+// expected score of white, given an evaluation in centipawns
+fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+pub struct Tuner {
+    pub positions: Vec<TuningPosition>,
+    pub threads: usize,
+    // scaling constant of the sigmoid, see `Tuner::optimise_k`
+    pub k: f64,
+}
+
+impl Tuner {
+    pub fn new(positions: Vec<TuningPosition>, threads: usize) -> Tuner {
+        Tuner {
+            positions,
+            threads: threads.max(1),
+            k: 1.0,
+        }
+    }
+
+    /// mean squared error between the game results and the quiescence scores of the positions
+    pub fn error(&self, params: &EvalParams) -> f64 {
+        if self.positions.is_empty() {
+            return 0.0;
+        }
+
+        let chunk_size = self.positions.len().div_ceil(self.threads);
+        let template = BoardBuilder::new().build();
+
+        let total: f64 = thread::scope(|s| {
+            let handles: Vec<_> = self
+                .positions
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        let mut engine = Engine::new(template);
+                        engine.eval_params = params.clone();
+
+                        chunk
+                            .iter()
+                            .map(|position| {
+                                position.load(&mut engine.board);
+                                let eval = engine.quiet_search(MIN, MAX, 0);
+                                let eval = if position.turn.is_white() {
+                                    eval
+                                } else {
+                                    -eval
+                                };
+
+                                (position.result - sigmoid(eval as f64, self.k)).powi(2)
+                            })
+                            .sum::<f64>()
+                    })
+                })
+                .collect();
+
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+
+        total / self.positions.len() as f64
+    }
+
+    /// finds the sigmoid scaling constant that fits the untuned evaluation best, so the tuner
+    /// doesnt just scale all parameters up or down
+    pub fn optimise_k(&mut self, params: &EvalParams) -> f64 {
+        let (mut low, mut high) = (0.0, 10.0);
+
+        // ternary search, the error is convex in k
+        for _ in 0..30 {
+            let third = (high - low) / 3.0;
+
+            self.k = low + third;
+            let low_error = self.error(params);
+            self.k = high - third;
+            let high_error = self.error(params);
+
+            if low_error < high_error {
+                high -= third;
+            } else {
+                low += third;
+            }
+        }
+
+        self.k = (low + high) / 2.0;
+        self.k
+    }
+
+    /// one pass of local search over every parameter, each one is moved by `step` in both
+    /// directions and the change is kept if it lowers the error
+    ///
+    /// returns the new error and the number of parameters that changed
+    pub fn local_search(&self, params: &mut EvalParams, step: i32) -> (f64, usize) {
+        let mut values = params.values();
+        let mut best_error = self.error(params);
+        let mut improved = 0;
+
+        for i in 0..values.len() {
+            let original = values[i];
+
+            for delta in [step, -step] {
+                values[i] = original + delta;
+                params.set_values(&values);
+
+                let error = self.error(params);
+                if error < best_error {
+                    best_error = error;
+                    improved += 1;
+                    break;
+                }
+
+                values[i] = original;
+                params.set_values(&values);
+            }
+        }
+
+        (best_error, improved)
+    }
+}
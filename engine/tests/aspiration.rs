@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use engine::search::{AspirationWindow, Bound, CHECKMATE, MATE_THRESHOLD, MAX, MIN};
+use engine::{Engine, PvNode};
+use game::Board;
+
+fn engine_from_fen(fen: &str) -> Engine {
+    Engine::new(Board::from_fen(fen).unwrap())
+}
+
+// score of a plain full window search at the given depth
+fn full_window_score(fen: &str, depth: u8) -> i32 {
+    let mut engine = engine_from_fen(fen);
+    engine.negamax(depth, 0, MIN, MAX, PvNode::new(None), &None).0
+}
+
+fn aspiration_score(fen: &str, depth: u8) -> i32 {
+    let mut engine = engine_from_fen(fen);
+    engine.iterative_deepening_search(depth, false, Instant::now(), Duration::from_secs(0), None);
+    engine.best_eval
+}
+
+#[test]
+fn first_window_is_full() {
+    assert!(AspirationWindow::new(None).is_full());
+}
+
+#[test]
+fn mate_scores_use_full_window() {
+    assert!(AspirationWindow::new(Some(-CHECKMATE - 5)).is_full());
+    assert!(AspirationWindow::new(Some(CHECKMATE + 5)).is_full());
+}
+
+#[test]
+fn window_is_centered_on_previous_score() {
+    let window = AspirationWindow::new(Some(40));
+    assert!(window.alpha < 40 && window.beta > 40);
+    assert!(!window.is_full());
+}
+
+#[test]
+fn fail_high_keeps_alpha_and_raises_beta() {
+    let mut window = AspirationWindow::new(Some(0));
+    let alpha = window.alpha;
+
+    // the score jumps by a piece
+    assert_eq!(window.update(300), Bound::Lower);
+    assert_eq!(window.alpha, alpha);
+    assert!(window.beta > 300);
+    assert_eq!(window.update(310), Bound::Exact);
+}
+
+#[test]
+fn fail_low_lowers_both_bounds() {
+    let mut window = AspirationWindow::new(Some(0));
+    let beta = window.beta;
+
+    assert_eq!(window.update(-500), Bound::Upper);
+    assert!(window.alpha < -500);
+    assert!(window.beta < beta);
+    assert_eq!(window.update(-490), Bound::Exact);
+}
+
+#[test]
+fn repeated_failures_fall_back_to_full_window() {
+    let mut window = AspirationWindow::new(Some(0));
+    let mut eval = 0;
+    let mut iterations = 0;
+
+    while !window.is_full() {
+        eval += 10_000;
+        assert_eq!(window.update(eval), Bound::Lower);
+        iterations += 1;
+        assert!(iterations <= 10, "window never widened to the full window");
+    }
+
+    assert_eq!(window.update(eval), Bound::Exact);
+}
+
+#[test]
+fn mate_score_opens_window() {
+    let mut window = AspirationWindow::new(Some(50));
+    assert_eq!(window.update(MATE_THRESHOLD + 10), Bound::Lower);
+    assert!(window.is_full());
+}
+
+#[test]
+fn window_stays_within_limits() {
+    let mut window = AspirationWindow::new(Some(MATE_THRESHOLD - 1));
+    window.update(MATE_THRESHOLD - 1 + 1_000_000);
+    assert!(window.alpha >= MIN && window.beta <= MAX);
+}
+
+// the score of these positions changes a lot between iterations, the aspiration windows
+// should still end up with the same score as a full window search
+#[test]
+fn knight_fork_matches_full_window() {
+    // Nc7+ wins the queen on a8, which is only seen from depth 3
+    let fen = "q3k3/8/8/3N4/8/8/8/4K3 w - - 0 1";
+    for depth in 3..=5 {
+        assert_eq!(aspiration_score(fen, depth), full_window_score(fen, depth));
+    }
+}
+
+#[test]
+fn losing_side_matches_full_window() {
+    let fen = "q3k3/8/8/3N4/8/8/8/4K3 b - - 0 1";
+    assert_eq!(aspiration_score(fen, 4), full_window_score(fen, 4));
+}
+
+#[test]
+fn finds_mate_after_quiet_iterations() {
+    // Qd8+ Bxd8 Re8#
+    let fen = "r1b2k1r/ppp1bppp/8/1B1Q4/5q2/2P5/PPP2PPP/R3R1K1 w - - 1 1";
+    assert_eq!(aspiration_score(fen, 4), -CHECKMATE - 3);
+}
@@ -0,0 +1,15 @@
+use engine::bench::{bench, BENCH_DEPTH};
+
+// update this when a change is supposed to change the search, a change that only makes the
+// engine faster should leave it alone
+const BENCH_SIGNATURE: u64 = 58906;
+
+#[test]
+fn bench_signature() {
+    assert_eq!(bench(BENCH_DEPTH).nodes, BENCH_SIGNATURE);
+}
+
+#[test]
+fn bench_is_deterministic() {
+    assert_eq!(bench(3).nodes, bench(3).nodes);
+}
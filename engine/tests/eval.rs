@@ -0,0 +1,111 @@
+use engine::{
+    eval::{Score, Term, TERMS},
+    Engine,
+};
+use game::{Board, KIWIPETE, STARTPOS};
+
+// open and closed middlegames, passed, doubled and isolated pawns, exposed kings and endgames
+const POSITIONS: [&str; 8] = [
+    STARTPOS,
+    KIWIPETE,
+    "r1bq1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP3PPP/R2QKB1R w KQ - 2 9",
+    "r4rk1/1pp2ppp/p1np1n2/4p3/2P1P3/2N2N1P/PP3PP1/R3K2R b KQ - 0 12",
+    "6k1/5p2/1p4p1/p2P3p/P1P5/6P1/5PKP/8 w - - 0 40",
+    "8/2k5/2p5/2Pp4/3P1K2/8/8/8 w - d6 0 50",
+    "4rrk1/p4p1p/1p4pQ/2q5/8/P5R1/1P3PPP/6K1 b - - 1 25",
+    "2kr3r/ppp2ppp/2n5/8/1b6/2N5/PPP2PPP/2KR1B1R w - - 0 14",
+];
+
+// the same position with the colors swapped: the board upside down, the pieces and the castling
+// rights of the other color and the other side to move
+fn mirror(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split(' ').collect();
+    let swap_case = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    };
+
+    let placement: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+    let turn = if fields[1] == "w" { "b" } else { "w" };
+    let castling = if fields[2] == "-" {
+        "-".to_owned()
+    } else {
+        let swapped = swap_case(fields[2]);
+        "KQkq".chars().filter(|&c| swapped.contains(c)).collect()
+    };
+    let en_passant = match fields[3].as_bytes() {
+        [file, rank] => format!("{}{}", *file as char, (b'9' - rank + b'0') as char),
+        _ => "-".to_owned(),
+    };
+
+    format!(
+        "{} {} {} {} {} {}",
+        placement.join("/"),
+        turn,
+        castling,
+        en_passant,
+        fields[4],
+        fields[5]
+    )
+}
+
+fn evaluate(fen: &str) -> i32 {
+    Engine::new(Board::from_fen(fen).unwrap()).evaluate_hce()
+}
+
+#[test]
+fn mirror_swaps_the_colors() {
+    assert_eq!(
+        mirror("r3k2r/8/8/3pP3/8/8/8/R3K3 w Qkq d6 0 1"),
+        "r3k3/8/8/8/3Pp3/8/8/R3K2R b KQq d3 0 1"
+    );
+    assert_eq!(mirror(&mirror(KIWIPETE)), KIWIPETE);
+}
+
+// the evaluation is from the side to move, so it must not change when the colors are swapped
+#[test]
+fn evaluation_is_color_symmetric() {
+    for fen in POSITIONS {
+        let mirrored = mirror(fen);
+        assert_eq!(
+            evaluate(fen),
+            evaluate(&mirrored),
+            "{} and {}",
+            fen,
+            mirrored
+        );
+    }
+}
+
+// the terms of the trace add up to the evaluation: everything but the tempo bonus is tapered
+// together and turned to the side to move, the tempo bonus already is from the side to move
+#[test]
+fn trace_terms_add_up_to_the_evaluation() {
+    for fen in POSITIONS
+        .iter()
+        .map(|fen| fen.to_string())
+        .chain(POSITIONS.map(mirror))
+    {
+        let engine = Engine::new(Board::from_fen(&fen).unwrap());
+        let trace = engine.trace_evaluation();
+
+        let mut score = Score(0, 0);
+        for term in TERMS.into_iter().filter(|&term| term != Term::Tempo) {
+            score = score + trace.white[term] - trace.black[term];
+        }
+        let tempo = trace.white[Term::Tempo] + trace.black[Term::Tempo];
+        assert_eq!(tempo, engine.eval_params.tempo);
+
+        let perspective = if trace.turn.is_white() { 1 } else { -1 };
+        let eval = score.taper(trace.phase) * perspective + tempo.taper(trace.phase);
+        assert_eq!(eval, engine.evaluate(), "{}", fen);
+        assert_eq!(trace.eval, eval, "{}", fen);
+    }
+}
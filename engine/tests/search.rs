@@ -0,0 +1,49 @@
+use engine::search::{MATE_THRESHOLD, MAX, MIN};
+use engine::{Engine, PvNode};
+use game::{Board, Move, Piece, Square, Variant};
+
+// a node below the root, reached by a real move so null move pruning is allowed
+fn search(variant: Variant, fen: &str, depth: u8, alpha: i32, beta: i32) -> i32 {
+    let board = Board::from_variant_fen(variant, fen).unwrap();
+    let mut engine = Engine::new(board);
+    // only has to be some move, a pv node without one is the child of a null move
+    let previous = Move::new(Square::E7, Square::E5, Piece::BlackPawn, None, None, false);
+    engine
+        .negamax(depth, 1, alpha, beta, PvNode::new(Some(previous)), &None)
+        .0
+}
+
+// white is stalemated: the bishop is pinned, the pawn is blocked and the king has nowhere to go
+const STALEMATE: &str = "8/8/8/8/8/7p/5k1P/r5BK w - - 0 1";
+
+#[test]
+fn stalemate_is_not_pruned() {
+    // the static eval is far above beta, reverse futility pruning would fail high
+    for depth in 1..=4 {
+        assert_eq!(search(Variant::Standard, STALEMATE, depth, MIN, -2000), 0);
+    }
+    // too deep for reverse futility pruning, null move pruning would fail high
+    assert_eq!(search(Variant::Standard, STALEMATE, 9, MIN, -2000), 0);
+}
+
+#[test]
+fn antichess_without_moves_is_a_win() {
+    // white has nothing left to move, which wins antichess
+    let score = search(
+        Variant::Antichess,
+        "8/8/8/8/8/8/8/k7 w - - 0 1",
+        2,
+        MIN,
+        -2000,
+    );
+    assert!(score > MATE_THRESHOLD, "{}", score);
+}
+
+#[test]
+fn antichess_quiescence_has_to_capture() {
+    // the queen has to take the last black piece, which wins for black, standing pat would not
+    let board =
+        Board::from_variant_fen(Variant::Antichess, "8/8/8/8/8/8/1p6/Q7 w - - 0 1").unwrap();
+    let score = Engine::new(board).quiet_search(MIN, MAX, 1);
+    assert!(score < -MATE_THRESHOLD, "{}", score);
+}
@@ -0,0 +1,47 @@
+use engine::{eval::EvalParams, tune::TuningPosition};
+
+#[test]
+fn results_of_the_annotations() {
+    let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+    let epd = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -";
+    for (line, result) in [
+        (format!("{} [1.0]", fen), 1.0),
+        (format!("{} [0-1]", fen), 0.0),
+        (format!("{} c9 \"1/2-1/2\";", epd), 0.5),
+        (format!("{} | 35 | 0.5", fen), 0.5),
+    ] {
+        let position = TuningPosition::parse(&line).expect(&line);
+        assert_eq!(position.result, result, "{}", line);
+    }
+}
+
+#[test]
+fn bad_lines_are_skipped() {
+    for line in [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [2.0]",
+        // a malformed fen is skipped like any other bad line
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [1.0]",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1 [1.0]",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1 [1.0]",
+    ] {
+        assert!(TuningPosition::parse(line).is_none(), "{}", line);
+    }
+}
+
+#[test]
+fn parameter_file_round_trip() {
+    assert_eq!(
+        EvalParams::default().to_string().parse(),
+        Ok(EvalParams::default())
+    );
+
+    // every value different, negative ones included
+    let mut params = EvalParams::default();
+    let values: Vec<i32> = (0..params.values().len() as i32).map(|i| i - 500).collect();
+    params.set_values(&values);
+    let parsed: EvalParams = params.to_string().parse().unwrap();
+    assert_eq!(parsed.values(), values);
+    assert_eq!(parsed, params);
+}
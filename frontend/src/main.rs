@@ -1,78 +1,137 @@
 use std::{collections::HashMap, sync::Mutex, time::{Duration, Instant}};
 
 use engine::Engine;
-use game::{rand, Board, BoardBuilder, Move, Piece, STARTPOS};
-use movegen::generate_legal_moves;
-use rocket::{fs::FileServer, response::Redirect, serde::json::Json, State};
+use game::{rand, record::{GameRecord, Outcome}, render::{RenderOptions, SPRITE}, Board, BoardBuilder, Color, Move, STARTPOS};
+use movegen::{generate_legal_moves, san::to_san, Attacks};
+use rocket::{fs::FileServer, http::ContentType, response::Redirect, serde::json::Json, State};
 
 #[macro_use] extern crate rocket;
 
+// the position of a game and the moves that led to it
+#[derive(Clone)]
+struct Game {
+    board: Board,
+    record: GameRecord,
+    last_move: Option<Move>,
+}
+
+type Games = Mutex<HashMap<u64, Game>>;
+
+impl Game {
+    fn new(board: Board) -> Game {
+        Game { board, record: GameRecord::new(&board), last_move: None }
+    }
+
+    // plays the move if it is legal, and records the result once the game is over
+    fn play(&mut self, uci: &str) {
+        let moves = generate_legal_moves(&mut self.board, false);
+        let Some(&m) = moves.iter().find(|m| m.to_uci() == uci) else {
+            return;
+        };
+
+        let san = to_san(&m, &mut self.board).ok();
+        self.record.push(m, san, None);
+        let _ = self.board.make_move(m);
+        self.last_move = Some(m);
+
+        let mover = !self.board.turn;
+        let side = if mover == Color::White { "White" } else { "Black" };
+        if self.board.is_variant_loss() {
+            self.record.finish(Outcome::win(mover), &format!("{} wins by the {} rules", side, self.board.variant));
+        } else if generate_legal_moves(&mut self.board, false).is_empty() {
+            if self.board.in_check() {
+                self.record.finish(Outcome::win(mover), &format!("{} mates", side));
+            } else {
+                self.record.finish(Outcome::Draw, "Draw by stalemate");
+            }
+        }
+    }
+}
+
 #[get("/")]
 fn index() -> Redirect {
     Redirect::to("/index.html")
 }
 
 #[get("/board")]
-fn board(active_boards: &State<Mutex<HashMap<u64, Board>>>) -> Json<(String, [[Piece; 8]; 8])> {
+fn board(active_boards: &State<Games>) -> Json<(String, Board)> {
     let b = BoardBuilder::new().set_position(STARTPOS.to_string()).build();
-    let ret = b.board;
     let id = rand::random();
-    active_boards.lock().unwrap().insert(id, b);
+    active_boards.lock().unwrap().insert(id, Game::new(b));
     println!("new board id {}\n{}", id, b);
     let idstr = id.to_string();
-    Json((idstr, ret))
+    Json((idstr, b))
 }
 
 #[get("/retboard/<id>")]
-fn retboard(id: u64, active_boards: &State<Mutex<HashMap<u64, Board>>>) -> Json<[[Piece; 8]; 8]> {
+fn retboard(id: u64, active_boards: &State<Games>) -> Json<Board> {
     Json(active_boards.lock().unwrap().get(&id).unwrap().board)
 }
 
-#[get("/legalmoves/<id>")]
-fn legalmoves(id: u64, active_boards: &State<Mutex<HashMap<u64, Board>>>) -> Json<Vec<String>> {
-    let mut b = active_boards.lock().unwrap().get(&id).unwrap().clone();
-    let moves = generate_legal_moves(&mut b, false);
-    let mut moves_vec = Vec::new();
-    for m in moves {
-        moves_vec.push(m.to_uci());
-    }
+#[get("/record/<id>")]
+fn record(id: u64, active_boards: &State<Games>) -> Json<GameRecord> {
+    Json(active_boards.lock().unwrap().get(&id).unwrap().record.clone())
+}
 
-    Json(moves_vec)
+#[get("/legalmoves/<id>")]
+fn legalmoves(id: u64, active_boards: &State<Games>) -> Json<Vec<Move>> {
+    let mut b = active_boards.lock().unwrap().get(&id).unwrap().board;
+    Json(generate_legal_moves(&mut b, false))
 }
 
 #[get("/makemove/<id>/<uci>")]
-fn makemove(id: u64, uci: String, active_boards: &State<Mutex<HashMap<u64, Board>>>) -> Json<[[Piece; 8]; 8]> {
-    let mut b = active_boards.lock().unwrap().get(&id).unwrap().clone();
-    let _ = b.make_move(Move::from_uci(&uci, b));
-    active_boards.lock().unwrap().insert(id, b);
+fn makemove(id: u64, uci: String, active_boards: &State<Games>) -> Json<Board> {
+    let mut games = active_boards.lock().unwrap();
+    let game = games.get_mut(&id).unwrap();
+    game.play(&uci);
 
-    Json(b.board)
+    Json(game.board)
 }
 
 #[get("/removegame/<id>")]
-fn removegame(id: u64, active_boards: &State<Mutex<HashMap<u64, Board>>>) {
+fn removegame(id: u64, active_boards: &State<Games>) {
     active_boards.lock().unwrap().remove(&id);
     println!("removed game {}", id);
 }
 
 #[get("/turn/<id>")]
-fn turn(id: u64, active_boards: &State<Mutex<HashMap<u64, Board>>>) -> Json<bool> {
-    Json(active_boards.lock().unwrap().get(&id).unwrap().turn)
+fn turn(id: u64, active_boards: &State<Games>) -> Json<Color> {
+    Json(active_boards.lock().unwrap().get(&id).unwrap().board.turn)
+}
+
+// the position as an svg diagram, from black's side with ?orientation=black
+#[get("/diagram/<id>?<orientation>")]
+fn diagram(id: u64, orientation: Option<&str>, active_boards: &State<Games>) -> (ContentType, String) {
+    let games = active_boards.lock().unwrap();
+    let game = games.get(&id).unwrap();
+    let options = RenderOptions {
+        orientation: if orientation == Some("black") { Color::Black } else { Color::White },
+        last_move: game.last_move,
+        id_prefix: format!("diagram-{}-", id),
+        ..RenderOptions::default()
+    };
+    (ContentType::SVG, game.board.render_svg(&options))
+}
+
+// the piece sprite sheet of the game crate, the same pieces as the diagrams
+#[get("/img/Chess_Pieces_Sprite.svg")]
+fn sprite() -> (ContentType, &'static str) {
+    (ContentType::SVG, SPRITE)
 }
 
 #[get("/bestmove/<id>")]
-fn bestmove(id: u64, active_boards: &State<Mutex<HashMap<u64, Board>>>) -> Json<String> {
-    let b = active_boards.lock().unwrap().get(&id).unwrap().clone();
+fn bestmove(id: u64, active_boards: &State<Games>) -> Json<Option<Move>> {
+    let b = active_boards.lock().unwrap().get(&id).unwrap().board;
     let mut engine = Engine::new(b);
     engine.iterative_deepening_search(200, true, Instant::now(), Duration::from_millis(1000), None);
-    Json(engine.best_move.unwrap().to_uci())
+    Json(engine.best_move)
 }
 
 #[launch]
 fn rocket() -> _ {
-    let active_boards: Mutex<HashMap<u64, Board>> = Mutex::new(HashMap::new());
+    let active_boards: Games = Mutex::new(HashMap::new());
     rocket::build()
         .mount("/", FileServer::from("./static"))
-        .mount("/", routes![index, board, retboard, legalmoves, makemove, removegame, turn, bestmove])
+        .mount("/", routes![index, board, retboard, record, legalmoves, makemove, removegame, turn, diagram, sprite, bestmove])
         .manage(active_boards)
 }
@@ -7,15 +7,8 @@ pieces.src = "img/Chess_Pieces_Sprite_alt.png";
 const grid_width = canvas.clientWidth / 8;
 const grid_height = canvas.clientHeight / 8;
 
-const PAWN = 0b00100;
-const BISHOP = 0b01000;
-const KNIGHT = 0b01100;
-const ROOK = 0b10000;
-const QUEEN = 0b10100;
-const KING = 0b11000;
-
-const WHITE = 0b01;
-const BLACK = 0b10;
+// the columns of the pieces in the sprite, white pieces are in the top row
+const SPRITE_COLUMNS = { k: 0, q: 1, b: 2, n: 3, r: 4, p: 5 };
 
 let game_board;
 let game_id = -1n;
@@ -86,44 +79,12 @@ function draw() {
 
             ctx.fillRect(c * grid_width, r * grid_height, (1 + c) * grid_width, (1 + r) * grid_height);
 
-            switch (game_board[r][c]) {
-                case WHITE | PAWN:
-                ctx.drawImage(pieces, 225, 0, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case WHITE | BISHOP:
-                ctx.drawImage(pieces, 90, 0, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case WHITE | KNIGHT:
-                ctx.drawImage(pieces, 135, 0, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case WHITE | ROOK:
-                ctx.drawImage(pieces, 180, 0, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case WHITE | QUEEN:
-                ctx.drawImage(pieces, 45, 0, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case WHITE | KING:
-                ctx.drawImage(pieces, 0, 0, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                    
-                case BLACK | PAWN:
-                ctx.drawImage(pieces, 225, 45, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case BLACK | BISHOP:
-                ctx.drawImage(pieces, 90, 45, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case BLACK | KNIGHT:
-                ctx.drawImage(pieces, 135, 45, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case BLACK | ROOK:
-                ctx.drawImage(pieces, 180, 45, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case BLACK | QUEEN:
-                ctx.drawImage(pieces, 45, 45, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                break;
-                case BLACK | KING:
-                ctx.drawImage(pieces, 0, 45, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
-                default:
+            // pieces are fen letters, uppercase for white
+            const piece = game_board.squares[r][c];
+            if (piece !== null) {
+                const sx = 45 * SPRITE_COLUMNS[piece.toLowerCase()];
+                const sy = piece === piece.toUpperCase() ? 0 : 45;
+                ctx.drawImage(pieces, sx, sy, 45, 45, c * grid_width, r * grid_height, grid_width, grid_height);
             }
         }
     }
@@ -191,8 +152,7 @@ window.addEventListener("click", async (event) => {
         let player_can_move = true;
 
         if (engine_to_move) {
-            let from = square_to_row_col(legal_moves[0].slice(0, 2));
-            if (((game_board[from[0]][from[1]] & 0b11) == WHITE) == engine_turn_bool) {
+            if ((game_board.turn == "white") == engine_turn_bool) {
                 player_can_move = false;
 
                 play_engine_move(); 
@@ -0,0 +1,90 @@
+// atomic chess: a capture explodes the capturing piece, the captured piece and every piece other
+// than a pawn next to the capture square. a side whose king explodes has lost, so kings cant
+// capture and the move rules (in movegen) only make sure the own king survives
+// https://lichess.org/variant/atomic
+use crate::{
+    genkeys::HASH_KEYS, precomputed::KING_MASKS, Bitboard, Board, Move, Piece, Square,
+    BLACK_KINGSIDE, BLACK_QUEENSIDE, WHITE_KINGSIDE, WHITE_QUEENSIDE,
+};
+
+/// the pieces next to the capture square that an atomic capture blew up, for unmake_move
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Explosion {
+    squares: u64,
+    // in the order of the squares
+    pieces: [Option<Piece>; 8],
+}
+
+impl Explosion {
+    /// the pieces that were blown up next to the capture square
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        Bitboard(self.squares).zip(self.pieces.iter().flatten().copied())
+    }
+}
+
+impl Board {
+    // takes a piece off the board, the mailbox, bitboards and hash
+    fn remove_exploded(&mut self, square: Square, piece: Piece) {
+        let mask = square.bitboard().0;
+        self.set_piece_at(square, None);
+        let pieces = self.bitboards_mut(piece.color());
+        *pieces.get_mut(piece.piece_type()) ^= mask;
+        pieces.all ^= mask;
+        self.hash ^= HASH_KEYS.piece_key(piece, square);
+    }
+
+    fn restore_exploded(&mut self, square: Square, piece: Piece) {
+        let mask = square.bitboard().0;
+        self.set_piece_at(square, Some(piece));
+        let pieces = self.bitboards_mut(piece.color());
+        *pieces.get_mut(piece.piece_type()) ^= mask;
+        pieces.all ^= mask;
+    }
+
+    // removes the capturing piece and the pieces around it, make_move calls it after the capture
+    // was made
+    pub(crate) fn explode(&mut self, m: Move) -> Explosion {
+        self.remove_exploded(m.to, m.promotion_piece.unwrap_or(m.piece));
+
+        let (r, f) = m.to.mailbox();
+        let pawns = self.white.pawns | self.black.pawns;
+        let squares = KING_MASKS[r][f] & (self.white.all | self.black.all) & !pawns;
+
+        let mut explosion = Explosion {
+            squares,
+            pieces: [None; 8],
+        };
+        for (i, square) in Bitboard(squares).enumerate() {
+            let piece = self.piece_at(square).unwrap();
+            explosion.pieces[i] = Some(piece);
+            self.remove_exploded(square, piece);
+        }
+
+        // a right is lost with its king or rook
+        let exploded = squares | m.to.bitboard().0;
+        for (right, key, king, rook) in [
+            (WHITE_KINGSIDE, HASH_KEYS.white_ks, Square::E1, Square::H1),
+            (WHITE_QUEENSIDE, HASH_KEYS.white_qs, Square::E1, Square::A1),
+            (BLACK_KINGSIDE, HASH_KEYS.black_ks, Square::E8, Square::H8),
+            (BLACK_QUEENSIDE, HASH_KEYS.black_qs, Square::E8, Square::A8),
+        ] {
+            let squares = king.bitboard().0 | rook.bitboard().0;
+            if self.castle_state & right != 0 && exploded & squares != 0 {
+                self.castle_state &= !right;
+                self.hash ^= key;
+            }
+        }
+
+        explosion
+    }
+
+    // puts the exploded pieces back, unmake_move then takes back the capture itself. the hash
+    // and the castling rights are restored from the undo info
+    pub(crate) fn unexplode(&mut self, m: Move, explosion: Explosion) {
+        self.restore_exploded(m.to, m.promotion_piece.unwrap_or(m.piece));
+
+        for (square, piece) in Bitboard(explosion.squares).zip(explosion.pieces) {
+            self.restore_exploded(square, piece.unwrap());
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// crazyhouse: a captured piece changes color and goes into the pocket of the side that captured
+// it, and instead of moving a side can drop a piece from its pocket onto an empty square. pawns
+// that promoted turn back into pawns when they are captured
+// https://lichess.org/variant/crazyhouse
+use crate::{genkeys::HASH_KEYS, Board, Color, Move, Piece, PieceType, UndoInfo};
+
+/// the pieces a side has in hand, counted by piece type
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pocket([u8; 5]);
+
+// the order the pieces of a pocket are written in a fen
+const FEN_ORDER: [PieceType; 5] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+impl Pocket {
+    /// the piece types that can be in a pocket, kings are never captured
+    pub const PIECE_TYPES: [PieceType; 5] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ];
+
+    pub fn count(&self, piece_type: PieceType) -> u8 {
+        self.0.get(piece_type.index()).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&count| count == 0)
+    }
+
+    /// the piece types in the pocket with how many of each there are
+    pub fn pieces(&self) -> impl Iterator<Item = (PieceType, u8)> + '_ {
+        Pocket::PIECE_TYPES
+            .into_iter()
+            .map(|piece_type| (piece_type, self.count(piece_type)))
+            .filter(|&(_, count)| count > 0)
+    }
+}
+
+// the pockets in brackets, white's pieces first ("[QRbn]")
+pub(crate) fn pockets_to_fen(pockets: &[Pocket; 2]) -> String {
+    let mut fen = String::from("[");
+    for color in Color::ALL {
+        for piece_type in FEN_ORDER {
+            let piece = Piece::new(color, piece_type);
+            for _ in 0..pockets[color.index()].count(piece_type) {
+                fen.push(piece.to_char());
+            }
+        }
+    }
+
+    fen + "]"
+}
+
+// the letters between the brackets of a fen, uppercase for white. none if there is a king or
+// a letter that is not a piece
+pub(crate) fn pockets_from_fen(pieces: &str) -> Option<[Pocket; 2]> {
+    let mut pockets = [Pocket::default(); 2];
+    for c in pieces.chars() {
+        let piece = Piece::from_char(c).filter(|piece| piece.piece_type() != PieceType::King)?;
+        pockets[piece.color().index()].0[piece.piece_type().index()] += 1;
+    }
+
+    Some(pockets)
+}
+
+impl Board {
+    /// the pieces the color can drop
+    pub fn pocket(&self, color: Color) -> &Pocket {
+        &self.pockets[color.index()]
+    }
+
+    fn add_to_pocket(&mut self, color: Color, piece_type: PieceType) {
+        let count = &mut self.pockets[color.index()].0[piece_type.index()];
+        self.hash ^= HASH_KEYS.pocket_key(color, piece_type, *count);
+        *count += 1;
+    }
+
+    fn remove_from_pocket(&mut self, color: Color, piece_type: PieceType) {
+        let count = &mut self.pockets[color.index()].0[piece_type.index()];
+        *count -= 1;
+        self.hash ^= HASH_KEYS.pocket_key(color, piece_type, *count);
+    }
+
+    // puts the piece a move captures into the pocket of the mover and moves the promoted marks
+    // along with the pieces, make_move calls it before the board changes
+    pub(crate) fn update_pockets(&mut self, m: Move) {
+        let (from, to) = (m.from.bitboard().0, m.to.bitboard().0);
+
+        if let Some(piece) = m.capture_piece {
+            // en passant captures a pawn, and the target square is empty
+            let piece_type = if self.promoted & to != 0 {
+                PieceType::Pawn
+            } else {
+                piece.piece_type()
+            };
+            self.add_to_pocket(m.piece.color(), piece_type);
+        }
+
+        self.promoted &= !to;
+        if self.promoted & from != 0 || m.promotion_piece.is_some() {
+            self.promoted = self.promoted & !from | to;
+        }
+    }
+
+    // a drop only adds a piece to the board, it cant capture, castle or promote
+    pub(crate) fn make_drop(&mut self, m: Move) {
+        let color = m.piece.color();
+        let square = m.to.bitboard().0;
+        self.remove_from_pocket(color, m.piece.piece_type());
+
+        if let Some(square) = self.en_passant_square.take() {
+            self.hash ^= HASH_KEYS.en_passant_square_file[square.file().index()];
+        }
+
+        self.set_piece_at(m.to, Some(m.piece));
+        let ours = self.bitboards_mut(color);
+        *ours.get_mut(m.piece.piece_type()) |= square;
+        ours.all |= square;
+        self.hash ^= HASH_KEYS.piece_key(m.piece, m.to);
+
+        // pawn drops reset the fifty move counter like pawn moves
+        if m.piece.piece_type() == PieceType::Pawn {
+            self.halfmoves = 0;
+        } else {
+            self.halfmoves += 1;
+        }
+
+        if self.turn == Color::Black {
+            self.fullmoves += 1
+        }
+        self.turn = !self.turn;
+        self.hash ^= HASH_KEYS.turn_key;
+
+        #[cfg(feature = "validate")]
+        self.debug_validate();
+    }
+
+    pub(crate) fn unmake_drop(&mut self, m: Move, undo: UndoInfo) {
+        self.turn = !self.turn;
+        if self.turn == Color::Black {
+            self.fullmoves -= 1;
+        }
+
+        let square = m.to.bitboard().0;
+        self.set_piece_at(m.to, None);
+        let ours = self.bitboards_mut(m.piece.color());
+        *ours.get_mut(m.piece.piece_type()) ^= square;
+        ours.all ^= square;
+
+        self.en_passant_square = undo.en_passant_square;
+        self.halfmoves = undo.halfmoves;
+        self.hash = undo.hash;
+        self.check_info = undo.check_info;
+        self.pockets = undo.pockets;
+
+        #[cfg(feature = "validate")]
+        self.debug_validate();
+    }
+}
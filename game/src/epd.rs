@@ -0,0 +1,145 @@
+// extended position description
+// https://www.chessprogramming.org/Extended_Position_Description
+//
+// the first four fields of a fen followed by operations separated by semicolons, for example
+// `r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - bm Nxc6; id "WAC.004";`
+//
+// perft suites use the same format with the move counters and `D<depth> <nodes>` operations:
+// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400`
+use std::str::FromStr;
+
+use crate::{Board, BoardBuilder};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Epd {
+    // all six fields, the move counters come from the hmvc and fmvn operations if there are any
+    pub fen: String,
+    // opcode and operands, quotes are removed from string operands
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    pub fn board(&self) -> Board {
+        BoardBuilder::new().set_position(self.fen.clone()).build()
+    }
+
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(op, _)| op == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    /// best moves in standard algebraic notation
+    pub fn best_moves(&self) -> &[String] {
+        self.operation("bm").unwrap_or(&[])
+    }
+
+    /// moves to avoid in standard algebraic notation
+    pub fn avoid_moves(&self) -> &[String] {
+        self.operation("am").unwrap_or(&[])
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.operation("id")?.first().map(String::as_str)
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.operation("c0")?.first().map(String::as_str)
+    }
+
+    /// expected perft results as (depth, nodes), from the D1..Dn operations
+    pub fn perft(&self) -> Vec<(u8, u64)> {
+        self.operations
+            .iter()
+            .filter_map(|(op, operands)| {
+                let depth = op.strip_prefix('D')?.parse().ok()?;
+                let nodes = operands.first()?.parse().ok()?;
+                Some((depth, nodes))
+            })
+            .collect()
+    }
+}
+
+// splits the operations on semicolons and whitespace, except inside quotes
+fn parse_operations(s: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut operations = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in s.chars().chain([';']) {
+        match c {
+            '"' => quoted = !quoted,
+            ';' | ' ' | '\t' if !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+
+                if c == ';' && !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    operations.push((opcode, std::mem::take(&mut tokens)));
+                }
+            }
+            _ => token.push(c),
+        }
+    }
+
+    if quoted {
+        return Err(format!("unterminated string in epd operations {}", s.trim()));
+    }
+
+    Ok(operations)
+}
+
+impl FromStr for Epd {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Epd, String> {
+        let s = s.trim();
+        let mut fields = Vec::new();
+        let mut rest = s;
+
+        // the four fen fields, and the move counters if they are there
+        while fields.len() < 6 {
+            let trimmed = rest.trim_start();
+            let end = trimmed
+                .find(|c: char| c.is_whitespace() || c == ';')
+                .unwrap_or(trimmed.len());
+            let field = &trimmed[..end];
+
+            if field.is_empty() || (fields.len() >= 4 && field.parse::<u16>().is_err()) {
+                break;
+            }
+
+            fields.push(field);
+            rest = &trimmed[end..];
+        }
+
+        if fields.len() < 4 {
+            return Err(format!("invalid epd {}", s));
+        }
+
+        let operations = parse_operations(rest)?;
+        let counter = |opcode: &str, field: usize, default: &'static str| {
+            operations
+                .iter()
+                .find(|(op, _)| op == opcode)
+                .and_then(|(_, operands)| operands.first().map(String::as_str))
+                .or(fields.get(field).copied())
+                .unwrap_or(default)
+                .to_owned()
+        };
+        let halfmoves = counter("hmvc", 4, "0");
+        let fullmoves = counter("fmvn", 5, "1");
+
+        let fen = format!(
+            "{} {} {} {} {} {}",
+            fields[0], fields[1], fields[2], fields[3], halfmoves, fullmoves
+        );
+        // board() can then build the position without checking it again
+        BoardBuilder::new().try_set_position(&fen)?;
+
+        Ok(Epd { fen, operations })
+    }
+}
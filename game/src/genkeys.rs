@@ -1,15 +1,22 @@
-use crate::rand;
-use crate::{
-    get_piece_color, get_piece_type, is_black_kingside, is_black_queenside, is_white_kingside,
-    is_white_queenside,
-};
+use crate::rand::Rng;
+use crate::{is_black_kingside, is_black_queenside, is_white_kingside, is_white_queenside};
 
 use crate::{BLACK_KINGSIDE, BLACK_QUEENSIDE, WHITE_KINGSIDE, WHITE_QUEENSIDE};
 
-use super::{Board, HashKeys, BISHOP, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+use super::{Bitboard, Board, Color, HashKeys, Piece, PieceType, Square};
+
+// the same keys for every board, so hashes can be compared between boards (and between runs)
+pub static HASH_KEYS: HashKeys = HashKeys::new();
+
+impl Default for HashKeys {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl HashKeys {
-    pub fn new() -> HashKeys {
+    pub const fn new() -> HashKeys {
+        let mut rng = Rng::from_seed(0x5A17_2B4E_93C1_D806);
         let mut keys = HashKeys {
             white_pawn: [[0; 8]; 8],
             white_bishop: [[0; 8]; 8],
@@ -29,112 +36,144 @@ impl HashKeys {
             black_ks: 0,
             black_qs: 0,
             en_passant_square_file: [0; 8],
+            checks: [[0; 3]; 2],
+            pockets: [[[0; 16]; 5]; 2],
         };
 
-        for rank in 0..8 {
-            for file in 0..8 {
-                keys.white_pawn[rank][file] = rand::random();
-                keys.white_bishop[rank][file] = rand::random();
-                keys.white_knight[rank][file] = rand::random();
-                keys.white_rook[rank][file] = rand::random();
-                keys.white_queen[rank][file] = rand::random();
-                keys.white_king[rank][file] = rand::random();
-                keys.black_bishop[rank][file] = rand::random();
-                keys.black_knight[rank][file] = rand::random();
-                keys.black_rook[rank][file] = rand::random();
-                keys.black_queen[rank][file] = rand::random();
-                keys.black_king[rank][file] = rand::random();
+        let mut rank = 0;
+        while rank < 8 {
+            let mut file = 0;
+            while file < 8 {
+                keys.white_pawn[rank][file] = rng.next_u64();
+                keys.white_bishop[rank][file] = rng.next_u64();
+                keys.white_knight[rank][file] = rng.next_u64();
+                keys.white_rook[rank][file] = rng.next_u64();
+                keys.white_queen[rank][file] = rng.next_u64();
+                keys.white_king[rank][file] = rng.next_u64();
+                keys.black_pawn[rank][file] = rng.next_u64();
+                keys.black_bishop[rank][file] = rng.next_u64();
+                keys.black_knight[rank][file] = rng.next_u64();
+                keys.black_rook[rank][file] = rng.next_u64();
+                keys.black_queen[rank][file] = rng.next_u64();
+                keys.black_king[rank][file] = rng.next_u64();
+                file += 1;
             }
 
-            keys.en_passant_square_file[rank] = rand::random();
+            keys.en_passant_square_file[rank] = rng.next_u64();
+            rank += 1;
+        }
+
+        keys.turn_key = rng.next_u64();
+        keys.white_ks = rng.next_u64();
+        keys.white_qs = rng.next_u64();
+        keys.black_ks = rng.next_u64();
+        keys.black_qs = rng.next_u64();
+
+        let mut checks = 0;
+        while checks < 3 {
+            keys.checks[0][checks] = rng.next_u64();
+            keys.checks[1][checks] = rng.next_u64();
+            checks += 1;
         }
 
-        keys.turn_key = rand::random();
-        keys.white_ks = rand::random();
-        keys.white_qs = rand::random();
-        keys.black_ks = rand::random();
-        keys.black_qs = rand::random();
+        let mut color = 0;
+        while color < 2 {
+            let mut piece_type = 0;
+            while piece_type < 5 {
+                let mut count = 0;
+                while count < 16 {
+                    keys.pockets[color][piece_type][count] = rng.next_u64();
+                    count += 1;
+                }
+                piece_type += 1;
+            }
+            color += 1;
+        }
 
         keys
     }
 
-    pub fn generate_hash(&self, board: &mut Board) {
-        let mut r: usize = 0;
-        let mut f: usize = 0;
-        for rank in board.board {
-            for piece in rank {
-                board.hash ^= match get_piece_type!(piece) {
-                    PAWN => {
-                        if get_piece_color!(piece) == WHITE {
-                            self.white_pawn[r][f]
-                        } else {
-                            self.black_pawn[r][f]
-                        }
-                    }
-                    BISHOP => {
-                        if get_piece_color!(piece) == WHITE {
-                            self.white_bishop[r][f]
-                        } else {
-                            self.black_bishop[r][f]
-                        }
-                    }
-                    KNIGHT => {
-                        if get_piece_color!(piece) == WHITE {
-                            self.white_knight[r][f]
-                        } else {
-                            self.black_knight[r][f]
-                        }
-                    }
-                    ROOK => {
-                        if get_piece_color!(piece) == WHITE {
-                            self.white_rook[r][f]
-                        } else {
-                            self.black_rook[r][f]
-                        }
-                    }
-                    QUEEN => {
-                        if get_piece_color!(piece) == WHITE {
-                            self.white_queen[r][f]
-                        } else {
-                            self.black_queen[r][f]
-                        }
-                    }
-                    KING => {
-                        if get_piece_color!(piece) == WHITE {
-                            self.white_king[r][f]
-                        } else {
-                            self.black_king[r][f]
-                        }
-                    }
-                    _ => 0,
-                };
-
-                f += 1;
+    // key of a piece (with its color) on a square
+    pub fn piece_key(&self, piece: Piece, square: Square) -> u64 {
+        let (r, f) = square.mailbox();
+        let white = piece.color() == Color::White;
+
+        match piece.piece_type() {
+            PieceType::Pawn if white => self.white_pawn[r][f],
+            PieceType::Pawn => self.black_pawn[r][f],
+            PieceType::Bishop if white => self.white_bishop[r][f],
+            PieceType::Bishop => self.black_bishop[r][f],
+            PieceType::Knight if white => self.white_knight[r][f],
+            PieceType::Knight => self.black_knight[r][f],
+            PieceType::Rook if white => self.white_rook[r][f],
+            PieceType::Rook => self.black_rook[r][f],
+            PieceType::Queen if white => self.white_queen[r][f],
+            PieceType::Queen => self.black_queen[r][f],
+            PieceType::King if white => self.white_king[r][f],
+            PieceType::King => self.black_king[r][f],
+        }
+    }
+
+    // key of the number of checks a color gave in three-check, no checks have no key
+    pub fn checks_key(&self, color: Color, checks: u8) -> u64 {
+        match checks {
+            0 => 0,
+            _ => self.checks[color.index()][checks.min(3) as usize - 1],
+        }
+    }
+
+    // key of the piece of a type a color has in hand when it already has `count` of them
+    pub fn pocket_key(&self, color: Color, piece_type: PieceType, count: u8) -> u64 {
+        self.pockets[color.index()][piece_type.index()][(count as usize).min(15)]
+    }
+
+    /// the hash of the board computed from scratch, make_move keeps it up to date incrementally
+    pub fn hash(&self, board: &Board) -> u64 {
+        let mut hash = 0;
+        for square in Bitboard(board.white.all | board.black.all) {
+            if let Some(piece) = board.piece_at(square) {
+                hash ^= self.piece_key(piece, square);
             }
-            f = 0;
-            r += 1;
         }
 
         if is_white_kingside!(board.castle_state) {
-            board.hash ^= self.white_ks;
+            hash ^= self.white_ks;
         }
 
         if is_white_queenside!(board.castle_state) {
-            board.hash ^= self.white_qs;
+            hash ^= self.white_qs;
         }
 
         if is_black_kingside!(board.castle_state) {
-            board.hash ^= self.black_ks;
+            hash ^= self.black_ks;
         }
 
         if is_black_queenside!(board.castle_state) {
-            board.hash ^= self.black_qs;
+            hash ^= self.black_qs;
+        }
+
+        if board.turn == Color::White {
+            hash ^= self.turn_key;
         }
 
-        if board.turn {
-            board.hash ^= self.turn_key;
+        if let Some(square) = board.en_passant_square {
+            hash ^= self.en_passant_square_file[square.file().index()]
         }
 
-        if let Some(square) = board.en_passant_square { board.hash ^= self.en_passant_square_file[square.1 as usize]}
+        for color in Color::ALL {
+            hash ^= self.checks_key(color, board.checks[color.index()]);
+
+            for (piece_type, count) in board.pocket(color).pieces() {
+                for n in 0..count {
+                    hash ^= self.pocket_key(color, piece_type, n);
+                }
+            }
+        }
+
+        hash
+    }
+
+    pub fn generate_hash(&self, board: &mut Board) {
+        board.hash ^= self.hash(board);
     }
 }
@@ -1,34 +1,36 @@
+pub mod atomic;
+pub mod crazyhouse;
+pub mod epd;
 pub mod genkeys;
+pub mod move16;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+pub mod piece;
 pub mod precomputed;
 pub mod rand;
+pub mod record;
+pub mod render;
+pub mod square;
+#[cfg(feature = "serde")]
+mod serialize;
+mod validate;
+pub mod variant;
 
 use std::fmt::Display;
 
-pub const WHITE_TO_MOVE: bool = true;
-pub const BLACK_TO_MOVE: bool = false;
+use atomic::Explosion;
+use crazyhouse::Pocket;
+use genkeys::HASH_KEYS;
+use render::RenderOptions;
+pub use piece::{Color, Piece, PieceType};
+pub use square::{Bitboard, File, Rank, Square};
+pub use validate::BoardError;
+pub use variant::Variant;
 
 pub const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 // for testing
 pub const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
 
-/**
- * right 5 bits are used
- * 0bXXXYY
- * XXX:
- * 001 - pawn
- * 010 - bishop
- * 011 - knight
- * 100 - rook
- * 101 - queen
- * 110 - king
- * YY:
- * 01 - white
- * 10 - black
- *
- * 0b00000 means empty square
- */
-pub type Piece = u8;
-
 /**
  * right four bits are used, 1 = can castle, 0 = can't
  * from the left:
@@ -79,20 +81,23 @@ macro_rules! get_bit_index {
     };
 }
 
-#[macro_export]
-macro_rules! get_piece_type {
-    ($piece: expr) => {
-        ($piece & 0b11100)
-    };
-}
-
-#[macro_export]
-macro_rules! get_piece_color {
-    ($piece: expr) => {
-        ($piece & 0b00011)
-    };
-}
-
+/**
+ * the old bit packed piece encoding, still used by Piece as its representation
+ * right 5 bits are used
+ * 0bXXXYY
+ * XXX:
+ * 001 - pawn
+ * 010 - bishop
+ * 011 - knight
+ * 100 - rook
+ * 101 - queen
+ * 110 - king
+ * YY:
+ * 01 - white
+ * 10 - black
+ *
+ * 0b00000 means empty square
+ */
 // matches the first 3 bits of the "piece" type
 // last 2 bits are 0 so the type can be ANDed with the color
 pub const PAWN: u8 = 0b00100;
@@ -129,16 +134,19 @@ const BLACK_QUEENSIDE_MOVE_MASK: u64 =
     0b00101000_00000000_00000000_00000000_00000000_00000000_00000000_00000000;
 
 //rank file, uci -> "a1", "a2", etc
+#[deprecated(note = "squares are no longer (rank, file) tuples, parse a Square instead")]
 pub fn square_from_uci(uci: &str) -> (u8, u8) {
-    let file: char = uci.chars().nth(0).unwrap();
+    let file: char = uci.chars().next().unwrap();
     let rank: u8 = 8 - uci.chars().nth(1).unwrap().to_digit(10).unwrap() as u8;
     (rank, file as u8 - 97)
 }
 
+// the fields only take 7 bytes, aligning to 8 lets a move be copied and compared as one word
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(align(8))]
 pub struct Move {
-    pub from: (u8, u8),
-    pub to: (u8, u8),
+    pub from: Square,
+    pub to: Square,
 
     // Piece, not piece type, so color should be included
     pub piece: Piece,
@@ -148,12 +156,14 @@ pub struct Move {
     pub capture_piece: Option<Piece>,
     pub promotion_piece: Option<Piece>,
     pub en_passant: bool,
+    // a piece put on the board from the pocket (crazyhouse), from is the same square as to
+    pub drop: bool,
 }
 
 impl Move {
     pub fn new(
-        from: (u8, u8),
-        to: (u8, u8),
+        from: Square,
+        to: Square,
         piece: Piece,
         capture_piece: Option<Piece>,
         promotion_piece: Option<Piece>,
@@ -166,77 +176,77 @@ impl Move {
             capture_piece,
             promotion_piece,
             en_passant,
+            drop: false,
+        }
+    }
+
+    /// dropping a piece from the pocket of its color onto an empty square
+    pub fn new_drop(piece: Piece, to: Square) -> Move {
+        Move {
+            drop: true,
+            ..Move::new(to, to, piece, None, None, false)
         }
     }
 
     //requires board so move information can be added
     pub fn from_uci(uci: &str, board: Board) -> Move {
-        let from = square_from_uci(&uci[..2]);
-        let to = square_from_uci(&uci[2..4]);
+        // drops are written with the piece letter, "N@f3"
+        if let Some((piece, to)) = uci.split_once('@') {
+            let piece_type = piece
+                .chars()
+                .next()
+                .and_then(PieceType::from_char)
+                .expect("invalid piece in uci drop");
+            let to: Square = to.parse().expect("invalid square in uci move");
+
+            return Move::new_drop(Piece::new(board.turn, piece_type), to);
+        }
+
+        let from: Square = uci[..2].parse().expect("invalid square in uci move");
+        let to: Square = uci[2..4].parse().expect("invalid square in uci move");
         let promotion = uci.chars().nth(4);
-        // println!("{:?}, {:?}", from, to);
+        let piece = board
+            .piece_at(from)
+            .expect("no piece on the from square of the uci move");
+        let en_passant =
+            piece.piece_type() == PieceType::Pawn && board.en_passant_square == Some(to);
+
         Move::new(
             from,
             to,
-            board.board[from.0 as usize][from.1 as usize],
-            match board.board[to.0 as usize][to.1 as usize] {
-                0 => match board.en_passant_square {
-                    Some(sq) => {
-                        if to == sq {
-                            Some(if board.turn { WHITE } else { BLACK } | PAWN)
-                        } else {
-                            None
-                        }
-                    }
-                    None => None,
-                },
-                piece => Some(piece),
-            },
-            match promotion {
-                Some(c) => match c {
-                    'n' => Some(
-                        get_piece_color!(board.board[from.0 as usize][from.1 as usize]) | KNIGHT,
-                    ),
-                    'b' => Some(
-                        get_piece_color!(board.board[from.0 as usize][from.1 as usize]) | BISHOP,
-                    ),
-                    'r' => {
-                        Some(get_piece_color!(board.board[from.0 as usize][from.1 as usize]) | ROOK)
-                    }
-                    'q' => Some(
-                        get_piece_color!(board.board[from.0 as usize][from.1 as usize]) | QUEEN,
-                    ),
-                    _ => {
-                        panic!("promotion in uci string is invalid! :skull:")
-                    }
-                },
-                None => None,
-            },
-            match board.en_passant_square {
-                Some(square) => square == to,
-                None => false,
+            piece,
+            if en_passant {
+                Some(Piece::new(!piece.color(), PieceType::Pawn))
+            } else {
+                board.piece_at(to)
             },
+            promotion.map(|c| match PieceType::from_char(c) {
+                Some(piece_type)
+                    if PieceType::PROMOTIONS.contains(&piece_type)
+                        || (piece_type == PieceType::King && board.variant == Variant::Antichess) =>
+                {
+                    Piece::new(piece.color(), piece_type)
+                }
+                _ => panic!("promotion in uci string is invalid! :skull:"),
+            }),
+            en_passant,
         )
     }
 
     pub fn to_uci(&self) -> String {
-        let mut s = String::new();
-
-        s += ["a", "b", "c", "d", "e", "f", "g", "h"][self.from.1 as usize];
-        s += ["8", "7", "6", "5", "4", "3", "2", "1"][self.from.0 as usize];
-        s += ["a", "b", "c", "d", "e", "f", "g", "h"][self.to.1 as usize];
-        s += ["8", "7", "6", "5", "4", "3", "2", "1"][self.to.0 as usize];
-
-        s += match self.promotion_piece {
-            Some(piece) => match get_piece_type!(piece) {
-                KNIGHT => "n",
-                BISHOP => "b",
-                ROOK => "r",
-                QUEEN => "q",
-                _ => panic!("invalid promotion type! (again) :skull:"),
-            },
-            None => "",
-        };
+        if self.drop {
+            return format!(
+                "{}@{}",
+                self.piece.piece_type().to_char().to_ascii_uppercase(),
+                self.to
+            );
+        }
+
+        let mut s = format!("{}{}", self.from, self.to);
+
+        if let Some(piece) = self.promotion_piece {
+            s.push(piece.piece_type().to_char());
+        }
 
         s
     }
@@ -250,6 +260,34 @@ impl Display for Move {
     }
 }
 
+// the state make_move cant recover from the move itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UndoInfo {
+    pub capture_piece: Option<Piece>,
+    pub castle_state: CastleState,
+    pub en_passant_square: Option<Square>,
+    pub halfmoves: u16,
+    pub hash: u64,
+    pub check_info: Option<CheckInfo>,
+    pub checks: [u8; 2],
+    pub pockets: [Pocket; 2],
+    pub promoted: u64,
+    pub explosion: Explosion,
+}
+
+/// checks and pins of a position. the move generator computes it the first time it is needed
+/// (`movegen::Attacks`) and the board keeps it until the next move
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CheckInfo {
+    /// the pieces giving check to the side to move
+    pub checkers: u64,
+    /// blockers[color] are the pieces (of either color) that are the only piece between the king
+    /// of that color and an enemy slider. the ones of the king's own color are pinned
+    pub blockers: [u64; 2],
+    /// the squares a piece of the side to move gives check from, indexed by piece type
+    pub check_squares: [u64; 6],
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Bitboards {
     pub pawns: u64,
@@ -261,6 +299,31 @@ pub struct Bitboards {
     pub all: u64,
 }
 
+impl Bitboards {
+    /// the bitboard of one piece type
+    pub fn get(&self, piece_type: PieceType) -> u64 {
+        match piece_type {
+            PieceType::Pawn => self.pawns,
+            PieceType::Knight => self.knights,
+            PieceType::Bishop => self.bishops,
+            PieceType::Rook => self.rooks,
+            PieceType::Queen => self.queens,
+            PieceType::King => self.king,
+        }
+    }
+
+    pub fn get_mut(&mut self, piece_type: PieceType) -> &mut u64 {
+        match piece_type {
+            PieceType::Pawn => &mut self.pawns,
+            PieceType::Knight => &mut self.knights,
+            PieceType::Bishop => &mut self.bishops,
+            PieceType::Rook => &mut self.rooks,
+            PieceType::Queen => &mut self.queens,
+            PieceType::King => &mut self.king,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct HashKeys {
     pub white_pawn: [[u64; 8]; 8],
@@ -286,6 +349,12 @@ pub struct HashKeys {
 
     // one key for each file
     pub en_passant_square_file: [u64; 8],
+
+    // checks given by each color in three-check, for one, two and three checks
+    pub checks: [[u64; 3]; 2],
+
+    // crazyhouse pockets, one key for every piece of a type in hand (the first, the second...)
+    pub pockets: [[[u64; 16]; 5]; 2],
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -293,769 +362,509 @@ pub struct Board {
     // board[rank][file]
     // rank index: 8th rank -> 0, 1st rank -> 7
     // file index: a file -> 0, h file -> 7
-    pub board: [[Piece; 8]; 8],
+    pub board: [[Option<Piece>; 8]; 8],
     //ranks are stored continuously (row-major), ones bit is h1, highest is a8 (to make visualization easier)
     pub white: Bitboards,
     pub black: Bitboards,
-    pub turn: bool,
+    pub turn: Color,
     pub castle_state: CastleState,
 
-    // (rank, file)
-    pub en_passant_square: Option<(u8, u8)>,
+    pub en_passant_square: Option<Square>,
+    // plies since the last capture or pawn move, for the fifty move rule
+    pub halfmoves: u16,
     //incremented after black's move
     pub fullmoves: u16,
-    //precomputed for knight moves
-    pub knight_masks: [[u64; 8]; 8],
-    // precomputed for king moves
-    pub king_masks: [[u64; 8]; 8],
-    // precomputed ray attacks (shifted bitboards)
-    // ray_attacks[direction][rank][file]
-    pub ray_attacks: [[[u64; 8]; 8]; 8],
-    // precomputed bishop and rook blocker masks (magic bitboards)
-    pub bishop_blocker_masks: [[u64; 8]; 8],
-    pub rook_blocker_masks: [[u64; 8]; 8],
-    // obstructed[from][to] gives bitboard of the squares inbetween the two
-    pub obstructed: [[u64; 64]; 64],
-
-    pub white_king_position: (u8, u8),
-    pub black_king_position: (u8, u8),
-
-    pub hash_keys: HashKeys,
+
+    pub white_king_position: Square,
+    pub black_king_position: Square,
+
     pub hash: u64,
+    // cleared by every move, code that changes the fields directly has to clear it as well
+    pub check_info: Option<CheckInfo>,
+
+    pub variant: Variant,
+    // checks given by each color, only counted in three-check
+    pub checks: [u8; 2],
+    // the captured pieces each color can drop, only used in crazyhouse
+    pub pockets: [Pocket; 2],
+    // pieces that were pawns before they promoted, they go back into a pocket as pawns
+    pub promoted: u64,
 }
 
 impl Board {
+    /// the piece on a square, none if it is empty
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        let (row, column) = square.mailbox();
+        self.board[row][column]
+    }
+
+    // only changes the mailbox, the bitboards and the hash are updated separately
+    fn set_piece_at(&mut self, square: Square, piece: Option<Piece>) {
+        let (row, column) = square.mailbox();
+        self.board[row][column] = piece;
+    }
+
+    pub fn bitboards(&self, color: Color) -> &Bitboards {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    fn bitboards_mut(&mut self, color: Color) -> &mut Bitboards {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+
+    /// square of the king of the given color
+    pub fn king_square(&self, color: Color) -> Square {
+        match color {
+            Color::White => self.white_king_position,
+            Color::Black => self.black_king_position,
+        }
+    }
+
     fn update_bitboards(&mut self, move_to_make: Move) {
-        let color = get_piece_color!(move_to_make.piece);
-        let piece_type = get_piece_type!(move_to_make.piece);
-        let from_mask = 1 << get_bit_index!(move_to_make.from);
-        let to_mask = 1 << get_bit_index!(move_to_make.to);
+        let color = move_to_make.piece.color();
+        let from_mask = move_to_make.from.bitboard().0;
+        let to_mask = move_to_make.to.bitboard().0;
         let move_mask = from_mask | to_mask;
 
         // update bitboard
-        if color == WHITE {
-            self.white.all ^= move_mask;
-
-            match piece_type {
-                PAWN => {
-                    self.white.pawns ^= from_mask;
-
-                    // promotions require different handling of the "to" square
-                    match move_to_make.promotion_piece {
-                        Some(piece) => match get_piece_type!(piece) {
-                            BISHOP => {
-                                self.white.bishops ^= to_mask;
-                            }
-                            KNIGHT => {
-                                self.white.knights ^= to_mask;
-                            }
-                            ROOK => {
-                                self.white.rooks ^= to_mask;
-                            }
-                            QUEEN => {
-                                self.white.queens ^= to_mask;
-                            }
-                            _ => {
-                                panic!("invalid promotion piece type! :skull:")
-                            }
-                        },
-                        None => {
-                            self.white.pawns ^= to_mask;
-                        }
-                    }
-                }
-                BISHOP => {
-                    self.white.bishops ^= move_mask;
-                }
-                KNIGHT => {
-                    self.white.knights ^= move_mask;
-                }
-                ROOK => {
-                    self.white.rooks ^= move_mask;
-                }
-                QUEEN => {
-                    self.white.queens ^= move_mask;
-                }
-                KING => {
-                    self.white.king ^= move_mask;
-                    if move_mask == WHITE_KINGSIDE_MOVE_MASK {
-                        self.white.rooks ^= WHITE_KINGSIDE_MASK;
-                        self.white.all ^= WHITE_KINGSIDE_MASK;
-                    } else if move_mask == WHITE_QUEENSIDE_MOVE_MASK {
-                        self.white.rooks ^= WHITE_QUEENSIDE_MASK;
-                        self.white.all ^= WHITE_QUEENSIDE_MASK;
-                    }
-                }
-                _ => {
-                    panic!("invalid piece type :skull:")
-                }
-            }
+        let ours = self.bitboards_mut(color);
+        ours.all ^= move_mask;
+        *ours.get_mut(move_to_make.piece.piece_type()) ^= from_mask;
+        // promotions require different handling of the "to" square
+        let to_piece = move_to_make.promotion_piece.unwrap_or(move_to_make.piece);
+        *ours.get_mut(to_piece.piece_type()) ^= to_mask;
+
+        if move_to_make.piece.piece_type() == PieceType::King {
+            let rook_mask = match (color, move_mask) {
+                (Color::White, WHITE_KINGSIDE_MOVE_MASK) => WHITE_KINGSIDE_MASK,
+                (Color::White, WHITE_QUEENSIDE_MOVE_MASK) => WHITE_QUEENSIDE_MASK,
+                (Color::Black, BLACK_KINGSIDE_MOVE_MASK) => BLACK_KINGSIDE_MASK,
+                (Color::Black, BLACK_QUEENSIDE_MOVE_MASK) => BLACK_QUEENSIDE_MASK,
+                _ => 0,
+            };
+            ours.rooks ^= rook_mask;
+            ours.all ^= rook_mask;
+        }
 
-            if move_to_make.en_passant {
-                self.black.pawns ^= to_mask >> 8;
-                self.black.all ^= to_mask >> 8;
-            } else if let Some(piece) =move_to_make.capture_piece {
-                        self.black.all ^= to_mask;
-                        match get_piece_type!(piece) {
-                            PAWN => {
-                                self.black.pawns ^= to_mask;
-                            }
-                            BISHOP => {
-                                self.black.bishops ^= to_mask;
-                            }
-                            KNIGHT => {
-                                self.black.knights ^= to_mask;
-                            }
-                            ROOK => {
-                                self.black.rooks ^= to_mask;
-                            }
-                            QUEEN => {
-                                self.black.queens ^= to_mask;
-                            }
-                            _ => {
-                                panic!("invalid capture piece type! :skull:")
-                            }
-                        }
-                    }
-
-        } else if color == BLACK {
-            self.black.all ^= move_mask;
-
-            match piece_type {
-                PAWN => {
-                    self.black.pawns ^= from_mask;
-
-                    // promotions require different handling of the "to" square
-                    match move_to_make.promotion_piece {
-                        Some(piece) => match get_piece_type!(piece) {
-                            BISHOP => {
-                                self.black.bishops ^= to_mask;
-                            }
-                            KNIGHT => {
-                                self.black.knights ^= to_mask;
-                            }
-                            ROOK => {
-                                self.black.rooks ^= to_mask;
-                            }
-                            QUEEN => {
-                                self.black.queens ^= to_mask;
-                            }
-                            _ => {
-                                panic!("invalid promotion piece type! :skull:")
-                            }
-                        },
-                        None => {
-                            self.black.pawns ^= to_mask;
-                        }
-                    }
-                }
-                BISHOP => {
-                    self.black.bishops ^= move_mask;
-                }
-                KNIGHT => {
-                    self.black.knights ^= move_mask;
-                }
-                ROOK => {
-                    self.black.rooks ^= move_mask;
-                }
-                QUEEN => {
-                    self.black.queens ^= move_mask;
-                }
-                KING => {
-                    self.black.king ^= move_mask;
-                    if move_mask == BLACK_KINGSIDE_MOVE_MASK {
-                        self.black.rooks ^= BLACK_KINGSIDE_MASK;
-                        self.black.all ^= BLACK_KINGSIDE_MASK;
-                    } else if move_mask == BLACK_QUEENSIDE_MOVE_MASK {
-                        self.black.rooks ^= BLACK_QUEENSIDE_MASK;
-                        self.black.all ^= BLACK_QUEENSIDE_MASK;
-                    }
-                }
-                _ => {
-                    panic!("invalid piece type :skull:")
-                }
-            }
+        if let Some(piece) = move_to_make.capture_piece {
+            // the pawn captured en passant is behind the target square
+            let capture_mask = match (move_to_make.en_passant, color) {
+                (false, _) => to_mask,
+                (true, Color::White) => to_mask >> 8,
+                (true, Color::Black) => to_mask << 8,
+            };
 
-            if move_to_make.en_passant {
-                self.white.pawns ^= to_mask << 8;
-                self.white.all ^= to_mask << 8;
-            } else if let Some(piece) = move_to_make.capture_piece {
-                        self.white.all ^= to_mask;
-                        match get_piece_type!(piece) {
-                            PAWN => {
-                                self.white.pawns ^= to_mask;
-                            }
-                            BISHOP => {
-                                self.white.bishops ^= to_mask;
-                            }
-                            KNIGHT => {
-                                self.white.knights ^= to_mask;
-                            }
-                            ROOK => {
-                                self.white.rooks ^= to_mask;
-                            }
-                            QUEEN => {
-                                self.white.queens ^= to_mask;
-                            }
-                            _ => {
-                                println!("{:08b}", piece);
-                                panic!("invalid capture piece type! :skull:")
-                            }
-                        }
-                    }
+            let theirs = self.bitboards_mut(!color);
+            theirs.all ^= capture_mask;
+            *theirs.get_mut(piece.piece_type()) ^= capture_mask;
         }
     }
 
     // assumes move is legal, does not check
-    pub fn make_move(&mut self, move_to_make: Move) -> impl Fn(&mut Board) {
-        // variables used to undo a move if needed
-        let prev_board = self.board;
-        let prev_white = self.white;
-        let prev_black = self.black;
-        let prev_turn = self.turn; // this one probably isn't necessary but whatever
-        let prev_castle_state = self.castle_state;
-        let prev_en_passant_square = self.en_passant_square;
-        let prev_fullmoves = self.fullmoves;
-        let prev_white_king_position = self.white_king_position;
-        let prev_black_king_position = self.black_king_position;
-        let prev_hash = self.hash;
-
-        // check that there is a color, check that color matches turn
-        assert!(
-            get_piece_color!(move_to_make.piece) == WHITE
-                || get_piece_color!(move_to_make.piece) == BLACK
-        );
-        assert_eq!((get_piece_color!(move_to_make.piece) == WHITE), self.turn);
-
-        self.hash ^= match get_piece_type!(move_to_make.piece) {
-            PAWN => {
-                if self.turn {
-                    self.hash_keys.white_pawn[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                } else {
-                    self.hash_keys.black_pawn[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                }
-            }
-            BISHOP => {
-                if self.turn {
-                    self.hash_keys.white_bishop[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                } else {
-                    self.hash_keys.black_bishop[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                }
-            }
-            KNIGHT => {
-                if self.turn {
-                    self.hash_keys.white_knight[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                } else {
-                    self.hash_keys.black_knight[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                }
-            }
-            ROOK => {
-                if self.turn {
-                    self.hash_keys.white_rook[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                } else {
-                    self.hash_keys.black_rook[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                }
-            }
-            QUEEN => {
-                if self.turn {
-                    self.hash_keys.white_queen[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                } else {
-                    self.hash_keys.black_queen[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                }
-            }
-            KING => {
-                if self.turn {
-                    self.hash_keys.white_king[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                } else {
-                    self.hash_keys.black_king[move_to_make.from.0 as usize]
-                        [move_to_make.from.1 as usize]
-                }
-            }
-            _ => panic!("invalid piece type! :skull:"),
+    // returns what is needed to take the move back with unmake_move
+    pub fn make_move(&mut self, move_to_make: Move) -> UndoInfo {
+        let mut undo = UndoInfo {
+            capture_piece: move_to_make.capture_piece,
+            castle_state: self.castle_state,
+            en_passant_square: self.en_passant_square,
+            halfmoves: self.halfmoves,
+            hash: self.hash,
+            check_info: self.check_info.take(),
+            checks: self.checks,
+            pockets: self.pockets,
+            promoted: self.promoted,
+            explosion: Explosion::default(),
         };
 
+        // check that color matches turn
+        assert_eq!(move_to_make.piece.color(), self.turn);
+
+        if move_to_make.drop {
+            self.make_drop(move_to_make);
+            return undo;
+        }
+        if self.variant == Variant::Crazyhouse {
+            self.update_pockets(move_to_make);
+        }
+
+        self.hash ^= HASH_KEYS
+            .piece_key(move_to_make.piece, move_to_make.from);
+
         //set en passant square
-        if get_piece_type!(move_to_make.piece) == PAWN
-            && ((self.turn && move_to_make.from.0 - move_to_make.to.0 == 2)
-                | (!self.turn && move_to_make.to.0 - move_to_make.from.0 == 2))
+        if let Some(square) = self.en_passant_square {
+            self.hash ^= HASH_KEYS.en_passant_square_file[square.file().index()]
+        }
+
+        let (from, to) = (move_to_make.from, move_to_make.to);
+        // horde pawns can double push from the first rank, but cant be taken en passant then
+        if move_to_make.piece.piece_type() == PieceType::Pawn
+            && from.rank().index().abs_diff(to.rank().index()) == 2
+            && from.rank() == Rank::Second.relative(self.turn.is_white())
         {
-            self.en_passant_square = Some((if self.turn { 5 } else { 2 }, move_to_make.to.1));
+            let rank = Rank::Third.relative(self.turn.is_white());
+            self.en_passant_square = Some(Square::new(to.file(), rank));
 
-            self.hash ^= self.hash_keys.en_passant_square_file[move_to_make.to.1 as usize]
+            self.hash ^= HASH_KEYS.en_passant_square_file[to.file().index()]
         } else {
-            if let Some(square) = self.en_passant_square {
-                self.hash ^= self.hash_keys.en_passant_square_file[square.1 as usize]
-            }
-
             self.en_passant_square = None;
         }
 
         // en passant captures
         if move_to_make.en_passant {
-            if self.turn {
-                self.board[move_to_make.to.0 as usize + 1][move_to_make.to.1 as usize] = 0;
-                self.hash ^= self.hash_keys.white_pawn[move_to_make.to.0 as usize]
-                    [move_to_make.to.1 as usize];
-            } else {
-                self.board[move_to_make.to.0 as usize - 1][move_to_make.to.1 as usize] = 0;
-                self.hash ^= self.hash_keys.black_pawn[move_to_make.to.0 as usize]
-                    [move_to_make.to.1 as usize];
-            }
-
-            self.hash ^= match get_piece_type!(move_to_make.capture_piece.unwrap()) {
-                PAWN => {
-                    if !self.turn {
-                        self.hash_keys.white_pawn[move_to_make.from.0 as usize - 1]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_pawn[move_to_make.from.0 as usize + 1]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                BISHOP => {
-                    if !self.turn {
-                        self.hash_keys.white_bishop[move_to_make.from.0 as usize - 1]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_bishop[move_to_make.from.0 as usize + 1]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                KNIGHT => {
-                    if !self.turn {
-                        self.hash_keys.white_knight[move_to_make.from.0 as usize - 1]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_knight[move_to_make.from.0 as usize + 1]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                ROOK => {
-                    if !self.turn {
-                        self.hash_keys.white_rook[move_to_make.from.0 as usize - 1]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_rook[move_to_make.from.0 as usize + 1]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                QUEEN => {
-                    if !self.turn {
-                        self.hash_keys.white_queen[move_to_make.from.0 as usize - 1]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_queen[move_to_make.from.0 as usize + 1]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                _ => panic!("invalid piece type! :skull:"),
-            }
+            // the captured pawn is next to the moving pawn
+            let captured = Square::new(to.file(), from.rank());
+            self.set_piece_at(captured, None);
+            self.hash ^= HASH_KEYS
+                .piece_key(move_to_make.capture_piece.unwrap(), captured);
         } else if let Some(piece) = move_to_make.capture_piece {
-            self.hash ^= match get_piece_type!(piece) {
-                PAWN => {
-                    if !self.turn {
-                        self.hash_keys.white_pawn[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_pawn[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                BISHOP => {
-                    if !self.turn {
-                        self.hash_keys.white_bishop[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_bishop[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                KNIGHT => {
-                    if !self.turn {
-                        self.hash_keys.white_knight[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_knight[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                ROOK => {
-                    if !self.turn {
-                        self.hash_keys.white_rook[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_rook[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                QUEEN => {
-                    if !self.turn {
-                        self.hash_keys.white_queen[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    } else {
-                        self.hash_keys.black_queen[move_to_make.from.0 as usize]
-                            [move_to_make.from.1 as usize]
-                    }
-                }
-                _ => panic!("invalid piece type! :skull:\n{:?}\n{}", move_to_make, self),
-            }
+            self.hash ^= HASH_KEYS.piece_key(piece, move_to_make.to);
         }
 
         // move
-        self.board[move_to_make.to.0 as usize][move_to_make.to.1 as usize] =
-            match move_to_make.promotion_piece {
-                Some(piece) => piece,
-                None => self.board[move_to_make.from.0 as usize][move_to_make.from.1 as usize],
-            };
-        self.board[move_to_make.from.0 as usize][move_to_make.from.1 as usize] = 0;
-
-        match move_to_make.promotion_piece {
-            Some(piece) => {
-                self.hash ^= match get_piece_type!(piece) {
-                    BISHOP => {
-                        if self.turn {
-                            self.hash_keys.white_bishop[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_bishop[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    KNIGHT => {
-                        if self.turn {
-                            self.hash_keys.white_knight[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_knight[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    ROOK => {
-                        if self.turn {
-                            self.hash_keys.white_rook[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_rook[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    QUEEN => {
-                        if self.turn {
-                            self.hash_keys.white_queen[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_queen[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    _ => panic!("invalid piece type! :skull:"),
-                }
-            }
-            None => {
-                self.hash ^= match get_piece_type!(move_to_make.piece) {
-                    PAWN => {
-                        if self.turn {
-                            self.hash_keys.white_pawn[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_pawn[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    BISHOP => {
-                        if self.turn {
-                            self.hash_keys.white_bishop[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_bishop[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    KNIGHT => {
-                        if self.turn {
-                            self.hash_keys.white_knight[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_knight[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    ROOK => {
-                        if self.turn {
-                            self.hash_keys.white_rook[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_rook[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    QUEEN => {
-                        if self.turn {
-                            self.hash_keys.white_queen[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_queen[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    KING => {
-                        if self.turn {
-                            self.hash_keys.white_king[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        } else {
-                            self.hash_keys.black_king[move_to_make.to.0 as usize]
-                                [move_to_make.to.1 as usize]
-                        }
-                    }
-                    _ => panic!("invalid piece type! :skull:"),
-                }
-            }
-        }
+        self.set_piece_at(
+            to,
+            Some(move_to_make.promotion_piece.unwrap_or(move_to_make.piece)),
+        );
+        self.set_piece_at(from, None);
+
+        self.hash ^= HASH_KEYS.piece_key(
+            move_to_make.promotion_piece.unwrap_or(move_to_make.piece),
+            move_to_make.to,
+        );
 
         // castling
-        if get_piece_type!(move_to_make.piece) == KING {
+        if move_to_make.piece.piece_type() == PieceType::King {
             // king position logic
-            if self.turn {
+            if self.turn.is_white() {
                 self.white_king_position = move_to_make.to;
             } else {
                 self.black_king_position = move_to_make.to;
             }
 
-            if move_to_make.from == (7, 4) && move_to_make.to == (7, 6) {
-                self.board[7][5] = get_piece_color!(move_to_make.piece) | ROOK;
-                self.board[7][7] = 0;
-            } else if move_to_make.from == (7, 4) && move_to_make.to == (7, 2) {
-                self.board[7][3] = get_piece_color!(move_to_make.piece) | ROOK;
-                self.board[7][0] = 0;
-            } else if move_to_make.from == (0, 4) && move_to_make.to == (0, 6) {
-                self.board[0][5] = get_piece_color!(move_to_make.piece) | ROOK;
-                self.board[0][7] = 0;
-            } else if move_to_make.from == (0, 4) && move_to_make.to == (0, 2) {
-                self.board[0][3] = get_piece_color!(move_to_make.piece) | ROOK;
-                self.board[0][0] = 0;
+            // (from, to) of the rook
+            let rook_move = match (from, to) {
+                (Square::E1, Square::G1) => Some((Square::H1, Square::F1)),
+                (Square::E1, Square::C1) => Some((Square::A1, Square::D1)),
+                (Square::E8, Square::G8) => Some((Square::H8, Square::F8)),
+                (Square::E8, Square::C8) => Some((Square::A8, Square::D8)),
+                _ => None,
+            };
+
+            if let Some((rook_from, rook_to)) = rook_move {
+                let rook = Piece::new(self.turn, PieceType::Rook);
+                self.set_piece_at(rook_to, Some(rook));
+                self.set_piece_at(rook_from, None);
+                self.hash ^= HASH_KEYS.piece_key(rook, rook_from)
+                    ^ HASH_KEYS.piece_key(rook, rook_to);
             }
 
-            if self.turn {
+            if self.turn.is_white() {
                 if is_white_kingside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.white_ks;
+                    self.hash ^= HASH_KEYS.white_ks;
                 }
                 if is_white_queenside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.white_qs;
+                    self.hash ^= HASH_KEYS.white_qs;
                 }
             } else {
                 if is_black_kingside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.black_ks;
+                    self.hash ^= HASH_KEYS.black_ks;
                 }
                 if is_black_queenside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.black_qs;
+                    self.hash ^= HASH_KEYS.black_qs;
                 }
             }
 
             // if king is moved, cannot castle
-            self.castle_state &= if self.turn { 0b0011 } else { 0b1100 };
-        } else if get_piece_type!(move_to_make.piece) == ROOK {
+            self.castle_state &= if self.turn.is_white() { 0b0011 } else { 0b1100 };
+        } else if move_to_make.piece.piece_type() == PieceType::Rook {
             // if rook is moved, that side can no longer castle
-            if move_to_make.from == (0, 0) {
+            if from == Square::A8 {
                 if is_black_queenside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.black_qs;
+                    self.hash ^= HASH_KEYS.black_qs;
                 }
 
                 self.castle_state &= !BLACK_QUEENSIDE;
-            } else if move_to_make.from == (0, 7) {
+            } else if from == Square::H8 {
                 if is_black_kingside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.black_ks;
+                    self.hash ^= HASH_KEYS.black_ks;
                 }
 
                 self.castle_state &= !BLACK_KINGSIDE;
-            } else if move_to_make.from == (7, 0) {
+            } else if from == Square::A1 {
                 if is_white_queenside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.white_qs;
+                    self.hash ^= HASH_KEYS.white_qs;
                 }
 
                 self.castle_state &= !WHITE_QUEENSIDE;
-            } else if move_to_make.from == (7, 7) {
+            } else if from == Square::H1 {
                 if is_white_kingside!(self.castle_state) {
-                    self.hash ^= self.hash_keys.white_ks;
+                    self.hash ^= HASH_KEYS.white_ks;
                 }
 
                 self.castle_state &= !WHITE_KINGSIDE;
             }
         }
         // if rook is captured, can no longer castle
-        if move_to_make.to == (0, 0) {
+        if to == Square::A8 {
             if is_black_queenside!(self.castle_state) {
-                self.hash ^= self.hash_keys.black_qs;
+                self.hash ^= HASH_KEYS.black_qs;
             }
             self.castle_state &= !BLACK_QUEENSIDE;
-        } else if move_to_make.to == (0, 7) {
+        } else if to == Square::H8 {
             if is_black_kingside!(self.castle_state) {
-                self.hash ^= self.hash_keys.black_ks;
+                self.hash ^= HASH_KEYS.black_ks;
             }
             self.castle_state &= !BLACK_KINGSIDE;
-        } else if move_to_make.to == (7, 0) {
+        } else if to == Square::A1 {
             if is_white_queenside!(self.castle_state) {
-                self.hash ^= self.hash_keys.white_qs;
+                self.hash ^= HASH_KEYS.white_qs;
             }
             self.castle_state &= !WHITE_QUEENSIDE;
-        } else if move_to_make.to == (7, 7) {
+        } else if to == Square::H1 {
             if is_white_kingside!(self.castle_state) {
-                self.hash ^= self.hash_keys.white_ks;
+                self.hash ^= HASH_KEYS.white_ks;
             }
             self.castle_state &= !WHITE_KINGSIDE;
         }
 
         self.update_bitboards(move_to_make);
 
-        if !self.turn {
+        if self.variant == Variant::Atomic && move_to_make.capture_piece.is_some() {
+            undo.explosion = self.explode(move_to_make);
+        }
+
+        if move_to_make.piece.piece_type() == PieceType::Pawn
+            || move_to_make.capture_piece.is_some()
+        {
+            self.halfmoves = 0;
+        } else {
+            self.halfmoves += 1;
+        }
+
+        if self.turn == Color::Black {
             self.fullmoves += 1
         }
         self.turn = !self.turn;
-        self.hash ^= self.hash_keys.turn_key;
-
-        move |board: &mut Board| {
-            board.board = prev_board;
-            board.white = prev_white;
-            board.black = prev_black;
-            board.turn = prev_turn;
-            board.castle_state = prev_castle_state;
-            board.en_passant_square = prev_en_passant_square;
-            board.fullmoves = prev_fullmoves;
-            board.white_king_position = prev_white_king_position;
-            board.black_king_position = prev_black_king_position;
-            board.hash = prev_hash;
+        self.hash ^= HASH_KEYS.turn_key;
+
+        if self.variant == Variant::ThreeCheck && self.king_attacked(self.turn) {
+            let mover = !self.turn;
+            let checks = &mut self.checks[mover.index()];
+            self.hash ^= HASH_KEYS.checks_key(mover, *checks);
+            *checks += 1;
+            self.hash ^= HASH_KEYS.checks_key(mover, *checks);
         }
+
+        #[cfg(feature = "validate")]
+        self.debug_validate();
+
+        undo
     }
-}
 
-impl Display for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut board_str: String = String::from(" abcdefgh\n");
-        let mut rank_index: u8 = 0;
-        for rank in self.board {
-            board_str += &(8 - rank_index).to_string();
-            for square in rank {
-                board_str += match get_piece_type!(square) {
-                    PAWN => match get_piece_color!(square) {
-                        WHITE => "P",
-                        BLACK => "p",
-                        _ => "",
-                    },
-                    BISHOP => match get_piece_color!(square) {
-                        WHITE => "B",
-                        BLACK => "b",
-                        _ => "",
-                    },
-                    KNIGHT => match get_piece_color!(square) {
-                        WHITE => "N",
-                        BLACK => "n",
-                        _ => "",
-                    },
-                    ROOK => match get_piece_color!(square) {
-                        WHITE => "R",
-                        BLACK => "r",
-                        _ => "",
-                    },
-                    QUEEN => match get_piece_color!(square) {
-                        WHITE => "Q",
-                        BLACK => "q",
-                        _ => "",
-                    },
-                    KING => match get_piece_color!(square) {
-                        WHITE => "K",
-                        BLACK => "k",
-                        _ => "",
-                    },
-                    _ => ".",
-                }
+    // takes back a move made with make_move, the board has to be in the position right after it
+    pub fn unmake_move(&mut self, m: Move, undo: UndoInfo) {
+        if m.drop {
+            self.unmake_drop(m, undo);
+            return;
+        }
+
+        self.turn = !self.turn;
+        if self.turn == Color::Black {
+            self.fullmoves -= 1;
+        }
+
+        if self.variant == Variant::Atomic && m.capture_piece.is_some() {
+            self.unexplode(m, undo.explosion);
+        }
+
+        // the bitboards are updated with xor, doing it again reverses it
+        self.update_bitboards(m);
+
+        self.set_piece_at(m.from, Some(m.piece));
+        if m.en_passant {
+            self.set_piece_at(m.to, None);
+            self.set_piece_at(Square::new(m.to.file(), m.from.rank()), undo.capture_piece);
+        } else {
+            self.set_piece_at(m.to, undo.capture_piece);
+        }
+
+        if m.piece.piece_type() == PieceType::King {
+            if self.turn.is_white() {
+                self.white_king_position = m.from;
+            } else {
+                self.black_king_position = m.from;
             }
-            board_str += &(rank_index.to_string() + " | ");
-
-            if rank_index == 0 {
-                board_str += &("pawns:   ".to_owned()
-                    + &format!("{:064b}", self.white.pawns)
-                    + " | "
-                    + &format!("{:064b}", self.black.pawns));
-            } else if rank_index == 1 {
-                board_str += &("bishops: ".to_owned()
-                    + &format!("{:064b}", self.white.bishops)
-                    + " | "
-                    + &format!("{:064b}", self.black.bishops));
-            } else if rank_index == 2 {
-                board_str += &("knights: ".to_owned()
-                    + &format!("{:064b}", self.white.knights)
-                    + " | "
-                    + &format!("{:064b}", self.black.knights));
-            } else if rank_index == 3 {
-                board_str += &("rooks:   ".to_owned()
-                    + &format!("{:064b}", self.white.rooks)
-                    + " | "
-                    + &format!("{:064b}", self.black.rooks));
-            } else if rank_index == 4 {
-                board_str += &("queens:  ".to_owned()
-                    + &format!("{:064b}", self.white.queens)
-                    + " | "
-                    + &format!("{:064b}", self.black.queens));
-            } else if rank_index == 5 {
-                board_str += &("king:    ".to_owned()
-                    + &format!("{:064b}", self.white.king)
-                    + " | "
-                    + &format!("{:064b}", self.black.king));
-            } else if rank_index == 6 {
-                board_str += &("all:     ".to_owned()
-                    + &format!("{:064b}", self.white.all)
-                    + " | "
-                    + &format!("{:064b}", self.black.all))
-            } else if rank_index == 7 {
-                match self.en_passant_square {
-                    Some(square) => board_str += &format!("{:?} ", square),
-                    None => board_str += "No En Passant Square ",
-                }
-                board_str += if is_white_kingside!(self.castle_state) {
-                    "K"
-                } else {
-                    ""
-                };
-                board_str += if is_white_queenside!(self.castle_state) {
-                    "Q"
-                } else {
-                    ""
-                };
-                board_str += if is_black_kingside!(self.castle_state) {
-                    "k"
+
+            // castling, put the rook back in the corner
+            let distance = m.from.file().index().abs_diff(m.to.file().index());
+            if m.from.file() == File::E && distance == 2 {
+                let (rook_from, rook_to) = if m.to.file() == File::G {
+                    (File::H, File::F)
                 } else {
-                    ""
+                    (File::A, File::D)
                 };
-                board_str += if is_black_queenside!(self.castle_state) {
-                    "q"
-                } else {
-                    ""
+                let rank = m.from.rank();
+                let rook = self.piece_at(Square::new(rook_to, rank));
+                self.set_piece_at(Square::new(rook_from, rank), rook);
+                self.set_piece_at(Square::new(rook_to, rank), None);
+            }
+        }
+
+        self.castle_state = undo.castle_state;
+        self.en_passant_square = undo.en_passant_square;
+        self.halfmoves = undo.halfmoves;
+        self.hash = undo.hash;
+        self.check_info = undo.check_info;
+        self.checks = undo.checks;
+        self.pockets = undo.pockets;
+        self.promoted = undo.promoted;
+
+        #[cfg(feature = "validate")]
+        self.debug_validate();
+    }
+
+    // passes the turn to the opponent without moving a piece (used for null move pruning)
+    // should never be called when the side to move is in check
+    pub fn make_null_move(&mut self) -> UndoInfo {
+        let undo = UndoInfo {
+            capture_piece: None,
+            castle_state: self.castle_state,
+            en_passant_square: self.en_passant_square,
+            halfmoves: self.halfmoves,
+            hash: self.hash,
+            check_info: self.check_info.take(),
+            checks: self.checks,
+            pockets: self.pockets,
+            promoted: self.promoted,
+            explosion: Explosion::default(),
+        };
+
+        // en passant is only possible directly after the double push
+        if let Some(square) = self.en_passant_square {
+            self.hash ^= HASH_KEYS.en_passant_square_file[square.file().index()];
+        }
+        self.en_passant_square = None;
+
+        if self.turn == Color::Black {
+            self.fullmoves += 1
+        }
+        self.turn = !self.turn;
+        self.hash ^= HASH_KEYS.turn_key;
+
+        #[cfg(feature = "validate")]
+        self.debug_validate();
+
+        undo
+    }
+
+    pub fn unmake_null_move(&mut self, undo: UndoInfo) {
+        self.turn = !self.turn;
+        if self.turn == Color::Black {
+            self.fullmoves -= 1;
+        }
+
+        self.en_passant_square = undo.en_passant_square;
+        self.hash = undo.hash;
+        self.check_info = undo.check_info;
+        self.checks = undo.checks;
+
+        #[cfg(feature = "validate")]
+        self.debug_validate();
+    }
+
+    /// the position of a fen, BoardBuilder for anything but the fen
+    pub fn from_fen(fen: &str) -> Result<Board, String> {
+        Board::from_variant_fen(Variant::Standard, fen)
+    }
+
+    /// the position of a fen with the rules of the variant
+    pub fn from_variant_fen(variant: Variant, fen: &str) -> Result<Board, String> {
+        Ok(BoardBuilder::new()
+            .set_variant(variant)
+            .try_set_position(fen)?
+            .build())
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        let crazyhouse = self.variant == Variant::Crazyhouse;
+        for (r, rank) in self.board.iter().enumerate() {
+            let mut empty = 0;
+            for (f, &square) in rank.iter().enumerate() {
+                let Some(piece) = square else {
+                    empty += 1;
+                    continue;
                 };
 
-                board_str += &format!(" hash: {:064b}", self.hash);
+                if empty > 0 {
+                    fen += &empty.to_string();
+                    empty = 0;
+                }
+
+                fen.push(piece.to_char());
+                // promoted pieces are marked with a tilde in crazyhouse
+                let promoted = self.promoted >> get_bit_index!(r, f) & 1;
+                if crazyhouse && promoted != 0 {
+                    fen.push('~');
+                }
             }
 
-            board_str += "\n";
-            rank_index += 1;
+            if empty > 0 {
+                fen += &empty.to_string();
+            }
+            if r < 7 {
+                fen.push('/');
+            }
         }
-        board_str += " 01234567\n";
-        board_str += if self.turn {
-            "White to move"
-        } else {
-            "Black to move"
+        if crazyhouse {
+            fen += &crazyhouse::pockets_to_fen(&self.pockets);
+        }
+
+        fen += if self.turn.is_white() { " w " } else { " b " };
+
+        let mut castling = String::new();
+        if is_white_kingside!(self.castle_state) {
+            castling.push('K');
+        }
+        if is_white_queenside!(self.castle_state) {
+            castling.push('Q');
+        }
+        if is_black_kingside!(self.castle_state) {
+            castling.push('k');
+        }
+        if is_black_queenside!(self.castle_state) {
+            castling.push('q');
+        }
+        fen += if castling.is_empty() { "-" } else { &castling };
+
+        fen += &match self.en_passant_square {
+            Some(square) => format!(" {} ", square),
+            None => " - ".to_owned(),
         };
 
-        f.write_str(&board_str)
+        // the checks each side still has to give, like the fen of other three-check engines
+        if self.variant == Variant::ThreeCheck {
+            let remaining =
+                |color: Color| variant::CHECKS_TO_WIN.saturating_sub(self.checks[color.index()]);
+            fen += &format!("{}+{} ", remaining(Color::White), remaining(Color::Black));
+        }
+
+        fen + &format!("{} {}", self.halfmoves, self.fullmoves)
+    }
+}
+
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render_text(&RenderOptions::default()))?;
+        writeln!(f, "Fen: {}", self.to_fen())?;
+        write!(f, "Key: {:016x}", self.hash)
     }
 }
 
@@ -1063,11 +872,17 @@ pub struct BoardBuilder {
     board: Board,
 }
 
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BoardBuilder {
     pub fn new() -> BoardBuilder {
         BoardBuilder {
             board: Board {
-                board: [[0; 8]; 8],
+                board: [[None; 8]; 8],
                 white: Bitboards {
                     pawns: 0u64,
                     bishops: 0u64,
@@ -1086,25 +901,30 @@ impl BoardBuilder {
                     king: 0u64,
                     all: 0u64,
                 },
-                turn: WHITE_TO_MOVE,
+                turn: Color::White,
                 castle_state: 0,
                 en_passant_square: None,
+                halfmoves: 0,
                 fullmoves: 0,
-                knight_masks: [[0; 8]; 8],
-                king_masks: [[0; 8]; 8],
-                ray_attacks: [[[0; 8]; 8]; 8],
-                bishop_blocker_masks: [[0; 8]; 8],
-                rook_blocker_masks: [[0; 8]; 8],
-                white_king_position: (0, 0),
-                black_king_position: (0, 0),
-                obstructed: [[0; 64]; 64],
-                hash_keys: HashKeys::new(),
+                white_king_position: Square::A8,
+                black_king_position: Square::A8,
                 hash: 0,
+                check_info: None,
+                variant: Variant::Standard,
+                checks: [0; 2],
+                pockets: [Pocket::default(); 2],
+                promoted: 0,
             },
         }
     }
 
-    pub fn set_turn(&mut self, to_move: bool) -> &mut BoardBuilder {
+    /// the variant of the board, setting it before or after the position makes no difference
+    pub fn set_variant(&mut self, variant: Variant) -> &mut BoardBuilder {
+        self.board.variant = variant;
+        self
+    }
+
+    pub fn set_turn(&mut self, to_move: Color) -> &mut BoardBuilder {
         self.board.turn = to_move;
         self
     }
@@ -1137,8 +957,14 @@ impl BoardBuilder {
         self
     }
 
-    pub fn set_en_passant_square(&mut self, rank: u8, file: u8) -> &mut BoardBuilder {
-        self.board.en_passant_square = Some((rank, file));
+    pub fn set_en_passant_square(&mut self, square: Square) -> &mut BoardBuilder {
+        self.board.en_passant_square = Some(square);
+
+        self
+    }
+
+    pub fn set_halfmoves(&mut self, halfmoves: u16) -> &mut BoardBuilder {
+        self.board.halfmoves = halfmoves;
 
         self
     }
@@ -1149,123 +975,140 @@ impl BoardBuilder {
         self
     }
 
+    /// panics on a malformed fen, try_set_position is for fens that can come from anywhere
     pub fn set_position(&mut self, fen: String) -> &mut BoardBuilder {
+        if let Err(e) = self.try_set_position(&fen) {
+            panic!("{}", e);
+        }
+
+        self
+    }
+
+    /// the position of the fen, or what is wrong with it. the builder is left as it was when the
+    /// fen is malformed
+    pub fn try_set_position(&mut self, fen: &str) -> Result<&mut BoardBuilder, String> {
+        let invalid = |reason: &str| format!("invalid fen \"{}\": {}", fen, reason);
+        let mut board = self.board;
+
         let tokens: Vec<&str> = fen.split(' ').collect();
+        if tokens.len() < 6 {
+            return Err(invalid("missing fields"));
+        }
 
-        let mut index: usize = 63;
-        for row in tokens[0].split('/') {
+        // crazyhouse fens have the pockets in brackets after the pieces
+        let placement = match tokens[0].split_once('[') {
+            Some((placement, pockets)) => {
+                board.pockets = pockets
+                    .strip_suffix(']')
+                    .and_then(crazyhouse::pockets_from_fen)
+                    .ok_or_else(|| invalid("invalid pockets"))?;
+                placement
+            }
+            None => tokens[0],
+        };
+
+        let rows: Vec<&str> = placement.split('/').collect();
+        if rows.len() != 8 {
+            return Err(invalid("the board needs 8 ranks"));
+        }
+        for (r, row) in rows.into_iter().enumerate() {
+            let mut f = 0;
+            let mut last_piece = None;
             for c in row.chars() {
-                if !c.is_ascii_digit() {
-                    if c.is_uppercase() {
-                        self.board.white.all |= 0b1 << index;
-                    } else {
-                        self.board.black.all |= 0b1 << index;
-                    }
+                if c == '~' {
+                    // a promoted piece is marked with a tilde after its letter
+                    let square: Square = last_piece.take().ok_or_else(|| invalid("stray tilde"))?;
+                    board.promoted |= square.bitboard().0;
+                    continue;
                 }
+                last_piece = None;
 
-                self.board.board[7 - index / 8][7 - index % 8] = match c {
-                    'P' => {
-                        self.board.white.pawns |= 0b1 << index;
-                        WHITE | PAWN
-                    }
-                    'p' => {
-                        self.board.black.pawns |= 0b1 << index;
-                        BLACK | PAWN
-                    }
-                    'B' => {
-                        self.board.white.bishops |= 0b1 << index;
-                        WHITE | BISHOP
-                    }
-                    'b' => {
-                        self.board.black.bishops |= 0b1 << index;
-                        BLACK | BISHOP
-                    }
-                    'N' => {
-                        self.board.white.knights |= 0b1 << index;
-                        WHITE | KNIGHT
-                    }
-                    'n' => {
-                        self.board.black.knights |= 0b1 << index;
-                        BLACK | KNIGHT
-                    }
-                    'R' => {
-                        self.board.white.rooks |= 0b1 << index;
-                        WHITE | ROOK
-                    }
-                    'r' => {
-                        self.board.black.rooks |= 0b1 << index;
-                        BLACK | ROOK
-                    }
-                    'Q' => {
-                        self.board.white.queens |= 0b1 << index;
-                        WHITE | QUEEN
-                    }
-                    'q' => {
-                        self.board.black.queens |= 0b1 << index;
-                        BLACK | QUEEN
-                    }
-                    'K' => {
-                        self.board.white.king |= 0b1 << index;
-                        self.board.white_king_position = (7 - index as u8 / 8, 7 - index as u8 % 8);
-                        WHITE | KING
-                    }
-                    'k' => {
-                        self.board.black.king |= 0b1 << index;
-                        self.board.black_king_position = (7 - index as u8 / 8, 7 - index as u8 % 8);
-                        BLACK | KING
-                    }
-                    _ => {
-                        let num: Option<u32> = c.to_digit(10);
-                        for _ in 0..(num.unwrap_or(0) - 1) {
-                            self.board.board[7 - index / 8][7 - index % 8] = 0b0;
-                            index -= 1;
-                        }
-
-                        0b0
-                    }
-                };
+                if let Some(empty) = c.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    f += empty as usize;
+                    continue;
+                }
 
-                index -= if index > 0 { 1 } else { 0 };
-            }
-        }
+                let piece = Piece::from_char(c)
+                    .ok_or_else(|| invalid(&format!("unknown piece '{}'", c)))?;
+                if f >= 8 {
+                    return Err(invalid("a rank has more than 8 squares"));
+                }
+                let square = Square::new(File::ALL[f], Rank::ALL[7 - r]);
+                let bitboards = board.bitboards_mut(piece.color());
+                *bitboards.get_mut(piece.piece_type()) |= square.bitboard().0;
+                bitboards.all |= square.bitboard().0;
+
+                if piece == Piece::WhiteKing {
+                    board.white_king_position = square;
+                } else if piece == Piece::BlackKing {
+                    board.black_king_position = square;
+                }
 
-        self.board.turn = tokens[1] == "w";
+                board.board[r][f] = Some(piece);
+                last_piece = Some(square);
+                f += 1;
+            }
 
-        if tokens[2].contains('K') {
-            self.board.castle_state |= WHITE_KINGSIDE;
+            if f != 8 {
+                return Err(invalid("a rank does not have 8 squares"));
+            }
         }
 
-        if tokens[2].contains('Q') {
-            self.board.castle_state |= WHITE_QUEENSIDE;
-        }
+        board.turn = match tokens[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(invalid("the side to move is not w or b")),
+        };
 
-        if tokens[2].contains('k') {
-            self.board.castle_state |= BLACK_KINGSIDE;
+        if tokens[2] != "-" {
+            for c in tokens[2].chars() {
+                board.castle_state |= match c {
+                    'K' => WHITE_KINGSIDE,
+                    'Q' => WHITE_QUEENSIDE,
+                    'k' => BLACK_KINGSIDE,
+                    'q' => BLACK_QUEENSIDE,
+                    _ => return Err(invalid("invalid castling rights")),
+                };
+            }
         }
 
-        if tokens[2].contains('q') {
-            self.board.castle_state |= BLACK_QUEENSIDE;
+        if tokens[3] != "-" {
+            let square = tokens[3].parse().map_err(|_| invalid("invalid en passant square"))?;
+            board.en_passant_square = Some(square);
         }
 
-        if tokens[3] != "-" {
-            self.board.en_passant_square = Some(square_from_uci(tokens[3]));
+        // three-check fens have the remaining checks after the en passant square ("3+3"), or the
+        // checks given at the end ("+0+0", as on lichess)
+        let checks = |checks: &str| -> Result<[u8; 2], String> {
+            let (white, black) = checks.split_once('+').ok_or_else(|| invalid("invalid checks"))?;
+            match (white.parse(), black.parse()) {
+                (Ok(white), Ok(black)) => Ok([white, black]),
+                _ => Err(invalid("invalid checks")),
+            }
+        };
+        let mut counters = &tokens[4..];
+        if let Some(given) = counters.last().and_then(|tok| tok.strip_prefix('+')) {
+            board.checks = checks(given)?;
+            counters = &counters[..counters.len() - 1];
+        } else if counters[0].contains('+') {
+            let remaining = checks(counters[0])?;
+            board.checks = remaining.map(|left| variant::CHECKS_TO_WIN.saturating_sub(left));
+            counters = &counters[1..];
         }
 
-        self.board.fullmoves = tokens[5].parse().expect("could not parse fullmoves");
+        let [halfmoves, fullmoves, ..] = counters else {
+            return Err(invalid("missing move counters"));
+        };
+        board.halfmoves = halfmoves.parse().map_err(|_| invalid("invalid halfmove clock"))?;
+        board.fullmoves = fullmoves.parse().map_err(|_| invalid("invalid fullmove number"))?;
 
-        self.board.hash_keys.clone().generate_hash(&mut self.board);
+        HASH_KEYS.generate_hash(&mut board);
+        self.board = board;
 
-        self
+        Ok(self)
     }
 
     pub fn build(&mut self) -> Board {
-        self.board.generate_knight_masks();
-        self.board.generate_king_masks();
-        self.board.generate_ray_attacks();
-        self.board.generate_rook_blocker_masks();
-        self.board.generate_bishop_blocker_masks();
-        self.board.generate_obstructed();
-
         self.board
     }
 }
@@ -0,0 +1,144 @@
+// a move packed into 16 bits, for the transposition table and the other places that store a lot
+// of moves. the pieces are not stored, they are read from the board when the move is unpacked
+//
+// bits 0-5: from square (the piece type for drops), bits 6-11: to square (bit indices, 0 is h1)
+// bits 12-15: flags
+use std::fmt::{self, Display};
+
+use crate::{crazyhouse::Pocket, Board, Move, Piece, PieceType, Square};
+
+const CASTLE: u16 = 0b0001;
+const EN_PASSANT: u16 = 0b0010;
+// a crazyhouse drop, the piece is always the color to move
+const DROP: u16 = 0b0011;
+// promoting to a king, only in antichess
+const KING_PROMOTION: u16 = 0b0100;
+// the lower two bits are the promotion piece
+const PROMOTION: u16 = 0b1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move16(pub u16);
+
+// the square of a 6 bit field
+fn square_of(bits: u16) -> Square {
+    Square::from_index((bits & 0x3f) as u8).unwrap()
+}
+
+impl Move16 {
+    pub fn from_square(&self) -> Square {
+        square_of(self.0)
+    }
+
+    pub fn to_square(&self) -> Square {
+        square_of(self.0 >> 6)
+    }
+
+    fn flags(&self) -> u16 {
+        self.0 >> 12
+    }
+
+    pub fn is_castle(&self) -> bool {
+        self.flags() == CASTLE
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags() == EN_PASSANT
+    }
+
+    /// the piece type of a drop
+    pub fn drop_piece(&self) -> Option<PieceType> {
+        (self.flags() == DROP)
+            .then(|| Pocket::PIECE_TYPES.get((self.0 & 0x3f) as usize).copied())
+            .flatten()
+    }
+
+    /// promotion piece type, without a color
+    pub fn promotion(&self) -> Option<PieceType> {
+        if self.flags() == KING_PROMOTION {
+            return Some(PieceType::King);
+        }
+
+        (self.flags() & PROMOTION != 0)
+            .then(|| PieceType::PROMOTIONS[(self.flags() & 0b11) as usize])
+    }
+
+    /// the full move in the given position, the board should be the one the move was packed in.
+    /// none if there is no piece on the from square
+    pub fn to_move(self, board: &Board) -> Option<Move> {
+        let to = self.to_square();
+        if self.flags() == DROP {
+            let piece_type = self.drop_piece()?;
+            return Some(Move::new_drop(Piece::new(board.turn, piece_type), to));
+        }
+
+        let from = self.from_square();
+        let piece = board.piece_at(from)?;
+        let color = piece.color();
+
+        let capture_piece = if self.is_en_passant() {
+            Some(Piece::new(!color, PieceType::Pawn))
+        } else {
+            board.piece_at(to)
+        };
+
+        Some(Move::new(
+            from,
+            to,
+            piece,
+            capture_piece,
+            self.promotion()
+                .map(|piece_type| Piece::new(color, piece_type)),
+            self.is_en_passant(),
+        ))
+    }
+}
+
+impl From<Move> for Move16 {
+    fn from(m: Move) -> Move16 {
+        if m.drop {
+            return Move16(
+                m.piece.piece_type().index() as u16 | (m.to.index() as u16) << 6 | DROP << 12,
+            );
+        }
+
+        let flags = if let Some(piece) = m.promotion_piece {
+            match PieceType::PROMOTIONS
+                .iter()
+                .position(|&p| p == piece.piece_type())
+            {
+                Some(index) => PROMOTION | index as u16,
+                None => KING_PROMOTION,
+            }
+        } else if m.en_passant {
+            EN_PASSANT
+        } else if m.piece.piece_type() == PieceType::King
+            && m.from.file().index().abs_diff(m.to.file().index()) == 2
+        {
+            CASTLE
+        } else {
+            0
+        };
+
+        Move16(m.from.index() as u16 | (m.to.index() as u16) << 6 | flags << 12)
+    }
+}
+
+impl Display for Move16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(piece_type) = self.drop_piece() {
+            return write!(
+                f,
+                "{}@{}",
+                piece_type.to_char().to_ascii_uppercase(),
+                self.to_square()
+            );
+        }
+
+        write!(f, "{}{}", self.from_square(), self.to_square())?;
+        if let Some(piece_type) = self.promotion() {
+            write!(f, "{}", piece_type.to_char())?;
+        }
+
+        Ok(())
+    }
+}
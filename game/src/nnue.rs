@@ -0,0 +1,277 @@
+// efficiently updatable neural network evaluation
+// https://www.chessprogramming.org/NNUE
+//
+// architecture: (768 -> HIDDEN)x2 -> 1, one accumulator for each perspective
+//
+// network file format, all values are little endian i16:
+// * feature weights: 768 * HIDDEN, one row of HIDDEN weights per input
+// * feature biases: HIDDEN
+// * output weights: 2 * HIDDEN, side to move first
+// * output bias: 1
+// anything after the output bias (like padding) is ignored
+//
+// input index: (side * 6 + piece) * 64 + square, where side is 0 for the pieces of the
+// perspective and 1 for the enemy pieces, piece is pawn, knight, bishop, rook, queen, king and
+// square counts from a1 = 0 to h8 = 63. the board is flipped vertically for black's perspective
+use std::{error::Error, fs, path::Path};
+
+use crate::{Bitboard, Board, Color, File, Move, Piece, PieceType, Square, UndoInfo, Variant};
+
+pub const INPUTS: usize = 768;
+pub const HIDDEN: usize = 256;
+
+// quantisation of the feature transformer and the output layer
+const QA: i32 = 255;
+const QB: i32 = 64;
+// converts the output of the network to centipawns
+const SCALE: i32 = 400;
+
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+struct Column([i16; HIDDEN]);
+
+pub struct Network {
+    feature_weights: Vec<Column>,
+    feature_bias: Column,
+    output_weights: [Column; 2],
+    output_bias: i16,
+}
+
+/// the output of the feature transformer from both perspectives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C, align(64))]
+pub struct Accumulator {
+    pub white: [i16; HIDDEN],
+    pub black: [i16; HIDDEN],
+}
+
+/// the accumulators of the positions from the root of the search to the current one, a move
+/// pushes the accumulator of the position it leads to and taking it back pops it
+///
+/// kept by the engine rather than the board, so copying a board doesnt copy the accumulators
+pub struct AccumulatorStack {
+    network: &'static Network,
+    accumulators: Vec<Accumulator>,
+}
+
+fn feature(perspective: Color, piece: Piece, square: Square) -> usize {
+    let side = if piece.color() == perspective { 0 } else { 1 };
+    let piece_index = piece.piece_type().index();
+
+    let mut index = square.rank().index() * 8 + square.file().index();
+    if perspective == Color::Black {
+        index ^= 56;
+    }
+
+    (side * 6 + piece_index) * 64 + index
+}
+
+// kept as simple loops over fixed size arrays so the compiler can vectorise them
+fn add_weights(accumulator: &mut [i16; HIDDEN], weights: &Column) {
+    for (value, weight) in accumulator.iter_mut().zip(weights.0.iter()) {
+        *value = value.wrapping_add(*weight);
+    }
+}
+
+fn sub_weights(accumulator: &mut [i16; HIDDEN], weights: &Column) {
+    for (value, weight) in accumulator.iter_mut().zip(weights.0.iter()) {
+        *value = value.wrapping_sub(*weight);
+    }
+}
+
+fn crelu_dot(accumulator: &[i16; HIDDEN], weights: &Column) -> i32 {
+    accumulator
+        .iter()
+        .zip(weights.0.iter())
+        .map(|(&value, &weight)| value.clamp(0, QA as i16) as i32 * weight as i32)
+        .sum()
+}
+
+impl Network {
+    /// reads a network file, the network lives until the program exits
+    pub fn load(path: impl AsRef<Path>) -> Result<&'static Network, Box<dyn Error>> {
+        Network::from_bytes(&fs::read(path)?)
+    }
+
+    /// a network in the layout of the network file
+    pub fn from_bytes(bytes: &[u8]) -> Result<&'static Network, Box<dyn Error>> {
+        let expected = 2 * (INPUTS * HIDDEN + HIDDEN + 2 * HIDDEN + 1);
+        if bytes.len() < expected {
+            return Err(format!(
+                "network file is {} bytes, expected at least {}",
+                bytes.len(),
+                expected
+            )
+            .into());
+        }
+
+        let mut values = bytes
+            .chunks_exact(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]));
+        let mut column = || {
+            let mut column = Column([0; HIDDEN]);
+            for (value, read) in column.0.iter_mut().zip(&mut values) {
+                *value = read;
+            }
+            column
+        };
+
+        let feature_weights = (0..INPUTS).map(|_| column()).collect();
+        let feature_bias = column();
+        let output_weights = [column(), column()];
+        let output_bias = values.next().unwrap();
+
+        Ok(Box::leak(Box::new(Network {
+            feature_weights,
+            feature_bias,
+            output_weights,
+            output_bias,
+        })))
+    }
+
+    /// evaluation in centipawns from the perspective of the side to move
+    pub fn evaluate(&self, accumulator: &Accumulator, turn: Color) -> i32 {
+        let (us, them) = if turn == Color::White {
+            (&accumulator.white, &accumulator.black)
+        } else {
+            (&accumulator.black, &accumulator.white)
+        };
+
+        let output = crelu_dot(us, &self.output_weights[0])
+            + crelu_dot(them, &self.output_weights[1]);
+
+        (output + self.output_bias as i32) * SCALE / (QA * QB)
+    }
+
+    /// the accumulator of a board computed from scratch
+    pub fn accumulator(&self, board: &Board) -> Accumulator {
+        let mut accumulator = Accumulator {
+            white: self.feature_bias.0,
+            black: self.feature_bias.0,
+        };
+
+        for square in Bitboard(board.white.all | board.black.all) {
+            let (row, column) = square.mailbox();
+            if let Some(piece) = board.board[row][column] {
+                self.add_piece(&mut accumulator, piece, square);
+            }
+        }
+
+        accumulator
+    }
+
+    fn add_piece(&self, accumulator: &mut Accumulator, piece: Piece, square: Square) {
+        add_weights(
+            &mut accumulator.white,
+            &self.feature_weights[feature(Color::White, piece, square)],
+        );
+        add_weights(
+            &mut accumulator.black,
+            &self.feature_weights[feature(Color::Black, piece, square)],
+        );
+    }
+
+    fn remove_piece(&self, accumulator: &mut Accumulator, piece: Piece, square: Square) {
+        sub_weights(
+            &mut accumulator.white,
+            &self.feature_weights[feature(Color::White, piece, square)],
+        );
+        sub_weights(
+            &mut accumulator.black,
+            &self.feature_weights[feature(Color::Black, piece, square)],
+        );
+    }
+}
+
+type Feature = Option<(Piece, Square)>;
+
+// pieces removed from and added to the board by a move, at most two of each (captures and
+// castling)
+fn move_features(m: &Move) -> ([Feature; 2], [Feature; 2]) {
+    // a drop only adds its piece
+    if m.drop {
+        return ([None, None], [Some((m.piece, m.to)), None]);
+    }
+
+    let mut removed = [Some((m.piece, m.from)), None];
+    let mut added = [Some((m.promotion_piece.unwrap_or(m.piece), m.to)), None];
+
+    if let Some(piece) = m.capture_piece {
+        // the captured pawn is next to the moving pawn for en passant
+        let square = if m.en_passant {
+            Square::new(m.to.file(), m.from.rank())
+        } else {
+            m.to
+        };
+        removed[1] = Some((piece, square));
+    }
+
+    if m.piece.piece_type() == PieceType::King && (m.from == Square::E1 || m.from == Square::E8) {
+        let rook = Piece::new(m.piece.color(), PieceType::Rook);
+        let rank = m.from.rank();
+        if m.to.file() == File::G {
+            removed[1] = Some((rook, Square::new(File::H, rank)));
+            added[1] = Some((rook, Square::new(File::F, rank)));
+        } else if m.to.file() == File::C {
+            removed[1] = Some((rook, Square::new(File::A, rank)));
+            added[1] = Some((rook, Square::new(File::D, rank)));
+        }
+    }
+
+    (removed, added)
+}
+
+impl AccumulatorStack {
+    pub fn new(network: &'static Network, board: &Board) -> AccumulatorStack {
+        AccumulatorStack {
+            network,
+            accumulators: vec![network.accumulator(board)],
+        }
+    }
+
+    /// starts over from the board, for a new search or when the board was changed without
+    /// telling the stack
+    pub fn refresh(&mut self, board: &Board) {
+        self.accumulators.clear();
+        self.accumulators.push(self.network.accumulator(board));
+    }
+
+    /// the accumulator of the current position
+    pub fn current(&self) -> &Accumulator {
+        self.accumulators.last().unwrap()
+    }
+
+    /// called after the board made the move, with the board after the move
+    pub fn push(&mut self, board: &Board, m: &Move, undo: &UndoInfo) {
+        let mut accumulator = *self.current();
+        let (removed, added) = move_features(m);
+        for (piece, square) in removed.into_iter().flatten() {
+            self.network.remove_piece(&mut accumulator, piece, square);
+        }
+        for (piece, square) in added.into_iter().flatten() {
+            self.network.add_piece(&mut accumulator, piece, square);
+        }
+
+        // an atomic capture blows up the capturing piece and the pieces next to it
+        if board.variant == Variant::Atomic && m.capture_piece.is_some() {
+            let piece = m.promotion_piece.unwrap_or(m.piece);
+            self.network.remove_piece(&mut accumulator, piece, m.to);
+            for (square, piece) in undo.explosion.pieces() {
+                self.network.remove_piece(&mut accumulator, piece, square);
+            }
+        }
+
+        self.accumulators.push(accumulator);
+    }
+
+    /// called when the move is taken back
+    pub fn pop(&mut self) {
+        self.accumulators.pop();
+        debug_assert!(!self.accumulators.is_empty(), "popped the root accumulator");
+    }
+
+    /// evaluation of the network from the perspective of the side to move
+    pub fn evaluate(&self, turn: Color) -> i32 {
+        self.network.evaluate(self.current(), turn)
+    }
+}
@@ -0,0 +1,263 @@
+// typed colors and pieces
+//
+// Piece uses the old bit packed encoding (see the constants in lib.rs) as its representation, so
+// converting to and from it is free
+use std::{
+    fmt::{self, Display},
+    ops::Not,
+};
+
+use crate::{BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    White,
+    Black,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PieceType {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Piece {
+    WhitePawn = WHITE | PAWN,
+    WhiteKnight = WHITE | KNIGHT,
+    WhiteBishop = WHITE | BISHOP,
+    WhiteRook = WHITE | ROOK,
+    WhiteQueen = WHITE | QUEEN,
+    WhiteKing = WHITE | KING,
+    BlackPawn = BLACK | PAWN,
+    BlackKnight = BLACK | KNIGHT,
+    BlackBishop = BLACK | BISHOP,
+    BlackRook = BLACK | ROOK,
+    BlackQueen = BLACK | QUEEN,
+    BlackKing = BLACK | KING,
+}
+
+impl Color {
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
+
+    /// WHITE or BLACK, none for anything else
+    pub const fn from_bits(bits: u8) -> Option<Color> {
+        match bits {
+            WHITE => Some(Color::White),
+            BLACK => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    pub const fn bits(self) -> u8 {
+        match self {
+            Color::White => WHITE,
+            Color::Black => BLACK,
+        }
+    }
+
+    pub const fn is_white(self) -> bool {
+        matches!(self, Color::White)
+    }
+
+    /// 0 for white, 1 for black
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    pub const fn opposite(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl Not for Color {
+    type Output = Color;
+
+    fn not(self) -> Color {
+        self.opposite()
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Color::White => "white",
+            Color::Black => "black",
+        })
+    }
+}
+
+impl PieceType {
+    pub const ALL: [PieceType; 6] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+        PieceType::King,
+    ];
+
+    /// the pieces a pawn can promote to
+    pub const PROMOTIONS: [PieceType; 4] = [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ];
+
+    /// PAWN, KNIGHT etc, the color bits are ignored. none for an empty square
+    pub const fn from_bits(bits: u8) -> Option<PieceType> {
+        match bits & 0b11100 {
+            PAWN => Some(PieceType::Pawn),
+            KNIGHT => Some(PieceType::Knight),
+            BISHOP => Some(PieceType::Bishop),
+            ROOK => Some(PieceType::Rook),
+            QUEEN => Some(PieceType::Queen),
+            KING => Some(PieceType::King),
+            _ => None,
+        }
+    }
+
+    pub const fn bits(self) -> u8 {
+        match self {
+            PieceType::Pawn => PAWN,
+            PieceType::Knight => KNIGHT,
+            PieceType::Bishop => BISHOP,
+            PieceType::Rook => ROOK,
+            PieceType::Queen => QUEEN,
+            PieceType::King => KING,
+        }
+    }
+
+    /// 0 for pawns up to 5 for kings
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// lowercase letter of the piece type
+    pub const fn to_char(self) -> char {
+        match self {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+        }
+    }
+
+    /// the letter in either case
+    pub const fn from_char(c: char) -> Option<PieceType> {
+        match c.to_ascii_lowercase() {
+            'p' => Some(PieceType::Pawn),
+            'n' => Some(PieceType::Knight),
+            'b' => Some(PieceType::Bishop),
+            'r' => Some(PieceType::Rook),
+            'q' => Some(PieceType::Queen),
+            'k' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+}
+
+impl Piece {
+    pub const fn new(color: Color, piece_type: PieceType) -> Piece {
+        match (color, piece_type) {
+            (Color::White, PieceType::Pawn) => Piece::WhitePawn,
+            (Color::White, PieceType::Knight) => Piece::WhiteKnight,
+            (Color::White, PieceType::Bishop) => Piece::WhiteBishop,
+            (Color::White, PieceType::Rook) => Piece::WhiteRook,
+            (Color::White, PieceType::Queen) => Piece::WhiteQueen,
+            (Color::White, PieceType::King) => Piece::WhiteKing,
+            (Color::Black, PieceType::Pawn) => Piece::BlackPawn,
+            (Color::Black, PieceType::Knight) => Piece::BlackKnight,
+            (Color::Black, PieceType::Bishop) => Piece::BlackBishop,
+            (Color::Black, PieceType::Rook) => Piece::BlackRook,
+            (Color::Black, PieceType::Queen) => Piece::BlackQueen,
+            (Color::Black, PieceType::King) => Piece::BlackKing,
+        }
+    }
+
+    /// the bit packed encoding, none for an empty square or an invalid piece
+    pub const fn from_bits(bits: u8) -> Option<Piece> {
+        match (Color::from_bits(bits & 0b11), PieceType::from_bits(bits)) {
+            (Some(color), Some(piece_type)) if bits & !0b11111 == 0 => {
+                Some(Piece::new(color, piece_type))
+            }
+            _ => None,
+        }
+    }
+
+    pub const fn bits(self) -> u8 {
+        self as u8
+    }
+
+    pub const fn color(self) -> Color {
+        if self as u8 & WHITE != 0 {
+            Color::White
+        } else {
+            Color::Black
+        }
+    }
+
+    pub const fn piece_type(self) -> PieceType {
+        match self {
+            Piece::WhitePawn | Piece::BlackPawn => PieceType::Pawn,
+            Piece::WhiteKnight | Piece::BlackKnight => PieceType::Knight,
+            Piece::WhiteBishop | Piece::BlackBishop => PieceType::Bishop,
+            Piece::WhiteRook | Piece::BlackRook => PieceType::Rook,
+            Piece::WhiteQueen | Piece::BlackQueen => PieceType::Queen,
+            Piece::WhiteKing | Piece::BlackKing => PieceType::King,
+        }
+    }
+
+    /// fen letter, uppercase for white
+    pub const fn to_char(self) -> char {
+        let c = self.piece_type().to_char();
+        match self.color() {
+            Color::White => c.to_ascii_uppercase(),
+            Color::Black => c,
+        }
+    }
+
+    pub const fn from_char(c: char) -> Option<Piece> {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        match PieceType::from_char(c) {
+            Some(piece_type) => Some(Piece::new(color, piece_type)),
+            None => None,
+        }
+    }
+}
+
+impl Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl From<Piece> for u8 {
+    fn from(piece: Piece) -> u8 {
+        piece.bits()
+    }
+}
+
+impl TryFrom<u8> for Piece {
+    type Error = u8;
+
+    fn try_from(bits: u8) -> Result<Piece, u8> {
+        Piece::from_bits(bits).ok_or(bits)
+    }
+}
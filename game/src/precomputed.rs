@@ -1,4 +1,5 @@
-use crate::{get_bit_index, Board};
+// attack tables and masks, computed at compile time
+use crate::get_bit_index;
 
 //directions knight can move
 const KNIGHT_DIRECTIONS: [(i8, i8); 8] = [
@@ -32,293 +33,153 @@ pub const SOUTHWEST: u8 = 5;
 pub const WEST: u8 = 6;
 pub const NORTHWEST: u8 = 7;
 
-impl Board {
-    pub fn generate_knight_masks(&mut self) {
-        for rank in 0u8..8 {
-            for file in 0u8..8 {
-                let mut mask: u64 = 0;
-                for direction in KNIGHT_DIRECTIONS {
-                    let to = (rank as i8 + direction.0, file as i8 + direction.1);
-                    if to.0 >= 0 && to.0 < 8 && to.1 >= 0 && to.1 < 8 {
-                        mask |= 1 << get_bit_index!(to);
-                    }
-                }
-                self.knight_masks[rank as usize][file as usize] = mask;
-            }
-        }
-    }
+pub static KNIGHT_MASKS: [[u64; 8]; 8] = step_masks(&KNIGHT_DIRECTIONS);
+pub static KING_MASKS: [[u64; 8]; 8] = step_masks(&KING_DIRECTIONS);
+// ray_attacks[direction][rank][file], the squares in one direction up to the edge of the board
+pub static RAY_ATTACKS: [[[u64; 8]; 8]; 8] = ray_attacks();
+// the squares whose occupancy matters for the magic bitboards, the edges are left out
+pub static ROOK_BLOCKER_MASKS: [[u64; 8]; 8] = blocker_masks(&[
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+]);
+pub static BISHOP_BLOCKER_MASKS: [[u64; 8]; 8] = blocker_masks(&[
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+]);
+// obstructed[from][to] gives bitboard of the squares inbetween the two (bit indices), 0 if they
+// are not on the same line
+pub static OBSTRUCTED: [[u64; 64]; 64] = obstructed();
 
-    pub fn generate_king_masks(&mut self) {
-        for rank in 0u8..8 {
-            for file in 0u8..8 {
-                let mut mask: u64 = 0;
+const fn on_board(rank: i8, file: i8) -> bool {
+    rank >= 0 && rank < 8 && file >= 0 && file < 8
+}
 
-                for direction in KING_DIRECTIONS {
-                    let to = (rank as i8 + direction.0, file as i8 + direction.1);
-                    if to.0 >= 0 && to.0 < 8 && to.1 >= 0 && to.1 < 8 {
-                        mask |= 1 << get_bit_index!(to);
-                    }
+// squares reached with a single step in any of the directions
+const fn step_masks(directions: &[(i8, i8); 8]) -> [[u64; 8]; 8] {
+    let mut masks = [[0; 8]; 8];
+
+    let mut rank = 0;
+    while rank < 8 {
+        let mut file = 0;
+        while file < 8 {
+            let mut i = 0;
+            while i < directions.len() {
+                let to = (rank + directions[i].0, file + directions[i].1);
+                if on_board(to.0, to.1) {
+                    masks[rank as usize][file as usize] |= 1 << get_bit_index!(to);
                 }
-
-                self.king_masks[rank as usize][file as usize] = mask;
+                i += 1;
             }
+            file += 1;
         }
+        rank += 1;
     }
 
-    // magic bitselfs goofy ah
-    pub fn generate_rook_blocker_masks(&mut self) {
-        for rank in 0u8..8 {
-            for file in 0u8..8 {
-                let mut r = rank;
-                let mut f = file;
-
-                while r > 1 {
-                    r -= 1;
-
-                    self.rook_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
-
-                r = rank;
-
-                while r < 6 {
-                    r += 1;
-
-                    self.rook_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
-
-                r = rank;
-
-                while f > 1 {
-                    f -= 1;
-
-                    self.rook_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
-
-                f = file;
-
-                while f < 6 {
-                    f += 1;
+    masks
+}
 
-                    self.rook_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
-            }
-        }
+// rank and file step of the directions
+const fn direction_step(direction: u8) -> (i8, i8) {
+    match direction {
+        NORTH => (-1, 0),
+        NORTHEAST => (-1, 1),
+        EAST => (0, 1),
+        SOUTHEAST => (1, 1),
+        SOUTH => (1, 0),
+        SOUTHWEST => (1, -1),
+        WEST => (0, -1),
+        _ => (-1, -1),
     }
+}
 
-    pub fn generate_bishop_blocker_masks(&mut self) {
-        for rank in 0u8..8 {
-            for file in 0u8..8 {
-                let mut r = rank;
-                let mut f = file;
-
-                while r > 1 && f > 1 {
-                    r -= 1;
-                    f -= 1;
-
-                    self.bishop_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
-
-                r = rank;
-                f = file;
-
-                while r > 1 && f < 6 {
-                    r -= 1;
-                    f += 1;
-
-                    self.bishop_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
-
-                r = rank;
-                f = file;
-
-                while r < 6 && f > 1 {
-                    r += 1;
-                    f -= 1;
+// every square in the direction, stopping before the square where keep_going is false
+const fn ray(rank: i8, file: i8, step: (i8, i8), edges: bool) -> u64 {
+    let mut mask = 0;
+    let (mut r, mut f) = (rank + step.0, file + step.1);
 
-                    self.bishop_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
+    while on_board(r, f) {
+        // the last square before the edge is the last one that can block
+        if !edges && !on_board(r + step.0, f + step.1) {
+            break;
+        }
 
-                r = rank;
-                f = file;
+        mask |= 1 << get_bit_index!(r, f);
+        r += step.0;
+        f += step.1;
+    }
 
-                while r < 6 && f < 6 {
-                    r += 1;
-                    f += 1;
+    mask
+}
 
-                    self.bishop_blocker_masks[rank as usize][file as usize] |=
-                        1 << get_bit_index!(r, f);
-                }
+const fn ray_attacks() -> [[[u64; 8]; 8]; 8] {
+    let mut attacks = [[[0; 8]; 8]; 8];
+
+    let mut direction = 0;
+    while direction < 8 {
+        let mut rank = 0;
+        while rank < 8 {
+            let mut file = 0;
+            while file < 8 {
+                attacks[direction as usize][rank as usize][file as usize] =
+                    ray(rank, file, direction_step(direction), true);
+                file += 1;
             }
+            rank += 1;
         }
+        direction += 1;
     }
 
-    pub fn generate_ray_attacks(&mut self) {
-        for direction in [
-            NORTH, NORTHEAST, EAST, SOUTHEAST, SOUTH, SOUTHWEST, WEST, NORTHWEST,
-        ] {
-            for rank in 0u8..8 {
-                for file in 0u8..8 {
-                    let mut r = rank;
-                    let mut f = file;
-                    let mut bit_index = get_bit_index!(rank, file);
-                    match direction {
-                        NORTH => {
-                            while r > 0 {
-                                r -= 1;
-                                bit_index += 8;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        NORTHEAST => {
-                            while r > 0 && f < 7 {
-                                r -= 1;
-                                f += 1;
-                                bit_index += 7;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        EAST => {
-                            while f < 7 {
-                                f += 1;
-                                bit_index -= 1;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        SOUTHEAST => {
-                            while r < 7 && f < 7 {
-                                r += 1;
-                                f += 1;
-                                bit_index -= 9;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        SOUTH => {
-                            while r < 7 {
-                                r += 1;
-                                bit_index -= 8;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        SOUTHWEST => {
-                            while r < 7 && f > 0 {
-                                r += 1;
-                                f -= 1;
-                                bit_index -= 7;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        WEST => {
-                            while f > 0 {
-                                f -= 1;
-                                bit_index += 1;
-
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        NORTHWEST => {
-                            while r > 0 && f > 0 {
-                                r -= 1;
-                                f -= 1;
-                                bit_index += 9;
+    attacks
+}
 
-                                self.ray_attacks[direction as usize][rank as usize]
-                                    [file as usize] |= 1 << bit_index;
-                            }
-                        }
-                        _ => {
-                            panic!("WRONG DIRECTION :skull:")
-                        }
-                    }
-                }
+const fn blocker_masks(directions: &[(i8, i8); 4]) -> [[u64; 8]; 8] {
+    let mut masks = [[0; 8]; 8];
+
+    let mut rank = 0;
+    while rank < 8 {
+        let mut file = 0;
+        while file < 8 {
+            let mut i = 0;
+            while i < directions.len() {
+                masks[rank as usize][file as usize] |= ray(rank, file, directions[i], false);
+                i += 1;
             }
+            file += 1;
         }
+        rank += 1;
     }
 
-    pub fn generate_obstructed(&mut self) {
-        for from in 0..64 {
-            for to in 0..64 {
-                let from_square = (7 - (from / 8), 7 - (from % 8));
-                let to_square = (7 - (to / 8), 7 - (to % 8));
-
-                if from != to
-                    && (from_square.0 + from_square.1 == to_square.0 + to_square.1
-                        || from_square.0 - from_square.1 == to_square.0 - to_square.1)
-                {
-                    // same diagonal
-                    let mut rank = from_square.0;
-                    let mut file = from_square.1;
-
-                    let deltas = (
-                        (to_square.0 - from_square.0) / i32::abs(to_square.0 - from_square.0),
-                        (to_square.1 - from_square.1) / i32::abs(to_square.1 - from_square.1),
-                    );
-
-                    loop {
-                        rank += deltas.0;
-                        file += deltas.1;
-
-                        if rank == to_square.0 && file == to_square.1 {
-                            break;
-                        }
-
-                        self.obstructed[from as usize][to as usize] |=
-                            1 << get_bit_index!(rank, file);
-                    }
-                }
-
-                if from != to && (from_square.0 == to_square.0 || from_square.1 == to_square.1) {
-                    // same row or column
-                    let mut rank = from_square.0;
-                    let mut file = from_square.1;
-
-                    let deltas = (
-                        (to_square.0 - from_square.0)
-                            / i32::abs(if to_square.0 - from_square.0 == 0 {
-                                1
-                            } else {
-                                to_square.0 - from_square.0
-                            }),
-                        (to_square.1 - from_square.1)
-                            / i32::abs(if to_square.1 - from_square.1 == 0 {
-                                1
-                            } else {
-                                to_square.1 - from_square.1
-                            }),
-                    );
-
-                    loop {
-                        rank += deltas.0;
-                        file += deltas.1;
-
-                        if rank == to_square.0 && file == to_square.1 {
-                            break;
-                        }
+    masks
+}
 
-                        self.obstructed[from as usize][to as usize] |=
-                            1 << get_bit_index!(rank, file);
-                    }
-                }
+const fn obstructed() -> [[u64; 64]; 64] {
+    let mut obstructed = [[0; 64]; 64];
+
+    let mut from = 0;
+    while from < 64 {
+        let from_square = (7 - (from / 8) as i8, 7 - (from % 8) as i8);
+
+        let mut direction = 0;
+        while direction < 8 {
+            // walk along the ray, every square passed is inbetween from and the next square
+            let step = direction_step(direction);
+            let mut between = 0u64;
+            let (mut r, mut f) = (from_square.0 + step.0, from_square.1 + step.1);
+
+            while on_board(r, f) {
+                obstructed[from][get_bit_index!(r, f) as usize] = between;
+                between |= 1 << get_bit_index!(r, f);
+                r += step.0;
+                f += step.1;
             }
+            direction += 1;
         }
+        from += 1;
     }
+
+    obstructed
 }
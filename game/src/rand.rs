@@ -12,7 +12,12 @@ impl Rng {
         Self(0xE926E6210D9E3486 | 1)
     }
 
-    pub fn next_u64(&mut self) -> u64 {
+    // independent of the global generator, so the numbers are reproducible across threads
+    pub const fn from_seed(seed: u64) -> Self {
+        Self(((seed as u128) << 64 | 0xE926E6210D9E3486) | 1)
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
         self.0 = self.0.wrapping_mul(0x2360ED051FC65DA44385DF649FCCF645);
         let rot = (self.0 >> 122) as u32;
         let xsl = (self.0 >> 64) as u64 ^ self.0 as u64;
@@ -23,5 +28,5 @@ impl Rng {
 static mut STATE: Rng = Rng::new();
 
 pub fn random() -> u64 {
-    unsafe { STATE.next_u64() }
+    unsafe { (*std::ptr::addr_of_mut!(STATE)).next_u64() }
 }
@@ -0,0 +1,100 @@
+// a game as the position it started from and the moves played, with the clocks and the result.
+// the moves are kept as uci strings (and san when the caller has it), since checking that they
+// are legal needs the move generator. with the serde feature this is the format the frontend,
+// stored games and test fixtures share
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Board, Color, Move, Variant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Outcome {
+    #[cfg_attr(feature = "serde", serde(rename = "1-0"))]
+    WhiteWins,
+    #[cfg_attr(feature = "serde", serde(rename = "0-1"))]
+    BlackWins,
+    #[cfg_attr(feature = "serde", serde(rename = "1/2-1/2"))]
+    Draw,
+}
+
+impl Outcome {
+    pub fn win(color: Color) -> Outcome {
+        match color {
+            Color::White => Outcome::WhiteWins,
+            Color::Black => Outcome::BlackWins,
+        }
+    }
+
+    pub fn winner(self) -> Option<Color> {
+        match self {
+            Outcome::WhiteWins => Some(Color::White),
+            Outcome::BlackWins => Some(Color::Black),
+            Outcome::Draw => None,
+        }
+    }
+
+    /// the result as it is written in a pgn
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::WhiteWins => "1-0",
+            Outcome::BlackWins => "0-1",
+            Outcome::Draw => "1/2-1/2",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecordedMove {
+    pub uci: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub san: Option<String>,
+    /// the time left on the clock of the side that moved, in milliseconds
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clock_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameRecord {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub variant: Variant,
+    /// the fen of the starting position
+    pub start: String,
+    pub moves: Vec<RecordedMove>,
+    /// none while the game is still going on
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub outcome: Option<Outcome>,
+    /// why the game ended ("White mates", "Draw by stalemate")
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub termination: Option<String>,
+}
+
+impl GameRecord {
+    /// an empty record of a game starting from the board
+    pub fn new(board: &Board) -> GameRecord {
+        GameRecord {
+            variant: board.variant,
+            start: board.to_fen(),
+            moves: Vec::new(),
+            outcome: None,
+            termination: None,
+        }
+    }
+
+    pub fn push(&mut self, m: Move, san: Option<String>, clock: Option<Duration>) {
+        self.moves.push(RecordedMove {
+            uci: m.to_uci(),
+            san,
+            clock_ms: clock.map(|clock| clock.as_millis() as u64),
+        });
+    }
+
+    pub fn finish(&mut self, outcome: Outcome, termination: &str) {
+        self.outcome = Some(outcome);
+        self.termination = Some(termination.to_owned());
+    }
+}
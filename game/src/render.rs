@@ -0,0 +1,324 @@
+// board diagrams, as text for the terminal (Display for Board, the uci "d" command) and as svg
+// for reports and the web ui. the svg pieces are the sprite sheet the frontend draws with too,
+// embedded in every diagram so that it can be shown without the frontend
+use std::fmt::Write;
+
+use crate::{
+    precomputed::KING_MASKS, Board, Color, File, Move, Piece, PieceType, Rank, Square, Variant,
+};
+
+/// the piece sprite sheet: six 45x45 pieces per row (king, queen, bishop, knight, rook, pawn),
+/// white on the first row
+pub const SPRITE: &str = include_str!("../assets/Chess_Pieces_Sprite.svg");
+const SQUARE_SIZE: usize = 45;
+// room for the coordinates left of and below the board
+const MARGIN: usize = 20;
+
+// the colors of the frontend
+const LIGHT: &str = "#bf9978";
+const DARK: &str = "#634f3e";
+const LAST_MOVE: &str = "#ffff00";
+const CHECK: &str = "#ff0000";
+const ARROW: &str = "#15781b";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Glyphs {
+    /// fen letters, uppercase for white
+    #[default]
+    Letters,
+    /// the chess symbols (♔ for the white king)
+    Unicode,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// the pieces of the text diagram, the svg always uses the sprite
+    pub glyphs: Glyphs,
+    /// the side at the bottom of the diagram
+    pub orientation: Color,
+    /// rank numbers and file letters around the board
+    pub coordinates: bool,
+    /// highlights the squares the move was made from and to
+    pub last_move: Option<Move>,
+    /// highlights the king of the side to move when it is in check
+    pub check: bool,
+    /// (from, to) arrows, listed below the text diagram. an arrow from a square to itself
+    /// circles the square
+    pub arrows: Vec<(Square, Square)>,
+    /// put in front of the ids in the svg, so that several diagrams can be inlined in one page
+    pub id_prefix: String,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            glyphs: Glyphs::Letters,
+            orientation: Color::White,
+            coordinates: true,
+            last_move: None,
+            check: true,
+            arrows: Vec::new(),
+            id_prefix: String::new(),
+        }
+    }
+}
+
+impl Glyphs {
+    pub fn glyph(self, piece: Piece) -> char {
+        match self {
+            Glyphs::Letters => piece.to_char(),
+            Glyphs::Unicode => match piece {
+                Piece::WhiteKing => '♔',
+                Piece::WhiteQueen => '♕',
+                Piece::WhiteRook => '♖',
+                Piece::WhiteBishop => '♗',
+                Piece::WhiteKnight => '♘',
+                Piece::WhitePawn => '♙',
+                Piece::BlackKing => '♚',
+                Piece::BlackQueen => '♛',
+                Piece::BlackRook => '♜',
+                Piece::BlackBishop => '♝',
+                Piece::BlackKnight => '♞',
+                Piece::BlackPawn => '♟',
+            },
+        }
+    }
+}
+
+// (row, column, square) from the top left of the diagram
+fn squares(orientation: Color) -> impl Iterator<Item = (usize, usize, Square)> {
+    (0..8).flat_map(move |row| {
+        (0..8).map(move |column| {
+            let square = match orientation {
+                Color::White => Square::new(File::ALL[column], Rank::ALL[7 - row]),
+                Color::Black => Square::new(File::ALL[7 - column], Rank::ALL[row]),
+            };
+            (row, column, square)
+        })
+    })
+}
+
+// top left corner of a square in the svg
+fn corner(square: Square, options: &RenderOptions) -> (usize, usize) {
+    let (row, column) = match options.orientation {
+        Color::White => (7 - square.rank().index(), square.file().index()),
+        Color::Black => (square.rank().index(), 7 - square.file().index()),
+    };
+    let margin = if options.coordinates { MARGIN } else { 0 };
+    (margin + column * SQUARE_SIZE, row * SQUARE_SIZE)
+}
+
+fn center(square: Square, options: &RenderOptions) -> (f64, f64) {
+    let (x, y) = corner(square, options);
+    let half = SQUARE_SIZE as f64 / 2.0;
+    (x as f64 + half, y as f64 + half)
+}
+
+impl Board {
+    // the king to highlight, none in antichess (where a king is just a piece) and when the side
+    // to move has no king
+    fn checked_king(&self) -> Option<Square> {
+        if self.variant == Variant::Antichess || self.bitboards(self.turn).king == 0 {
+            return None;
+        }
+
+        let king = self.king_square(self.turn);
+        // atomic kings next to each other cant be captured
+        let (r, f) = king.mailbox();
+        if self.variant == Variant::Atomic
+            && KING_MASKS[r][f] & self.bitboards(!self.turn).king != 0
+        {
+            return None;
+        }
+
+        self.king_attacked(self.turn).then_some(king)
+    }
+
+    fn highlights(&self, options: &RenderOptions) -> (Vec<Square>, Option<Square>) {
+        let last_move = options.last_move.map_or(Vec::new(), |m| vec![m.from, m.to]);
+        let check = if options.check {
+            self.checked_king()
+        } else {
+            None
+        };
+        (last_move, check)
+    }
+
+    /// the board as a grid of text, one line per rank. the squares of the last move are put in
+    /// brackets and a king in check in parentheses
+    pub fn render_text(&self, options: &RenderOptions) -> String {
+        let (last_move, check) = self.highlights(options);
+        let line = "+---+---+---+---+---+---+---+---+\n";
+
+        let mut text = String::from(line);
+        for (_, column, square) in squares(options.orientation) {
+            if column == 0 {
+                text.push('|');
+            }
+
+            let glyph = self
+                .piece_at(square)
+                .map_or(' ', |piece| options.glyphs.glyph(piece));
+            let (left, right) = if check == Some(square) {
+                ('(', ')')
+            } else if last_move.contains(&square) {
+                ('[', ']')
+            } else {
+                (' ', ' ')
+            };
+            let _ = write!(text, "{}{}{}|", left, glyph, right);
+
+            if column == 7 {
+                if options.coordinates {
+                    let _ = write!(text, " {}", square.rank());
+                }
+                text.push('\n');
+                text += line;
+            }
+        }
+
+        if options.coordinates {
+            for (_, _, square) in squares(options.orientation).take(8) {
+                let _ = write!(text, "  {} ", square.file());
+            }
+            text = text.trim_end().to_owned() + "\n";
+        }
+
+        if !options.arrows.is_empty() {
+            let arrows: Vec<String> = options
+                .arrows
+                .iter()
+                .map(|(from, to)| format!("{}{}", from, to))
+                .collect();
+            let _ = writeln!(text, "arrows: {}", arrows.join(" "));
+        }
+
+        text
+    }
+
+    /// the board as a standalone svg document with the pieces of the sprite sheet, 45 pixels per
+    /// square
+    pub fn render_svg(&self, options: &RenderOptions) -> String {
+        let (last_move, check) = self.highlights(options);
+        let margin = if options.coordinates { MARGIN } else { 0 };
+        let size = 8 * SQUARE_SIZE + margin;
+        let prefix = &options.id_prefix;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"1.1\" width=\"{size}\" \
+             height=\"{size}\" viewBox=\"0 0 {size} {size}\">"
+        );
+
+        // every piece is a 45x45 window into the whole sheet
+        let start = SPRITE.find("<svg").unwrap();
+        let start = start + SPRITE[start..].find('>').unwrap() + 1;
+        let end = SPRITE.rfind("</svg>").unwrap();
+        let _ = writeln!(
+            svg,
+            "<defs>\n<g id=\"{prefix}pieces\">{}</g>",
+            &SPRITE[start..end]
+        );
+        let _ = writeln!(
+            svg,
+            "<marker id=\"{prefix}arrowhead\" viewBox=\"0 0 10 10\" refX=\"5\" refY=\"5\" \
+             markerWidth=\"3\" markerHeight=\"3\" orient=\"auto\">\
+             <path d=\"M 0,0 L 10,5 L 0,10 z\" fill=\"{ARROW}\" fill-opacity=\"0.8\"/></marker>"
+        );
+        svg += "</defs>\n";
+
+        for (_, _, square) in squares(options.orientation) {
+            let (x, y) = corner(square, options);
+            let light = (square.rank().index() + square.file().index()) % 2 == 1;
+            let fill = if light { LIGHT } else { DARK };
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{SQUARE_SIZE}\" height=\"{SQUARE_SIZE}\" \
+                 fill=\"{fill}\"/>"
+            );
+        }
+
+        let highlights = last_move
+            .iter()
+            .map(|&square| (square, LAST_MOVE))
+            .chain(check.map(|square| (square, CHECK)));
+        for (square, fill) in highlights {
+            let (x, y) = corner(square, options);
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{x}\" y=\"{y}\" width=\"{SQUARE_SIZE}\" height=\"{SQUARE_SIZE}\" \
+                 fill=\"{fill}\" fill-opacity=\"0.5\"/>"
+            );
+        }
+
+        for (_, _, square) in squares(options.orientation) {
+            let Some(piece) = self.piece_at(square) else {
+                continue;
+            };
+            let (x, y) = corner(square, options);
+            let column = match piece.piece_type() {
+                PieceType::King => 0,
+                PieceType::Queen => 1,
+                PieceType::Bishop => 2,
+                PieceType::Knight => 3,
+                PieceType::Rook => 4,
+                PieceType::Pawn => 5,
+            };
+            let sprite_x = column * SQUARE_SIZE;
+            let sprite_y = piece.color().index() * SQUARE_SIZE;
+            let _ = writeln!(
+                svg,
+                "<svg x=\"{x}\" y=\"{y}\" width=\"{SQUARE_SIZE}\" height=\"{SQUARE_SIZE}\" \
+                 viewBox=\"{sprite_x} {sprite_y} {SQUARE_SIZE} {SQUARE_SIZE}\">\
+                 <use xlink:href=\"#{prefix}pieces\"/></svg>"
+            );
+        }
+
+        for &(from, to) in &options.arrows {
+            let (x1, y1) = center(from, options);
+            if from == to {
+                let _ = writeln!(
+                    svg,
+                    "<circle cx=\"{x1}\" cy=\"{y1}\" r=\"20\" fill=\"none\" stroke=\"{ARROW}\" \
+                     stroke-width=\"4\" stroke-opacity=\"0.8\"/>"
+                );
+                continue;
+            }
+
+            // the line stops half an arrowhead early, so that its tip is on the center
+            let (x2, y2) = center(to, options);
+            let length = (x2 - x1).hypot(y2 - y1);
+            let shorten = 13.5 / length;
+            let (x2, y2) = (x2 - (x2 - x1) * shorten, y2 - (y2 - y1) * shorten);
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" stroke=\"{ARROW}\" \
+                 stroke-width=\"9\" stroke-opacity=\"0.8\" stroke-linecap=\"round\" \
+                 marker-end=\"url(#{prefix}arrowhead)\"/>"
+            );
+        }
+
+        if options.coordinates {
+            let text = "font-family=\"sans-serif\" font-size=\"14\" text-anchor=\"middle\" \
+                        dominant-baseline=\"central\"";
+            for (row, column, square) in squares(options.orientation) {
+                let half = SQUARE_SIZE / 2;
+                if column == 0 {
+                    let (x, y) = (MARGIN / 2, row * SQUARE_SIZE + half);
+                    let rank = square.rank();
+                    let _ = writeln!(svg, "<text x=\"{x}\" y=\"{y}\" {text}>{rank}</text>");
+                }
+                if row == 7 {
+                    let (x, y) = (MARGIN + column * SQUARE_SIZE + half, size - MARGIN / 2);
+                    let file = square.file();
+                    let _ = writeln!(svg, "<text x=\"{x}\" y=\"{y}\" {text}>{file}</text>");
+                }
+            }
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+}
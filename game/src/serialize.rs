@@ -0,0 +1,120 @@
+// serde support (the serde feature). everything is written the way chess tools already write it:
+// colors as "white" and "black", pieces as their fen letter, squares as "e4", variants by their
+// uci name and moves as uci strings. a board is its fen with a few fields taken apart for
+// clients that dont want to parse it, and only the variant and the fen are read back
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Board, BoardBuilder, Color, Move, Piece, Square, Variant};
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "white" => Ok(Color::White),
+            "black" => Ok(Color::Black),
+            s => Err(D::Error::custom(format!("invalid color \"{}\"", s))),
+        }
+    }
+}
+
+impl Serialize for Piece {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_char(self.to_char())
+    }
+}
+
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Piece, D::Error> {
+        let c = char::deserialize(deserializer)?;
+        Piece::from_char(c).ok_or_else(|| D::Error::custom(format!("invalid piece '{}'", c)))
+    }
+}
+
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Square, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.uci_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Variant, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+// a move only means something together with its board, which is needed to read it back, so
+// there is no Deserialize (Move::from_uci does that with the board)
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_uci())
+    }
+}
+
+#[derive(Serialize)]
+struct BoardFields {
+    variant: Variant,
+    fen: String,
+    turn: Color,
+    // rank 8 first, and the a-file first in every rank
+    squares: [[Option<Piece>; 8]; 8],
+    en_passant: Option<Square>,
+    halfmoves: u16,
+    fullmoves: u16,
+}
+
+#[derive(Deserialize)]
+struct BoardInput {
+    #[serde(default)]
+    variant: Variant,
+    fen: String,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardFields {
+            variant: self.variant,
+            fen: self.to_fen(),
+            turn: self.turn,
+            squares: self.board,
+            en_passant: self.en_passant_square,
+            halfmoves: self.halfmoves,
+            fullmoves: self.fullmoves,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let input = BoardInput::deserialize(deserializer)?;
+        let board = BoardBuilder::new()
+            .set_variant(input.variant)
+            .try_set_position(&input.fen)
+            .map_err(D::Error::custom)?
+            .build();
+        board.validate().map_err(D::Error::custom)?;
+
+        Ok(board)
+    }
+}
@@ -0,0 +1,428 @@
+// typed squares and bitboards
+//
+// squares are numbered like the bits of a bitboard, 0 is h1 and 63 is a8. the mailbox
+// (Board::board) is indexed from the 8th rank instead, Square::mailbox converts between the two
+use std::{
+    fmt::{self, Display},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, Shr},
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl File {
+    pub const ALL: [File; 8] = [
+        File::A,
+        File::B,
+        File::C,
+        File::D,
+        File::E,
+        File::F,
+        File::G,
+        File::H,
+    ];
+
+    /// 0 is the a file, none if the index is off the board
+    pub const fn new(index: u8) -> Option<File> {
+        if index < 8 {
+            Some(File::ALL[index as usize])
+        } else {
+            None
+        }
+    }
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// the file the given number of files towards the h file, none if it is off the board
+    pub const fn offset(self, files: i8) -> Option<File> {
+        let index = self as i8 + files;
+        if index < 0 {
+            None
+        } else {
+            File::new(index as u8)
+        }
+    }
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    /// 0 is the first rank, none if the index is off the board
+    pub const fn new(index: u8) -> Option<Rank> {
+        if index < 8 {
+            Some(Rank::ALL[index as usize])
+        } else {
+            None
+        }
+    }
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// the rank the given number of ranks towards the 8th rank, none if it is off the board
+    pub const fn offset(self, ranks: i8) -> Option<Rank> {
+        let index = self as i8 + ranks;
+        if index < 0 {
+            None
+        } else {
+            Rank::new(index as u8)
+        }
+    }
+
+    /// the same rank seen from black's side when white is false (the first rank is the eighth)
+    pub const fn relative(self, white: bool) -> Rank {
+        if white {
+            self
+        } else {
+            Rank::ALL[7 - self as usize]
+        }
+    }
+}
+
+impl Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", (b'a' + *self as u8) as char)
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", *self as u8 + 1)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+
+impl Square {
+    pub const fn new(file: File, rank: Rank) -> Square {
+        Square(rank as u8 * 8 + 7 - file as u8)
+    }
+
+    /// square of a bit index, none if the index is off the board
+    pub const fn from_index(index: u8) -> Option<Square> {
+        if index < 64 {
+            Some(Square(index))
+        } else {
+            None
+        }
+    }
+
+    /// bit index of the square, 0 is h1
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub const fn file(self) -> File {
+        File::ALL[7 - (self.0 % 8) as usize]
+    }
+
+    pub const fn rank(self) -> Rank {
+        Rank::ALL[(self.0 / 8) as usize]
+    }
+
+    /// (row, column) of the square in the mailbox and the other [8][8] tables, row 0 is the
+    /// 8th rank and column 0 is the a file
+    pub const fn mailbox(self) -> (usize, usize) {
+        (7 - (self.0 / 8) as usize, 7 - (self.0 % 8) as usize)
+    }
+
+    pub const fn bitboard(self) -> Bitboard {
+        Bitboard(1 << self.0)
+    }
+
+    /// the square the given number of files and ranks away, towards the h file and the 8th rank,
+    /// none if it is off the board
+    pub const fn offset(self, files: i8, ranks: i8) -> Option<Square> {
+        match (self.file().offset(files), self.rank().offset(ranks)) {
+            (Some(file), Some(rank)) => Some(Square::new(file, rank)),
+            _ => None,
+        }
+    }
+
+    #[deprecated(note = "squares are no longer (rank, file) tuples, use Square::new")]
+    pub const fn from_tuple(square: (u8, u8)) -> Square {
+        Square((7 - square.1) + 8 * (7 - square.0))
+    }
+
+    #[deprecated(note = "squares are no longer (rank, file) tuples, use Square::mailbox")]
+    pub const fn to_tuple(self) -> (u8, u8) {
+        (7 - self.0 / 8, 7 - self.0 % 8)
+    }
+}
+
+// named squares, Square::E4 etc
+macro_rules! squares {
+    ($($rank: ident: $a: ident $b: ident $c: ident $d: ident $e: ident $f: ident $g: ident
+        $h: ident;)*) => {
+        impl Square {
+            $(
+                pub const $a: Square = Square::new(File::A, Rank::$rank);
+                pub const $b: Square = Square::new(File::B, Rank::$rank);
+                pub const $c: Square = Square::new(File::C, Rank::$rank);
+                pub const $d: Square = Square::new(File::D, Rank::$rank);
+                pub const $e: Square = Square::new(File::E, Rank::$rank);
+                pub const $f: Square = Square::new(File::F, Rank::$rank);
+                pub const $g: Square = Square::new(File::G, Rank::$rank);
+                pub const $h: Square = Square::new(File::H, Rank::$rank);
+            )*
+        }
+    };
+}
+
+squares! {
+    First: A1 B1 C1 D1 E1 F1 G1 H1;
+    Second: A2 B2 C2 D2 E2 F2 G2 H2;
+    Third: A3 B3 C3 D3 E3 F3 G3 H3;
+    Fourth: A4 B4 C4 D4 E4 F4 G4 H4;
+    Fifth: A5 B5 C5 D5 E5 F5 G5 H5;
+    Sixth: A6 B6 C6 D6 E6 F6 G6 H6;
+    Seventh: A7 B7 C7 D7 E7 F7 G7 H7;
+    Eighth: A8 B8 C8 D8 E8 F8 G8 H8;
+}
+
+impl Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+impl fmt::Debug for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+// "e4"
+impl FromStr for Square {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Square, String> {
+        let mut chars = s.chars();
+        let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(format!("invalid square \"{}\"", s));
+        };
+
+        let file = ('a'..='h')
+            .contains(&file)
+            .then(|| File::new(file as u8 - b'a'));
+        let rank = ('1'..='8')
+            .contains(&rank)
+            .then(|| Rank::new(rank as u8 - b'1'));
+        match (file.flatten(), rank.flatten()) {
+            (Some(file), Some(rank)) => Ok(Square::new(file, rank)),
+            _ => Err(format!("invalid square \"{}\"", s)),
+        }
+    }
+}
+
+/// a set of squares, bit 0 is h1 and bit 63 is a8
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+const A_FILE: u64 = 0x8080_8080_8080_8080;
+const H_FILE: u64 = 0x0101_0101_0101_0101;
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const FULL: Bitboard = Bitboard(u64::MAX);
+
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & 1 << square.0 != 0
+    }
+
+    pub const fn with(self, square: Square) -> Bitboard {
+        Bitboard(self.0 | 1 << square.0)
+    }
+
+    pub const fn without(self, square: Square) -> Bitboard {
+        Bitboard(self.0 & !(1 << square.0))
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// the square with the lowest bit index
+    pub const fn lsb(self) -> Option<Square> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(Square(self.0.trailing_zeros() as u8))
+        }
+    }
+
+    /// removes the square with the lowest bit index and returns it
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let square = self.lsb();
+        self.0 &= self.0.wrapping_sub(1);
+        square
+    }
+
+    // the squares moved by one square in a direction, squares moved off the board are dropped
+    pub const fn north(self) -> Bitboard {
+        Bitboard(self.0 << 8)
+    }
+
+    pub const fn south(self) -> Bitboard {
+        Bitboard(self.0 >> 8)
+    }
+
+    pub const fn east(self) -> Bitboard {
+        Bitboard((self.0 & !H_FILE) >> 1)
+    }
+
+    pub const fn west(self) -> Bitboard {
+        Bitboard((self.0 & !A_FILE) << 1)
+    }
+}
+
+// the squares from lowest to highest bit index
+impl Iterator for Bitboard {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        self.pop_lsb()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count() as usize, Some(self.count() as usize))
+    }
+}
+
+impl ExactSizeIterator for Bitboard {}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Bitboard {
+        square.bitboard()
+    }
+}
+
+impl From<u64> for Bitboard {
+    fn from(bits: u64) -> Bitboard {
+        Bitboard(bits)
+    }
+}
+
+impl From<Bitboard> for u64 {
+    fn from(bitboard: Bitboard) -> u64 {
+        bitboard.0
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(iter: I) -> Bitboard {
+        iter.into_iter().fold(Bitboard::EMPTY, Bitboard::with)
+    }
+}
+
+macro_rules! bitboard_op {
+    ($trait: ident, $fn: ident, $assign_trait: ident, $assign_fn: ident, $op: tt) => {
+        impl $trait for Bitboard {
+            type Output = Bitboard;
+
+            fn $fn(self, rhs: Bitboard) -> Bitboard {
+                Bitboard(self.0 $op rhs.0)
+            }
+        }
+
+        impl $assign_trait for Bitboard {
+            fn $assign_fn(&mut self, rhs: Bitboard) {
+                self.0 = self.0 $op rhs.0;
+            }
+        }
+    };
+}
+
+bitboard_op!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bitboard_op!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bitboard_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl Shl<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shl(self, rhs: u32) -> Bitboard {
+        Bitboard(self.0 << rhs)
+    }
+}
+
+impl Shr<u32> for Bitboard {
+    type Output = Bitboard;
+
+    fn shr(self, rhs: u32) -> Bitboard {
+        Bitboard(self.0 >> rhs)
+    }
+}
+
+// the board from white's side, x for the squares in the set
+impl Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rank in Rank::ALL.into_iter().rev() {
+            for file in File::ALL {
+                let c = if self.contains(Square::new(file, rank)) {
+                    'x'
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            if rank != Rank::First {
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Bitboard({:#018x})", self.0)
+    }
+}
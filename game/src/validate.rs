@@ -0,0 +1,175 @@
+// consistency checks for the redundant parts of the board: the mailbox, the bitboards, the king
+// positions and the incremental hash are all updated separately by make_move
+use std::{error::Error, fmt};
+
+use crate::{
+    genkeys::HASH_KEYS, Bitboard, Board, CastleState, Color, Piece, PieceType, Rank, Square,
+    Variant, BLACK_KINGSIDE, BLACK_QUEENSIDE, WHITE_KINGSIDE, WHITE_QUEENSIDE,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+    /// the mailbox and the piece bitboards disagree about the square
+    PieceMismatch(Square),
+    /// the `all` bitboard is not the union of the piece bitboards
+    OccupancyMismatch(Color),
+    /// the side has a number of kings the variant does not allow
+    KingCount(Color, u32),
+    /// the stored king position is not the square of the king
+    KingPosition(Color),
+    /// no pawn can have just been pushed past the en passant square
+    EnPassant(Square),
+    /// a castling right without the king and the rook on their starting squares
+    CastlingRights { color: Color, kingside: bool },
+    /// the incremental hash differs from the hash computed from scratch
+    Hash { expected: u64, found: u64 },
+    /// a square marked as a promoted pawn has no piece a pawn can promote to
+    Promoted(Square),
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardError::PieceMismatch(square) => {
+                write!(f, "mailbox and bitboards disagree on {}", square)
+            }
+            BoardError::OccupancyMismatch(color) => {
+                write!(f, "{} occupancy does not match the {} pieces", color, color)
+            }
+            BoardError::KingCount(color, count) => write!(f, "{} has {} kings", color, count),
+            BoardError::KingPosition(color) => write!(f, "wrong {} king position", color),
+            BoardError::EnPassant(square) => write!(f, "impossible en passant square {}", square),
+            BoardError::CastlingRights { color, kingside } => write!(
+                f,
+                "{} can castle {} without the king and rook in place",
+                color,
+                if *kingside { "kingside" } else { "queenside" }
+            ),
+            BoardError::Hash { expected, found } => {
+                write!(f, "hash is {:016x}, should be {:016x}", found, expected)
+            }
+            BoardError::Promoted(square) => write!(f, "no promoted piece on {}", square),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+// (right, color, kingside, king square, rook square)
+const CASTLING: [(CastleState, Color, bool, Square, Square); 4] = [
+    (WHITE_KINGSIDE, Color::White, true, Square::E1, Square::H1),
+    (WHITE_QUEENSIDE, Color::White, false, Square::E1, Square::A1),
+    (BLACK_KINGSIDE, Color::Black, true, Square::E8, Square::H8),
+    (BLACK_QUEENSIDE, Color::Black, false, Square::E8, Square::A8),
+];
+
+impl Board {
+    /// checks that the mailbox, bitboards, king positions, en passant square, castling rights
+    /// and hash all describe the same position
+    pub fn validate(&self) -> Result<(), BoardError> {
+        for color in Color::ALL {
+            let bitboards = self.bitboards(color);
+            let all = PieceType::ALL
+                .iter()
+                .fold(0, |all, &piece_type| all | bitboards.get(piece_type));
+            if all != bitboards.all {
+                return Err(BoardError::OccupancyMismatch(color));
+            }
+        }
+
+        // the mailbox the bitboards describe, a square in two bitboards is a mismatch as well
+        let mut mailbox = [[None; 8]; 8];
+        for color in Color::ALL {
+            for piece_type in PieceType::ALL {
+                for square in Bitboard(self.bitboards(color).get(piece_type)) {
+                    let (r, f) = square.mailbox();
+                    if mailbox[r][f].is_some() {
+                        return Err(BoardError::PieceMismatch(square));
+                    }
+                    mailbox[r][f] = Some(Piece::new(color, piece_type));
+                }
+            }
+        }
+        for square in Bitboard::FULL {
+            let (r, f) = square.mailbox();
+            if mailbox[r][f] != self.board[r][f] {
+                return Err(BoardError::PieceMismatch(square));
+            }
+        }
+
+        for color in Color::ALL {
+            // in antichess the king is an ordinary piece, there can be any number of them. kings
+            // explode in atomic chess and the white horde has none
+            let allowed = match (self.variant, color) {
+                (Variant::Antichess, _) => continue,
+                (Variant::Atomic, _) | (Variant::Horde, Color::White) => 0..=1,
+                _ => 1..=1,
+            };
+
+            let kings = Bitboard(self.bitboards(color).king);
+            if !allowed.contains(&kings.count()) {
+                return Err(BoardError::KingCount(color, kings.count()));
+            }
+            if kings.count() == 1 && !kings.contains(self.king_square(color)) {
+                return Err(BoardError::KingPosition(color));
+            }
+        }
+
+        if let Some(square) = self.en_passant_square {
+            // the side that is not to move just pushed a pawn past the square
+            let pusher = !self.turn;
+            let forward = if pusher.is_white() { 1 } else { -1 };
+            let pawn = square
+                .offset(0, forward)
+                .and_then(|pawn| self.piece_at(pawn));
+            let origin = square
+                .offset(0, -forward)
+                .and_then(|origin| self.piece_at(origin));
+
+            if square.rank() != Rank::Third.relative(pusher.is_white())
+                || self.piece_at(square).is_some()
+                || pawn != Some(Piece::new(pusher, PieceType::Pawn))
+                || origin.is_some()
+            {
+                return Err(BoardError::EnPassant(square));
+            }
+        }
+
+        for (right, color, kingside, king, rook) in CASTLING {
+            if self.castle_state & right != 0
+                && (self.piece_at(king) != Some(Piece::new(color, PieceType::King))
+                    || self.piece_at(rook) != Some(Piece::new(color, PieceType::Rook)))
+            {
+                return Err(BoardError::CastlingRights { color, kingside });
+            }
+        }
+
+        let promotions = PieceType::PROMOTIONS
+            .iter()
+            .fold(0, |promotions, &piece_type| {
+                promotions | self.white.get(piece_type) | self.black.get(piece_type)
+            });
+        if let Some(square) = Bitboard(self.promoted & !promotions).lsb() {
+            return Err(BoardError::Promoted(square));
+        }
+
+        let expected = HASH_KEYS.hash(self);
+        if expected != self.hash {
+            return Err(BoardError::Hash {
+                expected,
+                found: self.hash,
+            });
+        }
+
+        Ok(())
+    }
+
+    // called after every make and unmake with the validate feature (the movegen tests turn it
+    // on), so tests fail on the first move that leaves the board inconsistent
+    #[cfg(feature = "validate")]
+    pub(crate) fn debug_validate(&self) {
+        if let Err(e) = self.validate() {
+            panic!("invalid board after a move: {}\n{}", e, self.to_fen());
+        }
+    }
+}
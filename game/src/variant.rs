@@ -0,0 +1,153 @@
+// chess variants, they use the same board but change how the game is won and which moves are
+// allowed. the move rules are in movegen, the win conditions are here
+// https://lichess.org/variant
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::{
+    precomputed::{
+        EAST, KING_MASKS, KNIGHT_MASKS, NORTH, NORTHEAST, NORTHWEST, OBSTRUCTED, RAY_ATTACKS,
+        SOUTH, SOUTHEAST, SOUTHWEST, WEST,
+    },
+    Bitboard, Board, Color, Square, STARTPOS,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    // a king on one of the four centre squares wins
+    KingOfTheHill,
+    // checking the opponent's king three times wins
+    ThreeCheck,
+    // captures are compulsory, the king is an ordinary piece and losing every piece (or having
+    // no moves) wins
+    Antichess,
+    // captured pieces can be dropped back onto the board by the side that captured them
+    Crazyhouse,
+    // captures explode the pieces around the capture square, exploding the enemy king wins
+    Atomic,
+    // white has a horde of pawns and no king, and wins by capturing every white piece
+    Horde,
+}
+
+// d4, e4, d5 and e5
+pub const CENTER: u64 = 0x0000_0018_1800_0000;
+
+// a side that gave this many checks has won a three-check game
+pub const CHECKS_TO_WIN: u8 = 3;
+
+impl Variant {
+    pub const ALL: [Variant; 7] = [
+        Variant::Standard,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::Antichess,
+        Variant::Crazyhouse,
+        Variant::Atomic,
+        Variant::Horde,
+    ];
+
+    /// the name used by the `UCI_Variant` option
+    pub const fn uci_name(self) -> &'static str {
+        match self {
+            Variant::Standard => "chess",
+            Variant::KingOfTheHill => "kingofthehill",
+            Variant::ThreeCheck => "3check",
+            Variant::Antichess => "antichess",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::Atomic => "atomic",
+            Variant::Horde => "horde",
+        }
+    }
+
+    /// the position a game of the variant starts from, what `position startpos` sets up
+    pub const fn start_fen(self) -> &'static str {
+        match self {
+            Variant::Standard | Variant::KingOfTheHill | Variant::Atomic => STARTPOS,
+            Variant::ThreeCheck => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+            // nobody castles in antichess
+            Variant::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Variant::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            Variant::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.uci_name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Variant, String> {
+        match s.to_lowercase().as_str() {
+            "chess" | "standard" => Ok(Variant::Standard),
+            "kingofthehill" | "koth" => Ok(Variant::KingOfTheHill),
+            "3check" | "threecheck" => Ok(Variant::ThreeCheck),
+            "antichess" | "giveaway" => Ok(Variant::Antichess),
+            "crazyhouse" | "zh" => Ok(Variant::Crazyhouse),
+            "atomic" => Ok(Variant::Atomic),
+            "horde" => Ok(Variant::Horde),
+            _ => Err(format!("unknown variant {}", s)),
+        }
+    }
+}
+
+// the lines through a square on an empty board
+fn lines(directions: [u8; 4], square: Square) -> u64 {
+    let (r, f) = square.mailbox();
+    directions.iter().fold(0, |lines, &direction| {
+        lines | RAY_ATTACKS[direction as usize][r][f]
+    })
+}
+
+impl Board {
+    /// whether a rule of the variant already ended the game, the side to move has lost. an
+    /// antichess game ends when the side to move has no moves, which the move generator finds
+    pub fn is_variant_loss(&self) -> bool {
+        let opponent = !self.turn;
+
+        match self.variant {
+            Variant::KingOfTheHill => self.bitboards(opponent).king & CENTER != 0,
+            Variant::ThreeCheck => self.checks[opponent.index()] >= CHECKS_TO_WIN,
+            Variant::Atomic => self.bitboards(self.turn).king == 0,
+            // only white can run out of pieces, black still has its king
+            Variant::Horde => self.bitboards(self.turn).all == 0,
+            Variant::Standard | Variant::Antichess | Variant::Crazyhouse => false,
+        }
+    }
+
+    // whether the king of the color is attacked. the sliders are found with the lines on an
+    // empty board and the squares in between, since the magic bitboards are in movegen
+    pub(crate) fn king_attacked(&self, color: Color) -> bool {
+        let king = self.king_square(color);
+        let (r, f) = king.mailbox();
+        let enemy = self.bitboards(!color);
+        let occupancy = self.white.all | self.black.all;
+
+        let pawns = if color.is_white() {
+            king.bitboard().north()
+        } else {
+            king.bitboard().south()
+        };
+        if (pawns.east() | pawns.west()).0 & enemy.pawns != 0
+            || KNIGHT_MASKS[r][f] & enemy.knights != 0
+            || KING_MASKS[r][f] & enemy.king != 0
+        {
+            return true;
+        }
+
+        let sliders = (lines([NORTH, EAST, SOUTH, WEST], king) & (enemy.rooks | enemy.queens))
+            | (lines([NORTHEAST, SOUTHEAST, SOUTHWEST, NORTHWEST], king)
+                & (enemy.bishops | enemy.queens));
+        Bitboard(sliders).any(|slider| OBSTRUCTED[king.index()][slider.index()] & occupancy == 0)
+    }
+}
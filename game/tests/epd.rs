@@ -0,0 +1,70 @@
+use game::{epd::Epd, STARTPOS};
+
+fn epd(line: &str) -> Epd {
+    line.parse().unwrap()
+}
+
+#[test]
+fn operations() {
+    let epd = epd(
+        "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - bm Nxc6 Qe2; \
+         am Nf3; id \"WAC.004\";",
+    );
+    assert_eq!(
+        epd.fen,
+        "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 0 1"
+    );
+    assert_eq!(epd.best_moves(), ["Nxc6", "Qe2"]);
+    assert_eq!(epd.avoid_moves(), ["Nf3"]);
+    assert_eq!(epd.id(), Some("WAC.004"));
+    assert_eq!(epd.operation("c0"), None);
+    assert_eq!(epd.board().to_fen(), epd.fen);
+}
+
+#[test]
+fn quoted_operands_keep_spaces_and_semicolons() {
+    let epd =
+        epd("8/8/8/8/8/8/8/K6k w - - id \"tricky; with spaces\"; c0 \"a; b\" \"c d\";  bm Kb2 ;");
+    assert_eq!(epd.id(), Some("tricky; with spaces"));
+    assert_eq!(epd.operation("c0").unwrap(), ["a; b", "c d"]);
+    assert_eq!(epd.comment(), Some("a; b"));
+    assert_eq!(epd.best_moves(), ["Kb2"]);
+}
+
+#[test]
+fn move_counters() {
+    // from the hmvc and fmvn operations
+    let epd = epd("8/8/8/8/8/8/8/K6k b - - hmvc 12; fmvn 40;");
+    assert_eq!(epd.fen, "8/8/8/8/8/8/8/K6k b - - 12 40");
+
+    // from the fen, unless there are operations for them
+    let epd = self::epd("8/8/8/8/8/8/8/K6k b - - 3 20 ;fmvn 41");
+    assert_eq!(epd.fen, "8/8/8/8/8/8/8/K6k b - - 3 41");
+}
+
+#[test]
+fn perft_depths() {
+    let epd = epd(&format!("{} ;D1 20 ;D2 400 ;D3 8902", STARTPOS));
+    assert_eq!(epd.fen, STARTPOS);
+    assert_eq!(epd.perft(), [(1, 20), (2, 400), (3, 8902)]);
+
+    // operations that arent depths are left out
+    let epd = self::epd("8/8/8/8/8/8/8/K6k w - - D1 3; Dx 5; D2 x; id \"x\";");
+    assert_eq!(epd.perft(), [(1, 3)]);
+}
+
+#[test]
+fn malformed_epds_are_errors() {
+    for line in [
+        "",
+        "8/8/8/8/8/8/8/K6k w -",
+        "8/8/8/8/8/8/K6k w - - bm Kb2;",
+        "8/8/8/8/8/8/8/K6x w - - bm Kb2;",
+        "8/8/8/8/8/8/8/K6k x - - bm Kb2;",
+        "8/8/8/8/8/8/8/K6k w - z9 bm Kb2;",
+        "8/8/8/8/8/8/8/K6k w - - hmvc x;",
+        "8/8/8/8/8/8/8/K6k w - - id \"unterminated;",
+    ] {
+        assert!(line.parse::<Epd>().is_err(), "{}", line);
+    }
+}
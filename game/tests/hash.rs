@@ -0,0 +1,72 @@
+use game::{genkeys::HASH_KEYS, Board, Move, KIWIPETE, STARTPOS};
+
+// plays the moves, the incrementally updated hash has to be the hash of the board computed from
+// scratch and the hash of the same position set up from its fen
+fn play(fen: &str, moves: &[&str]) -> Board {
+    let mut board = Board::from_fen(fen).unwrap();
+    let mut history = Vec::new();
+
+    for uci in moves {
+        let m = Move::from_uci(uci, board);
+        let undo = board.make_move(m);
+        assert_eq!(board.hash, HASH_KEYS.hash(&board), "after {}", uci);
+        assert_eq!(
+            board.hash,
+            Board::from_fen(&board.to_fen()).unwrap().hash,
+            "after {}",
+            uci
+        );
+        history.push((m, undo));
+    }
+
+    let end = board;
+    while let Some((m, undo)) = history.pop() {
+        board.unmake_move(m, undo);
+        assert_eq!(board.hash, HASH_KEYS.hash(&board));
+    }
+    assert_eq!(board.hash, Board::from_fen(fen).unwrap().hash);
+
+    end
+}
+
+#[test]
+fn pawn_moves_and_en_passant() {
+    // double pushes of both colors, en passant both ways and a black pawn capture
+    play(
+        STARTPOS,
+        &[
+            "e2e4", "h7h5", "e4e5", "d7d5", "e5d6", "h5h4", "g2g4", "h4g3", "d6c7", "b7b5",
+        ],
+    );
+}
+
+#[test]
+fn castling_captures_and_promotions() {
+    play(
+        KIWIPETE,
+        &[
+            "e1g1", "h3g2", "d5e6", "e8c8", "e6f7", "g2f1q", "f7f8n", "d8f8",
+        ],
+    );
+    play(KIWIPETE, &["e1c1", "e8g8", "e5f7", "f8f7", "d5e6", "b4c3"]);
+    // a rook captured on its starting square takes the castling right with it
+    play(
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        &["a1a8", "e8e7", "h1h8"],
+    );
+}
+
+#[test]
+fn transpositions_have_the_same_hash() {
+    let a = play(STARTPOS, &["g1f3", "g8f6", "b1c3", "b8c6"]);
+    let b = play(STARTPOS, &["b1c3", "b8c6", "g1f3", "g8f6"]);
+    assert_eq!(a.hash, b.hash);
+
+    // the same pieces, but en passant is only possible after the double push
+    let a = play(STARTPOS, &["e2e4", "d7d5", "e4e5", "f7f5"]);
+    let b = play(
+        STARTPOS,
+        &["e2e4", "f7f6", "e4e5", "f6f5", "g1f3", "d7d5", "f3g1"],
+    );
+    assert_ne!(a.hash, b.hash);
+}
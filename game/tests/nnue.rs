@@ -0,0 +1,94 @@
+#![cfg(feature = "nnue")]
+
+use game::{
+    nnue::{AccumulatorStack, Network, HIDDEN, INPUTS},
+    rand::Rng,
+    Board, Move, Variant, KIWIPETE, STARTPOS,
+};
+
+// random weights in the layout of the network file
+fn network() -> &'static Network {
+    let values = 2 * (INPUTS * HIDDEN + HIDDEN + 2 * HIDDEN + 1);
+    let mut rng = Rng::from_seed(31);
+    let bytes: Vec<u8> = (0..values)
+        .flat_map(|_| ((rng.next_u64() % 256) as i16 - 128).to_le_bytes())
+        .collect();
+    Network::from_bytes(&bytes).unwrap()
+}
+
+// plays the moves and takes them back, the incrementally updated accumulator has to be the one
+// computed from scratch after every move
+fn play_and_take_back(variant: Variant, fen: &str, moves: &[&str]) {
+    let network = network();
+    let mut board = Board::from_variant_fen(variant, fen).unwrap();
+    let mut stack = AccumulatorStack::new(network, &board);
+    let mut history = Vec::new();
+
+    for uci in moves {
+        let m = Move::from_uci(uci, board);
+        let undo = board.make_move(m);
+        stack.push(&board, &m, &undo);
+        assert_eq!(
+            *stack.current(),
+            network.accumulator(&board),
+            "after {}",
+            uci
+        );
+        assert_eq!(
+            stack.evaluate(board.turn),
+            network.evaluate(&network.accumulator(&board), board.turn)
+        );
+        history.push((m, undo));
+    }
+
+    while let Some((m, undo)) = history.pop() {
+        board.unmake_move(m, undo);
+        stack.pop();
+        assert_eq!(
+            *stack.current(),
+            network.accumulator(&board),
+            "after taking back {}",
+            m.to_uci()
+        );
+    }
+}
+
+#[test]
+fn captures_castling_and_promotions() {
+    play_and_take_back(
+        Variant::Standard,
+        KIWIPETE,
+        &["e1g1", "h3g2", "d5e6", "e8c8", "e6f7", "g2f1q", "f7f8n"],
+    );
+    play_and_take_back(
+        Variant::Standard,
+        KIWIPETE,
+        &["e1c1", "e8g8", "e5f7", "f8f7"],
+    );
+}
+
+#[test]
+fn en_passant() {
+    play_and_take_back(
+        Variant::Standard,
+        STARTPOS,
+        &["e2e4", "a7a6", "e4e5", "d7d5", "e5d6", "c7d6"],
+    );
+}
+
+#[test]
+fn drops_and_explosions() {
+    play_and_take_back(
+        Variant::Crazyhouse,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+        &[
+            "e2e4", "d7d5", "e4d5", "d8d5", "b1c3", "d5a5", "P@e6", "P@e3",
+        ],
+    );
+    // the knight blows up the queen, bishop and knight next to c7
+    play_and_take_back(
+        Variant::Atomic,
+        STARTPOS,
+        &["b1c3", "e7e6", "c3b5", "a7a6", "b5c7"],
+    );
+}
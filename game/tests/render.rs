@@ -0,0 +1,119 @@
+use game::{
+    render::{Glyphs, RenderOptions},
+    Board, Color, Move, Square, Variant, STARTPOS,
+};
+
+#[test]
+fn text_diagram() {
+    let board = Board::from_variant_fen(Variant::Standard, STARTPOS).unwrap();
+    let text = board.render_text(&RenderOptions::default());
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 18);
+    assert_eq!(lines[0], "+---+---+---+---+---+---+---+---+");
+    assert_eq!(lines[1], "| r | n | b | q | k | b | n | r | 8");
+    assert_eq!(lines[13], "| P | P | P | P | P | P | P | P | 2");
+    assert_eq!(lines[17], "  a   b   c   d   e   f   g   h");
+
+    let text = board.render_text(&RenderOptions {
+        glyphs: Glyphs::Unicode,
+        orientation: Color::Black,
+        coordinates: false,
+        ..RenderOptions::default()
+    });
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 17);
+    assert_eq!(lines[1], "| ♖ | ♘ | ♗ | ♔ | ♕ | ♗ | ♘ | ♖ |");
+    assert_eq!(lines[15], "| ♜ | ♞ | ♝ | ♚ | ♛ | ♝ | ♞ | ♜ |");
+}
+
+#[test]
+fn text_highlights() {
+    let mut board = Board::from_variant_fen(Variant::Standard, STARTPOS).unwrap();
+    let mut last_move = None;
+    for uci in ["e2e4", "e7e5", "d1h5", "b8c6", "h5f7"] {
+        let m = Move::from_uci(uci, board);
+        let _ = board.make_move(m);
+        last_move = Some(m);
+    }
+
+    let text = board.render_text(&RenderOptions {
+        last_move,
+        arrows: vec![(Square::E8, Square::F7), (Square::G8, Square::G8)],
+        ..RenderOptions::default()
+    });
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[1], "| r |   | b | q |(k)| b | n | r | 8");
+    assert_eq!(lines[3], "| p | p | p | p |   |[Q]| p | p | 7");
+    assert_eq!(lines[7], "|   |   |   |   | p |   |   |[ ]| 5");
+    assert_eq!(lines[18], "arrows: e8f7 g8g8");
+
+    // without the check
+    let text = board.render_text(&RenderOptions {
+        check: false,
+        ..RenderOptions::default()
+    });
+    assert!(text.contains("| k |"));
+
+    // a king is just a piece in antichess
+    let board =
+        Board::from_variant_fen(Variant::Antichess, "4k3/8/8/8/8/8/8/4R2K b - - 0 1").unwrap();
+    assert!(!board.render_text(&RenderOptions::default()).contains('('));
+}
+
+#[test]
+fn svg_diagram() {
+    let mut board = Board::from_variant_fen(Variant::Standard, STARTPOS).unwrap();
+    let m = Move::from_uci("e2e4", board);
+    let _ = board.make_move(m);
+
+    let svg = board.render_svg(&RenderOptions {
+        last_move: Some(m),
+        arrows: vec![(Square::E7, Square::E5)],
+        ..RenderOptions::default()
+    });
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert!(svg.contains("width=\"380\" height=\"380\""));
+    // the sprite sheet once, then a window into it for every piece
+    assert_eq!(svg.matches("<!-- white king //-->").count(), 1);
+    assert_eq!(svg.matches("<use xlink:href=\"#pieces\"/>").count(), 32);
+    assert!(svg.contains("marker-end=\"url(#arrowhead)\""));
+    // the white king on e1 and the black queen on d8
+    assert!(
+        svg.contains("<svg x=\"200\" y=\"315\" width=\"45\" height=\"45\" viewBox=\"0 0 45 45\">")
+    );
+    assert!(
+        svg.contains("<svg x=\"155\" y=\"0\" width=\"45\" height=\"45\" viewBox=\"45 45 45 45\">")
+    );
+    assert_eq!(svg.matches("fill=\"#ffff00\"").count(), 2);
+    assert_eq!(svg.matches("<line ").count(), 1);
+    assert_eq!(svg.matches("<text ").count(), 16);
+
+    // from black's side the king is on the other side of the board
+    let svg = board.render_svg(&RenderOptions {
+        orientation: Color::Black,
+        coordinates: false,
+        ..RenderOptions::default()
+    });
+    assert!(svg.contains("width=\"360\" height=\"360\""));
+    assert!(
+        svg.contains("<svg x=\"135\" y=\"0\" width=\"45\" height=\"45\" viewBox=\"0 0 45 45\">")
+    );
+    assert_eq!(svg.matches("<text ").count(), 0);
+}
+
+#[test]
+fn svg_id_prefix() {
+    let board = Board::from_variant_fen(Variant::Standard, STARTPOS).unwrap();
+    let svg = board.render_svg(&RenderOptions {
+        arrows: vec![(Square::E2, Square::E4)],
+        id_prefix: "game-1-".to_owned(),
+        ..RenderOptions::default()
+    });
+    // every id and every reference to one has the prefix
+    assert_eq!(svg.matches(" id=\"").count(), 2);
+    assert_eq!(svg.matches(" id=\"game-1-").count(), 2);
+    assert_eq!(svg.matches("#game-1-pieces").count(), 32);
+    assert!(svg.contains("url(#game-1-arrowhead)"));
+    assert!(!svg.contains("\"#pieces\""));
+}
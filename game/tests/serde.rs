@@ -0,0 +1,133 @@
+#![cfg(feature = "serde")]
+
+use std::time::Duration;
+
+use game::{
+    record::{GameRecord, Outcome},
+    Board, Move, Piece, Variant, KIWIPETE, STARTPOS,
+};
+use serde_json::json;
+
+#[test]
+fn board_round_trip() {
+    for (variant, fen) in [
+        (Variant::Standard, STARTPOS),
+        (Variant::Standard, KIWIPETE),
+        (Variant::ThreeCheck, "4k3/8/8/8/8/8/8/3QK3 w - - 2+3 0 1"),
+        (Variant::Crazyhouse, "4k3/8/8/8/8/8/8/3q~K3[Pn] w - - 0 1"),
+        (Variant::Horde, "4k3/8/8/8/8/8/8/P7 w - - 0 1"),
+    ] {
+        let board = Board::from_variant_fen(variant, fen).unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        let read: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.to_fen(), fen);
+        assert_eq!(read.variant, variant);
+        assert_eq!(read.hash, board.hash);
+    }
+}
+
+#[test]
+fn board_fields() {
+    let mut board = Board::from_variant_fen(Variant::Standard, STARTPOS).unwrap();
+    let _ = board.make_move(Move::from_uci("e2e4", board));
+
+    let json = serde_json::to_value(board).unwrap();
+    assert_eq!(json["variant"], "chess");
+    assert_eq!(
+        json["fen"],
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+    assert_eq!(json["turn"], "black");
+    assert_eq!(json["en_passant"], "e3");
+    assert_eq!(
+        json["squares"][0],
+        json!(["r", "n", "b", "q", "k", "b", "n", "r"])
+    );
+    assert_eq!(json["squares"][4][4], "P");
+    assert_eq!(json["squares"][6][4], json!(null));
+
+    // only the fen is needed, the variant defaults to standard chess
+    let read: Board = serde_json::from_value(json!({ "fen": KIWIPETE })).unwrap();
+    assert_eq!(read.to_fen(), KIWIPETE);
+    assert_eq!(read.variant, Variant::Standard);
+}
+
+#[test]
+fn invalid_boards_are_errors() {
+    for fen in [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1",
+        // well formed, but white has no king
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w kq - 0 1",
+    ] {
+        let result = serde_json::from_value::<Board>(json!({ "fen": fen }));
+        assert!(result.is_err(), "{}", fen);
+    }
+
+    let result = serde_json::from_value::<Board>(json!({ "variant": "chess960", "fen": STARTPOS }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn moves_and_pieces() {
+    let board =
+        Board::from_variant_fen(Variant::Standard, "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    let m = Move::from_uci("b7b8q", board);
+    assert_eq!(serde_json::to_value(m).unwrap(), "b7b8q");
+
+    assert_eq!(serde_json::to_value(Piece::BlackKnight).unwrap(), "n");
+    assert_eq!(
+        serde_json::from_value::<Piece>(json!("Q")).unwrap(),
+        Piece::WhiteQueen
+    );
+    assert!(serde_json::from_value::<Piece>(json!("x")).is_err());
+}
+
+#[test]
+fn game_record() {
+    let mut board = Board::from_variant_fen(Variant::Standard, STARTPOS).unwrap();
+    let mut record = GameRecord::new(&board);
+    for (uci, san) in [
+        ("f2f3", "f3"),
+        ("e7e5", "e5"),
+        ("g2g4", "g4"),
+        ("d8h4", "Qh4#"),
+    ] {
+        let m = Move::from_uci(uci, board);
+        record.push(m, Some(san.to_owned()), Some(Duration::from_millis(60_000)));
+        let _ = board.make_move(m);
+    }
+    record.finish(Outcome::BlackWins, "Black mates");
+
+    let json = serde_json::to_value(&record).unwrap();
+    assert_eq!(
+        json,
+        json!({
+            "variant": "chess",
+            "start": STARTPOS,
+            "moves": [
+                { "uci": "f2f3", "san": "f3", "clock_ms": 60000 },
+                { "uci": "e7e5", "san": "e5", "clock_ms": 60000 },
+                { "uci": "g2g4", "san": "g4", "clock_ms": 60000 },
+                { "uci": "d8h4", "san": "Qh4#", "clock_ms": 60000 },
+            ],
+            "outcome": "0-1",
+            "termination": "Black mates",
+        })
+    );
+    assert_eq!(serde_json::from_value::<GameRecord>(json).unwrap(), record);
+
+    // a game in progress, without san or clocks
+    let read: GameRecord = serde_json::from_value(json!({
+        "start": STARTPOS,
+        "moves": [{ "uci": "e2e4" }],
+    }))
+    .unwrap();
+    assert_eq!(read.variant, Variant::Standard);
+    assert_eq!(read.moves[0].san, None);
+    assert_eq!(read.outcome, None);
+}
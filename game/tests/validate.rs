@@ -0,0 +1,235 @@
+use game::{
+    genkeys::HASH_KEYS, Board, BoardBuilder, BoardError, Color, Move, Square, KIWIPETE, STARTPOS,
+};
+
+// plays the moves, checking the board after every make and every unmake
+fn play_and_take_back(fen: &str, moves: &[&str]) {
+    let mut board = Board::from_fen(fen).unwrap();
+    let mut history = Vec::new();
+
+    for uci in moves {
+        let m = Move::from_uci(uci, board);
+        let undo = board.make_move(m);
+        assert_eq!(board.validate(), Ok(()), "after {}", uci);
+        history.push((m, undo));
+    }
+
+    while let Some((m, undo)) = history.pop() {
+        board.unmake_move(m, undo);
+        assert_eq!(board.validate(), Ok(()), "after taking back {}", m.to_uci());
+    }
+
+    assert_eq!(board.to_fen(), fen);
+}
+
+#[test]
+fn positions_from_fen_are_valid() {
+    assert_eq!(Board::from_fen(STARTPOS).unwrap().validate(), Ok(()));
+    assert_eq!(Board::from_fen(KIWIPETE).unwrap().validate(), Ok(()));
+    assert_eq!(
+        Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+            .unwrap()
+            .validate(),
+        Ok(())
+    );
+}
+
+#[test]
+fn moves_keep_the_board_valid() {
+    // castling both ways, captures and a promotion
+    play_and_take_back(
+        KIWIPETE,
+        &["e1g1", "h3g2", "d5e6", "e8c8", "e6f7", "g2f1q", "f7f8n"],
+    );
+    // double pushes and en passant
+    play_and_take_back(STARTPOS, &["e2e4", "a7a6", "e4e5", "d7d5", "e5d6", "c7d6"]);
+
+    let mut board = Board::from_fen(KIWIPETE).unwrap();
+    let undo = board.make_null_move();
+    assert_eq!(board.validate(), Ok(()));
+    board.unmake_null_move(undo);
+    assert_eq!(board.validate(), Ok(()));
+}
+
+#[test]
+fn null_moves_keep_the_hash_and_en_passant() {
+    let mut board = Board::from_fen(STARTPOS).unwrap();
+    let _ = board.make_move(Move::from_uci("e2e4", board));
+    let fen = board.to_fen();
+    let hash = board.hash;
+    assert_eq!(board.en_passant_square, Some(Square::E3));
+
+    // the en passant square is gone after the null move, and with it its key
+    let undo = board.make_null_move();
+    assert_eq!(board.en_passant_square, None);
+    assert_eq!(board.turn, Color::White);
+    assert_eq!(board.hash, HASH_KEYS.hash(&board));
+    assert_ne!(board.hash, hash);
+
+    // two null moves in a row
+    let second = board.make_null_move();
+    assert_eq!(board.hash, HASH_KEYS.hash(&board));
+    board.unmake_null_move(second);
+    assert_eq!(board.hash, HASH_KEYS.hash(&board));
+
+    board.unmake_null_move(undo);
+    assert_eq!(board.en_passant_square, Some(Square::E3));
+    assert_eq!(board.to_fen(), fen);
+    assert_eq!(board.hash, hash);
+}
+
+#[test]
+fn malformed_fens_are_errors() {
+    for fen in [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/7/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1",
+        "rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +x+0",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0",
+        // a tilde without a piece, a king in the pocket and an unclosed pocket
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1~BNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[K] w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[Q w KQkq - 0 1",
+    ] {
+        let mut builder = BoardBuilder::new();
+        let error = builder.try_set_position(fen).err();
+        assert!(error.is_some_and(|e| e.contains(fen)), "{}", fen);
+        // the builder is left as it was
+        assert_eq!(builder.build().to_fen(), "8/8/8/8/8/8/8/8 w - - 0 0");
+    }
+
+    let kiwipete = Board::from_fen(KIWIPETE).map(|board| board.to_fen());
+    assert_eq!(kiwipete, Ok(KIWIPETE.to_owned()));
+}
+
+#[test]
+fn mailbox_and_bitboard_mismatches() {
+    let mut mailbox = Board::from_fen(STARTPOS).unwrap();
+    mailbox.board[6][4] = None;
+    assert_eq!(
+        mailbox.validate(),
+        Err(BoardError::PieceMismatch(Square::E2))
+    );
+
+    let mut overlapping = Board::from_fen(STARTPOS).unwrap();
+    overlapping.white.knights |= Square::E2.bitboard().0;
+    assert_eq!(
+        overlapping.validate(),
+        Err(BoardError::PieceMismatch(Square::E2))
+    );
+
+    let mut occupancy = Board::from_fen(STARTPOS).unwrap();
+    occupancy.black.all |= Square::E4.bitboard().0;
+    assert_eq!(
+        occupancy.validate(),
+        Err(BoardError::OccupancyMismatch(Color::Black))
+    );
+}
+
+#[test]
+fn kings() {
+    assert_eq!(
+        Board::from_fen("4k3/8/8/8/8/8/8/K3K3 w - - 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::KingCount(Color::White, 2))
+    );
+    assert_eq!(
+        Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::KingCount(Color::Black, 0))
+    );
+
+    let mut board = Board::from_fen(STARTPOS).unwrap();
+    board.black_king_position = Square::D8;
+    assert_eq!(
+        board.validate(),
+        Err(BoardError::KingPosition(Color::Black))
+    );
+}
+
+#[test]
+fn en_passant_square_needs_a_double_pushed_pawn() {
+    // wrong rank for the side to move
+    assert_eq!(
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::EnPassant(Square::E3))
+    );
+    // no pawn in front of the square
+    assert_eq!(
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq e3 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::EnPassant(Square::E3))
+    );
+    // the pawn cannot have come from its starting square
+    assert_eq!(
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPPPPPP/RNBQKBN1 b Qkq e3 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::EnPassant(Square::E3))
+    );
+}
+
+#[test]
+fn castling_rights_need_king_and_rook() {
+    assert_eq!(
+        Board::from_fen("r3k2r/8/8/8/8/8/8/R3K3 w KQkq - 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::CastlingRights {
+            color: Color::White,
+            kingside: true
+        })
+    );
+    assert_eq!(
+        Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+            .unwrap()
+            .validate(),
+        Ok(())
+    );
+    assert_eq!(
+        Board::from_fen("r4k1r/8/8/8/8/8/8/R3K2R w KQq - 0 1")
+            .unwrap()
+            .validate(),
+        Err(BoardError::CastlingRights {
+            color: Color::Black,
+            kingside: false
+        })
+    );
+}
+
+#[test]
+fn hash_mismatch() {
+    let mut board = Board::from_fen(KIWIPETE).unwrap();
+    let hash = board.hash;
+    board.hash ^= 1;
+
+    assert_eq!(
+        board.validate(),
+        Err(BoardError::Hash {
+            expected: hash,
+            found: hash ^ 1
+        })
+    );
+}
+
+// make_move checks the board with the validate feature
+#[cfg(feature = "validate")]
+#[test]
+#[should_panic(expected = "invalid board")]
+fn make_move_catches_a_corrupted_board() {
+    let mut board = Board::from_fen(STARTPOS).unwrap();
+    board.castle_state = 0;
+    let _ = board.make_move(Move::from_uci("e2e4", board));
+}
@@ -2,10 +2,12 @@ use std::fmt::Display;
 use std::fs::{self};
 use std::io::Write;
 
-use game::BoardBuilder;
 use game::{
-    precomputed::{EAST, NORTH, NORTHEAST, NORTHWEST, SOUTH, SOUTHEAST, SOUTHWEST, WEST},
-    rand, Board,
+    precomputed::{
+        BISHOP_BLOCKER_MASKS, EAST, NORTH, NORTHEAST, NORTHWEST, RAY_ATTACKS, ROOK_BLOCKER_MASKS,
+        SOUTH, SOUTHEAST, SOUTHWEST, WEST,
+    },
+    rand,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -34,15 +36,10 @@ impl Display for SlidingPieces {
     }
 }
 
-fn get_ray_attacks(
-    board: &Board,
-    current_square: (u8, u8),
-    direction: u8,
-    mut blockers: u64,
-) -> u64 {
+fn get_ray_attacks(current_square: (u8, u8), direction: u8, mut blockers: u64) -> u64 {
     let mut attacks =
-        board.ray_attacks[direction as usize][current_square.0 as usize][current_square.1 as usize];
-    blockers = attacks & blockers;
+        RAY_ATTACKS[direction as usize][current_square.0 as usize][current_square.1 as usize];
+    blockers &= attacks;
 
     if blockers > 0 {
         blockers = match direction {
@@ -121,7 +118,6 @@ fn get_ray_attacks(
 }
 
 pub fn find_magic(
-    board: &Board,
     piece: SlidingPieces,
     square: (u8, u8),
     index_bits: u8,
@@ -135,39 +131,34 @@ pub fn find_magic(
             shift,
             offset: 0u32,
         };
-        if let Ok(table) = try_magic(board, square, &piece, &entry) {
+        if let Ok(table) = try_magic(square, &piece, &entry) {
             return (table, entry);
         }
     }
 }
 
 // returns array for looking up legal moves
-fn try_magic(
-    board: &Board,
-    square: (u8, u8),
-    piece: &SlidingPieces,
-    entry: &MagicEntry,
-) -> Result<Vec<u64>, ()> {
+fn try_magic(square: (u8, u8), piece: &SlidingPieces, entry: &MagicEntry) -> Result<Vec<u64>, ()> {
     let mut blockers = 0;
     let mask = match piece {
-        SlidingPieces::Bishop => board.bishop_blocker_masks[square.0 as usize][square.1 as usize],
-        SlidingPieces::Rook => board.rook_blocker_masks[square.0 as usize][square.1 as usize],
+        SlidingPieces::Bishop => BISHOP_BLOCKER_MASKS[square.0 as usize][square.1 as usize],
+        SlidingPieces::Rook => ROOK_BLOCKER_MASKS[square.0 as usize][square.1 as usize],
     };
     let index_bits: u8 = 64 - entry.shift;
     let mut table = vec![0; 1 << index_bits];
     loop {
         let moves = match piece {
             SlidingPieces::Bishop => {
-                get_ray_attacks(board, square, NORTHEAST, blockers)
-                    | get_ray_attacks(board, square, NORTHWEST, blockers)
-                    | get_ray_attacks(board, square, SOUTHEAST, blockers)
-                    | get_ray_attacks(board, square, SOUTHWEST, blockers)
+                get_ray_attacks(square, NORTHEAST, blockers)
+                    | get_ray_attacks(square, NORTHWEST, blockers)
+                    | get_ray_attacks(square, SOUTHEAST, blockers)
+                    | get_ray_attacks(square, SOUTHWEST, blockers)
             }
             SlidingPieces::Rook => {
-                get_ray_attacks(board, square, NORTH, blockers)
-                    | get_ray_attacks(board, square, EAST, blockers)
-                    | get_ray_attacks(board, square, SOUTH, blockers)
-                    | get_ray_attacks(board, square, WEST, blockers)
+                get_ray_attacks(square, NORTH, blockers)
+                    | get_ray_attacks(square, EAST, blockers)
+                    | get_ray_attacks(square, SOUTH, blockers)
+                    | get_ray_attacks(square, WEST, blockers)
             }
         };
 
@@ -227,28 +218,26 @@ pub fn write_magics_to_file(filename: String) {
     }; 8]; 8];
     let mut _rook_table_size = 0usize;
     let mut _bishop_table_size = 0usize;
-    let board = BoardBuilder::new().build();
 
     for rank in 0u8..8 {
         for file in 0u8..8 {
             let index_bits: u8 =
-                board.rook_blocker_masks[rank as usize][file as usize].count_ones() as u8;
+                ROOK_BLOCKER_MASKS[rank as usize][file as usize].count_ones() as u8;
             (
                 rook_moves[rank as usize][file as usize],
                 rook_magics[rank as usize][file as usize],
-            ) = find_magic(&board, SlidingPieces::Rook, (rank, file), index_bits);
+            ) = find_magic(SlidingPieces::Rook, (rank, file), index_bits);
             _rook_table_size += rook_moves[rank as usize][file as usize].len();
         }
     }
 
     for rank in 0u8..8 {
         for file in 0u8..8 {
-            let index_bits =
-                board.bishop_blocker_masks[rank as usize][file as usize].count_ones() as u8;
+            let index_bits = BISHOP_BLOCKER_MASKS[rank as usize][file as usize].count_ones() as u8;
             (
                 bishop_moves[rank as usize][file as usize],
                 bishop_magics[rank as usize][file as usize],
-            ) = find_magic(&board, SlidingPieces::Bishop, (rank, file), index_bits);
+            ) = find_magic(SlidingPieces::Bishop, (rank, file), index_bits);
             _bishop_table_size += bishop_moves[rank as usize][file as usize].len();
         }
     }
@@ -277,20 +266,20 @@ pub fn write_magics_to_file(filename: String) {
         }
     }
     let _ =
-        out_file.write(format!("];\npub const ROOK_MAGICS: [[MagicEntry; 8]; 8] = [").as_bytes());
-    for rank in 0..8usize {
+        out_file.write(b"];\npub const ROOK_MAGICS: [[MagicEntry; 8]; 8] = [");
+    for rank in &rook_magics {
         let _ = out_file.write(b"[");
-        for file in 0..8usize {
-            let _ = out_file.write(format!("{:?},", rook_magics[rank][file]).as_bytes());
+        for entry in rank {
+            let _ = out_file.write(format!("{:?},", entry).as_bytes());
         }
         let _ = out_file.write(b"],");
     }
     let _ =
-        out_file.write(format!("];\npub const BISHOP_MAGICS: [[MagicEntry; 8]; 8] = [").as_bytes());
-    for rank in 0..8usize {
+        out_file.write(b"];\npub const BISHOP_MAGICS: [[MagicEntry; 8]; 8] = [");
+    for rank in &bishop_magics {
         let _ = out_file.write(b"[");
-        for file in 0..8usize {
-            let _ = out_file.write(format!("{:?},", bishop_magics[rank][file]).as_bytes());
+        for entry in rank {
+            let _ = out_file.write(format!("{:?},", entry).as_bytes());
         }
         let _ = out_file.write(b"],");
     }
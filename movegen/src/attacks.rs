@@ -0,0 +1,209 @@
+// attack maps and check information of a position, shared by the search, move ordering and SAN
+// so they dont each work out which king to look at and which pieces attack it
+use game::{
+    precomputed::{KING_MASKS, KNIGHT_MASKS, OBSTRUCTED},
+    Bitboard, Board, CheckInfo, Color, Move, PieceType, Square, Variant,
+};
+
+use crate::{
+    atomic_in_check, bishop_attacks, is_in_check, kings_touch, pawn_attackers_mask, rook_attacks,
+};
+
+pub trait Attacks {
+    /// the pieces of both colors attacking the square, with the given occupancy for the sliders
+    fn attackers_to(&self, square: Square, occupancy: u64) -> u64;
+
+    /// every square attacked by a piece of the color
+    fn attacked_squares(&self, color: Color) -> u64;
+
+    /// the check info of the position, computed the first time it is needed after a move
+    fn check_info(&mut self) -> CheckInfo;
+
+    /// the pieces giving check to the side to move
+    fn checkers(&mut self) -> u64 {
+        self.check_info().checkers
+    }
+
+    /// the pieces of the color that cant leave the line between their king and an enemy slider
+    fn pinned(&mut self, color: Color) -> u64;
+
+    /// whether the side to move is in check, uses the check info if it is already there
+    fn in_check(&self) -> bool;
+
+    /// whether the (legal) move checks the opponent's king
+    fn gives_check(&mut self, m: Move) -> bool;
+}
+
+// the pieces that are the only piece between the king of the color and an enemy slider
+fn blockers(board: &Board, color: Color) -> u64 {
+    let Some(king) = Bitboard(board.bitboards(color).king).lsb() else {
+        return 0;
+    };
+    let enemy = board.bitboards(!color);
+    let occupancy = board.white.all | board.black.all;
+
+    let snipers = (rook_attacks(king, 0) & (enemy.rooks | enemy.queens))
+        | (bishop_attacks(king, 0) & (enemy.bishops | enemy.queens));
+
+    let mut blockers = 0;
+    for sniper in Bitboard(snipers) {
+        let between = OBSTRUCTED[king.index()][sniper.index()] & occupancy;
+        if between.count_ones() == 1 {
+            blockers |= between;
+        }
+    }
+
+    blockers
+}
+
+// whether the squares are on one line, with b between a and c or c between a and b
+fn aligned(a: Square, b: Square, c: Square) -> bool {
+    OBSTRUCTED[a.index()][c.index()] & b.bitboard().0 != 0
+        || OBSTRUCTED[a.index()][b.index()] & c.bitboard().0 != 0
+}
+
+impl Attacks for Board {
+    fn attackers_to(&self, square: Square, occupancy: u64) -> u64 {
+        let (r, f) = square.mailbox();
+        let (white, black) = (self.white, self.black);
+        let diagonal = white.bishops | white.queens | black.bishops | black.queens;
+        let orthogonal = white.rooks | white.queens | black.rooks | black.queens;
+
+        (bishop_attacks(square, occupancy) & diagonal)
+            | (rook_attacks(square, occupancy) & orthogonal)
+            | (KNIGHT_MASKS[r][f] & (white.knights | black.knights))
+            | (KING_MASKS[r][f] & (white.king | black.king))
+            // a white pawn attacks the square from where a black pawn on it would attack
+            | (pawn_attackers_mask(Color::Black, square) & white.pawns)
+            | (pawn_attackers_mask(Color::White, square) & black.pawns)
+    }
+
+    fn attacked_squares(&self, color: Color) -> u64 {
+        let pieces = self.bitboards(color);
+        let occupancy = self.white.all | self.black.all;
+
+        let mut attacked = 0;
+        for square in Bitboard(pieces.king) {
+            let (r, f) = square.mailbox();
+            attacked |= KING_MASKS[r][f];
+        }
+        for square in Bitboard(pieces.pawns) {
+            attacked |= pawn_attackers_mask(color, square);
+        }
+        for square in Bitboard(pieces.knights) {
+            let (r, f) = square.mailbox();
+            attacked |= KNIGHT_MASKS[r][f];
+        }
+        for square in Bitboard(pieces.bishops | pieces.queens) {
+            attacked |= bishop_attacks(square, occupancy);
+        }
+        for square in Bitboard(pieces.rooks | pieces.queens) {
+            attacked |= rook_attacks(square, occupancy);
+        }
+
+        attacked
+    }
+
+    fn check_info(&mut self) -> CheckInfo {
+        if let Some(info) = self.check_info {
+            return info;
+        }
+
+        // the king is an ordinary piece in antichess, nothing is ever in check or pinned
+        if self.variant == Variant::Antichess {
+            self.check_info = Some(CheckInfo::default());
+            return CheckInfo::default();
+        }
+
+        let us = self.turn;
+        let occupancy = self.white.all | self.black.all;
+        // the horde has no king, and kings explode in atomic chess
+        let our_king = Bitboard(self.bitboards(us).king).lsb();
+        let their_king = Bitboard(self.bitboards(!us).king).lsb();
+
+        let mut check_squares = [0; 6];
+        if let Some(their_king) = their_king {
+            let (r, f) = their_king.mailbox();
+            let bishop_checks = bishop_attacks(their_king, occupancy);
+            let rook_checks = rook_attacks(their_king, occupancy);
+            check_squares[PieceType::Pawn.index()] = pawn_attackers_mask(!us, their_king);
+            check_squares[PieceType::Knight.index()] = KNIGHT_MASKS[r][f];
+            check_squares[PieceType::Bishop.index()] = bishop_checks;
+            check_squares[PieceType::Rook.index()] = rook_checks;
+            check_squares[PieceType::Queen.index()] = bishop_checks | rook_checks;
+        }
+
+        let mut blockers_by_color = [0; 2];
+        for color in Color::ALL {
+            blockers_by_color[color.index()] = blockers(self, color);
+        }
+
+        // kings next to each other cant capture one another in atomic chess
+        let checkers = match our_king {
+            Some(_) if self.variant == Variant::Atomic && kings_touch(self) => 0,
+            Some(our_king) => self.attackers_to(our_king, occupancy) & self.bitboards(!us).all,
+            None => 0,
+        };
+
+        let info = CheckInfo {
+            checkers,
+            blockers: blockers_by_color,
+            check_squares,
+        };
+        self.check_info = Some(info);
+
+        info
+    }
+
+    fn pinned(&mut self, color: Color) -> u64 {
+        self.check_info().blockers[color.index()] & self.bitboards(color).all
+    }
+
+    fn in_check(&self) -> bool {
+        match self.check_info {
+            Some(info) => info.checkers != 0,
+            None if self.variant == Variant::Antichess => false,
+            None if self.variant == Variant::Atomic => atomic_in_check(self, self.turn),
+            None => match Bitboard(self.bitboards(self.turn).king).lsb() {
+                Some(king) => is_in_check(self, self.turn, king),
+                None => false,
+            },
+        }
+    }
+
+    fn gives_check(&mut self, m: Move) -> bool {
+        if self.variant == Variant::Antichess || self.bitboards(!self.turn).king == 0 {
+            return false;
+        }
+
+        // exploding pieces can uncover a check from anywhere, and exploding the king is not a
+        // check
+        if self.variant == Variant::Atomic {
+            let mut after = *self;
+            let _ = after.make_move(m);
+            return atomic_in_check(&after, after.turn);
+        }
+
+        let info = self.check_info();
+
+        // castling (the rook gives the check), en passant (two pawns leave their squares) and
+        // promotions (the pawn may have been blocking the new piece) are rare enough to just
+        // make the move
+        let castling = m.piece.piece_type() == PieceType::King
+            && m.from.file().index().abs_diff(m.to.file().index()) == 2;
+        if castling || m.en_passant || m.promotion_piece.is_some() {
+            let undo = self.make_move(m);
+            let check = self.in_check();
+            self.unmake_move(m, undo);
+
+            return check;
+        }
+
+        let their_king = self.king_square(!self.turn);
+
+        // a direct check, or a discovered check by a piece that leaves the line to the king
+        info.check_squares[m.piece.piece_type().index()] & m.to.bitboard().0 != 0
+            || (info.blockers[(!self.turn).index()] & m.from.bitboard().0 != 0
+                && !aligned(their_king, m.from, m.to))
+    }
+}
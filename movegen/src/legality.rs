@@ -0,0 +1,197 @@
+// legality of single moves, for pseudo-legal move generation and for moves that come from
+// somewhere other than the move generator (the transposition table, killers). a hash collision
+// can hand the search a move from a different position, so these have to be checked before they
+// are played
+use game::{
+    precomputed::{KING_MASKS, KNIGHT_MASKS},
+    Board, Move, Piece, PieceType, Rank, Square, Variant,
+};
+
+use crate::{
+    bishop_attacks, can_castle, has_pseudo_legal_moves, is_atomic_legal, is_attacked,
+    pawn_attackers_mask, rook_attacks, Castling, GenType, CASTLING, PAWN_DROP_RANKS,
+};
+
+pub trait Legality {
+    /// whether the move follows the movement rules in this position, ignoring whether it leaves
+    /// the king in check. every field of the move has to match the board, so a move made for
+    /// a different position is rejected
+    fn is_pseudo_legal(&self, m: Move) -> bool;
+
+    /// whether a pseudo-legal move keeps the king of the side to move out of check
+    fn is_legal(&self, m: Move) -> bool;
+}
+
+// the castling of the side to move with the given king squares, if there is one
+fn castling(board: &Board, from: Square, to: Square) -> Option<Castling> {
+    CASTLING
+        .into_iter()
+        .find(|&(color, _, king_from, king_to, _, _)| {
+            color == board.turn && king_from == from && king_to == to
+        })
+}
+
+impl Legality for Board {
+    fn is_pseudo_legal(&self, m: Move) -> bool {
+        let color = self.turn;
+        if m.drop {
+            let piece_type = m.piece.piece_type();
+            let allowed = if piece_type == PieceType::Pawn {
+                PAWN_DROP_RANKS
+            } else {
+                u64::MAX
+            };
+
+            return self.variant == Variant::Crazyhouse
+                && m.piece.color() == color
+                && self.pocket(color).count(piece_type) > 0
+                && self.piece_at(m.to).is_none()
+                && allowed & m.to.bitboard().0 != 0
+                && m == Move::new_drop(m.piece, m.to)
+                && !self.is_variant_loss();
+        }
+
+        if m.piece.color() != color
+            || self.piece_at(m.from) != Some(m.piece)
+            || self.is_variant_loss()
+        {
+            return false;
+        }
+
+        let ours = self.bitboards(color).all;
+        let occupancy = self.white.all | self.black.all;
+        let to = m.to.bitboard().0;
+        if ours & to != 0 {
+            return false;
+        }
+
+        let piece_type = m.piece.piece_type();
+        if m.en_passant {
+            return piece_type == PieceType::Pawn
+                && self.en_passant_square == Some(m.to)
+                && m.capture_piece == Some(Piece::new(!color, PieceType::Pawn))
+                && m.promotion_piece.is_none()
+                && pawn_attackers_mask(color, m.from) & to != 0;
+        }
+
+        // the king is never captured (except in antichess), a position where it could be is
+        // already lost. in atomic chess the king cant capture at all
+        if m.capture_piece != self.piece_at(m.to)
+            || (m.capture_piece.map(|piece| piece.piece_type()) == Some(PieceType::King)
+                && self.variant != Variant::Antichess)
+            || (m.capture_piece.is_some()
+                && piece_type == PieceType::King
+                && self.variant == Variant::Atomic)
+        {
+            return false;
+        }
+
+        let last_rank = m.to.rank().relative(color.is_white()) == Rank::Eighth;
+        let promotes = piece_type == PieceType::Pawn && last_rank;
+        let promotion_allowed = match m.promotion_piece {
+            Some(piece) => {
+                promotes
+                    && piece.color() == color
+                    && (PieceType::PROMOTIONS.contains(&piece.piece_type())
+                        || (piece.piece_type() == PieceType::King
+                            && self.variant == Variant::Antichess))
+            }
+            None => !promotes,
+        };
+        if !promotion_allowed {
+            return false;
+        }
+
+        let (r, f) = m.from.mailbox();
+        let reachable = match piece_type {
+            PieceType::Pawn => {
+                let forward = if color.is_white() { 1 } else { -1 };
+                if m.capture_piece.is_some() {
+                    pawn_attackers_mask(color, m.from)
+                } else if m.from.offset(0, forward) == Some(m.to) {
+                    to
+                } else {
+                    // the double push from the starting rank, over an empty square. the white
+                    // pawns of the horde can also double push from the first rank
+                    let rank = m.from.rank().relative(color.is_white());
+                    let start = rank == Rank::Second
+                        || (rank == Rank::First
+                            && color.is_white()
+                            && self.variant == Variant::Horde);
+                    match m.from.offset(0, forward) {
+                        Some(over)
+                            if start
+                                && occupancy & over.bitboard().0 == 0
+                                && over.offset(0, forward) == Some(m.to) =>
+                        {
+                            to
+                        }
+                        _ => 0,
+                    }
+                }
+            }
+            PieceType::Knight => KNIGHT_MASKS[r][f],
+            PieceType::Bishop => bishop_attacks(m.from, occupancy),
+            PieceType::Rook => rook_attacks(m.from, occupancy),
+            PieceType::Queen => bishop_attacks(m.from, occupancy) | rook_attacks(m.from, occupancy),
+            PieceType::King => {
+                if let Some(castling) = castling(self, m.from, m.to) {
+                    return m.capture_piece.is_none() && can_castle(self, castling);
+                }
+
+                KING_MASKS[r][f]
+            }
+        };
+
+        reachable & to != 0
+    }
+
+    // a drop is checked like any other move that isnt made by the king: the new piece is added
+    // to the occupancy, so it blocks a check if it can
+    fn is_legal(&self, m: Move) -> bool {
+        // there are no checks in antichess, but captures are compulsory
+        if self.variant == Variant::Antichess {
+            return m.capture_piece.is_some() || !has_pseudo_legal_moves(self, GenType::Captures);
+        }
+
+        if self.variant == Variant::Atomic {
+            return is_atomic_legal(self, m);
+        }
+
+        let color = m.piece.color();
+        // the horde has no king to leave in check
+        if self.bitboards(color).king == 0 {
+            return true;
+        }
+
+        let occupancy = self.white.all | self.black.all;
+        let from = m.from.bitboard().0;
+        let to = m.to.bitboard().0;
+
+        if m.piece.piece_type() == PieceType::King {
+            // castling already checked every square the king passes
+            if castling(self, m.from, m.to).is_some() {
+                return true;
+            }
+
+            // the king is taken out of the occupancy, so it cant block a slider attacking the
+            // square it moves to
+            return !is_attacked(self, color, m.to, occupancy ^ from, 0);
+        }
+
+        let captured = if m.en_passant {
+            // the captured pawn is next to the moving pawn
+            Square::new(m.to.file(), m.from.rank()).bitboard().0
+        } else {
+            to
+        };
+
+        !is_attacked(
+            self,
+            color,
+            self.king_square(color),
+            occupancy & !from & !captured | to,
+            captured,
+        )
+    }
+}
@@ -1,15 +1,21 @@
+mod attacks;
+mod legality;
 pub mod magics;
+pub mod movelist;
 pub mod perft;
+pub mod picker;
 pub mod san;
 
-use game::{
-    get_bit_index, get_piece_color, get_piece_type, is_black_kingside, is_black_queenside,
-    is_white_kingside, is_white_queenside,
-};
+pub use attacks::Attacks;
+pub use legality::Legality;
+pub use movelist::{MoveList, MoveSink};
 
 use game::{
-    Board, Move, Piece, BISHOP, BLACK, BLACK_KINGSIDE, BLACK_QUEENSIDE, KING, KNIGHT, PAWN, QUEEN,
-    ROOK, WHITE, WHITE_KINGSIDE, WHITE_QUEENSIDE,
+    precomputed::{
+        BISHOP_BLOCKER_MASKS, KING_MASKS, KNIGHT_MASKS, OBSTRUCTED, ROOK_BLOCKER_MASKS,
+    },
+    Bitboard, Board, CastleState, Color, Move, Piece, PieceType, Rank, Square, Variant,
+    BLACK_KINGSIDE, BLACK_QUEENSIDE, WHITE_KINGSIDE, WHITE_QUEENSIDE,
 };
 
 const NOT_H_FILE: u64 = 0x7F7F7F7F7F7F7F7F;
@@ -23,305 +29,521 @@ const BLACK_KINGSIDE_OCCUPANCY_MASK: u64 =
 const BLACK_QUEENSIDE_OCCUPANCY_MASK: u64 =
     0b01110000_00000000_00000000_00000000_00000000_00000000_00000000_00000000;
 
-pub fn generate_legal_moves(board: &mut Board, captures_only: bool) -> Vec<Move> {
-    // with_capacity so no allocations are needed during movegen
-    let mut moves = Vec::with_capacity(218);
+// (color, right, king from, king to, squares that have to be empty, square the king passes)
+type Castling = (Color, CastleState, Square, Square, u64, Square);
+
+const CASTLING: [Castling; 4] = [
+    (
+        Color::White,
+        WHITE_KINGSIDE,
+        Square::E1,
+        Square::G1,
+        WHITE_KINGSIDE_OCCUPANCY_MASK,
+        Square::F1,
+    ),
+    (
+        Color::White,
+        WHITE_QUEENSIDE,
+        Square::E1,
+        Square::C1,
+        WHITE_QUEENSIDE_OCCUPANCY_MASK,
+        Square::D1,
+    ),
+    (
+        Color::Black,
+        BLACK_KINGSIDE,
+        Square::E8,
+        Square::G8,
+        BLACK_KINGSIDE_OCCUPANCY_MASK,
+        Square::F8,
+    ),
+    (
+        Color::Black,
+        BLACK_QUEENSIDE,
+        Square::E8,
+        Square::C8,
+        BLACK_QUEENSIDE_OCCUPANCY_MASK,
+        Square::D8,
+    ),
+];
+
+// pawns can also promote to a king in antichess
+const ANTICHESS_PROMOTIONS: [PieceType; 5] = [
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+// pawns on these ranks promote with their next move
+const WHITE_PROMOTION_RANK: u64 = 0x00FF_0000_0000_0000;
+const BLACK_PROMOTION_RANK: u64 = 0xFF00;
+// pawns that can move without promoting, the first rank is for the pawns of the horde
+const WHITE_PAWN_RANKS: u64 = 0x0000_FFFF_FFFF_FFFF;
+const BLACK_PAWN_RANKS: u64 = 0xFFFF_FFFF_FFFF_0000;
+// pawns cant be dropped on the first or the last rank
+pub(crate) const PAWN_DROP_RANKS: u64 = 0x00FF_FFFF_FFFF_FF00;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenType {
+    // moves that capture a piece, including en passant and capturing promotions
+    Captures,
+    // every other move, including castling, drops and promotions that dont capture
+    Quiets,
+    All,
+}
 
-    let mut current_square: (u8, u8) = (0, 0);
-    let king_position: (u8, u8) = if board.turn {
-        board.white_king_position
+// squares that pieces (other than pawns) of the side to move can move to
+fn target_mask(board: &Board, gen: GenType) -> u64 {
+    let (ours, theirs) = if board.turn.is_white() {
+        (board.white.all, board.black.all)
     } else {
-        board.black_king_position
+        (board.black.all, board.white.all)
     };
 
-    let (num_checkers, checkers) =
-        count_attackers(board, if board.turn { WHITE } else { BLACK }, king_position);
+    match gen {
+        GenType::Captures => theirs,
+        GenType::Quiets => !(ours | theirs),
+        GenType::All => !ours,
+    }
+}
+
+pub fn generate_legal_moves(board: &mut Board, captures_only: bool) -> Vec<Move> {
+    let mut moves = MoveList::new();
+    generate_moves(
+        board,
+        &mut moves,
+        if captures_only {
+            GenType::Captures
+        } else {
+            GenType::All
+        },
+    );
+
+    moves.to_vec()
+}
+
+/// writes the legal moves of the given type into the move list, the list is not cleared first
+pub fn generate_moves(board: &mut Board, moves: &mut impl MoveSink, gen: GenType) {
+    // the game is already over
+    if board.is_variant_loss() {
+        return;
+    }
+    if board.variant == Variant::Antichess {
+        generate_antichess_moves(board, moves, gen);
+        return;
+    }
+    if board.variant == Variant::Atomic {
+        generate_atomic_moves(board, moves, gen);
+        return;
+    }
+    // the horde has no king to leave in check
+    if board.bitboards(board.turn).king == 0 {
+        generate_pseudo_legal_moves(board, moves, gen);
+        return;
+    }
+
+    let king_position = board.king_square(board.turn);
+
+    let (num_checkers, checkers) = count_attackers(board, board.turn, king_position);
     let double_check = num_checkers > 1;
     if double_check {
-        generate_king_moves(board, &mut moves, king_position, captures_only);
+        generate_king_moves(board, moves, king_position, gen);
+        return;
+    }
+
+    let allowed_targets = if num_checkers == 0 {
+        u64::MAX
     } else {
-        let allowed_targets = if num_checkers == 0 {
-            u64::MAX
+        // we are in check (not double)
+
+        checkers
+    }; // if we are in check, checkers includes both the checking pieces and the square which
+       // block the check
+
+    let pinned = generate_pinned_moves(board, moves, gen, allowed_targets);
+    // the sink may only want to know whether there are moves at all
+    if moves.is_full() {
+        return;
+    }
+
+    let our_bitboards = *board.bitboards(board.turn);
+    let targets = target_mask(board, gen) & allowed_targets;
+
+    // pinned knights can never move, the other pinned pieces were handled above
+    for square in Bitboard(our_bitboards.knights & !pinned) {
+        generate_knight_moves(board, moves, square, targets);
+    }
+
+    for square in Bitboard(our_bitboards.bishops & !pinned) {
+        generate_bishop_moves(board, moves, square, targets);
+    }
+
+    for square in Bitboard(our_bitboards.rooks & !pinned) {
+        generate_rook_moves(board, moves, square, targets);
+    }
+
+    for square in Bitboard(our_bitboards.queens & !pinned) {
+        generate_queen_moves(board, moves, square, targets);
+    }
+    if moves.is_full() {
+        return;
+    }
+
+    generate_king_moves(board, moves, king_position, gen);
+    if moves.is_full() {
+        return;
+    }
+
+    let promoting = our_bitboards.pawns
+        & !pinned
+        & if board.turn.is_white() {
+            WHITE_PROMOTION_RANK
         } else {
-            // we are in check (not double)
+            BLACK_PROMOTION_RANK
+        };
+    for square in Bitboard(promoting) {
+        generate_pawn_promotions(board, moves, square, allowed_targets, gen);
+    }
 
-            checkers
-        }; // if we are in check, checkers includes both the checking pieces and the square which
-           // block the check
+    if gen != GenType::Captures {
+        generate_pawn_pushes(board, moves, allowed_targets, !pinned);
+        // a drop can only get out of check by blocking it
+        generate_drops(board, moves, allowed_targets);
+    }
+    if gen != GenType::Quiets {
+        generate_pawn_captures(board, moves, allowed_targets, !pinned, true);
+    }
+}
 
-        let pinned = generate_pinned_moves(board, &mut moves, captures_only, allowed_targets);
+/// writes the pseudo-legal moves of the given type into the move list, the list is not cleared
+/// first. the moves follow the movement rules but may leave the king in check, so they have to
+/// be checked with `Legality::is_legal` before they are played
+pub fn generate_pseudo_legal_moves(board: &Board, moves: &mut impl MoveSink, gen: GenType) {
+    if board.is_variant_loss() {
+        return;
+    }
 
-        for rank in board.board {
-            for piece in rank {
-                if (get_piece_color!(piece) == WHITE) != board.turn || piece == 0 {
-                    current_square.1 += 1;
-                    continue;
-                }
+    let our_bitboards = *board.bitboards(board.turn);
+    let targets = target_mask(board, gen);
 
-                // skip if piece is pinned
-                if (1 << get_bit_index!(current_square)) & pinned > 0 {
-                    current_square.1 += 1;
-                    continue;
-                }
+    for square in Bitboard(our_bitboards.knights) {
+        generate_knight_moves(board, moves, square, targets);
+    }
 
-                if get_piece_type!(piece) == KNIGHT {
-                    generate_knight_moves(
-                        board,
-                        &mut moves,
-                        current_square,
-                        captures_only,
-                        allowed_targets,
-                    );
-                }
+    for square in Bitboard(our_bitboards.bishops) {
+        generate_bishop_moves(board, moves, square, targets);
+    }
 
-                if get_piece_type!(piece) == KING {
-                    generate_king_moves(board, &mut moves, current_square, captures_only);
-                }
+    for square in Bitboard(our_bitboards.rooks) {
+        generate_rook_moves(board, moves, square, targets);
+    }
 
-                if get_piece_type!(piece) == BISHOP {
-                    generate_bishop_moves(
-                        board,
-                        &mut moves,
-                        current_square,
-                        captures_only,
-                        allowed_targets,
-                    );
-                }
+    for square in Bitboard(our_bitboards.queens) {
+        generate_queen_moves(board, moves, square, targets);
+    }
+    if moves.is_full() {
+        return;
+    }
 
-                if get_piece_type!(piece) == ROOK {
-                    generate_rook_moves(
-                        board,
-                        &mut moves,
-                        current_square,
-                        captures_only,
-                        allowed_targets,
-                    );
-                }
+    // antichess can have any number of kings, and kings cant capture in atomic chess
+    let king_targets = if board.variant == Variant::Atomic {
+        targets & !board.bitboards(!board.turn).all
+    } else {
+        targets
+    };
+    for square in Bitboard(our_bitboards.king) {
+        let (r, f) = square.mailbox();
+        generate_moves_from_targets(
+            board,
+            moves,
+            square,
+            Piece::new(board.turn, PieceType::King),
+            KING_MASKS[r][f] & king_targets,
+        );
+    }
+    if gen != GenType::Captures {
+        generate_castling(board, moves);
+    }
+    if moves.is_full() {
+        return;
+    }
 
-                if get_piece_type!(piece) == QUEEN {
-                    generate_queen_moves(
-                        board,
-                        &mut moves,
-                        current_square,
-                        captures_only,
-                        allowed_targets,
-                    );
-                }
+    let promoting = our_bitboards.pawns
+        & if board.turn.is_white() {
+            WHITE_PROMOTION_RANK
+        } else {
+            BLACK_PROMOTION_RANK
+        };
+    for square in Bitboard(promoting) {
+        generate_pawn_promotions(board, moves, square, u64::MAX, gen);
+    }
 
-                if (current_square.0 == 1 || current_square.0 == 6)
-                    && get_piece_type!(piece) == PAWN
-                {
-                    generate_pawn_promotions(board, &mut moves, current_square, allowed_targets);
-                }
+    if gen != GenType::Captures {
+        generate_pawn_pushes(board, moves, u64::MAX, u64::MAX);
+        generate_drops(board, moves, u64::MAX);
+    }
+    if gen != GenType::Quiets {
+        generate_pawn_captures(board, moves, u64::MAX, u64::MAX, false);
+    }
+}
 
-                current_square.1 += 1;
-            }
-            current_square.0 += 1;
-            current_square.1 = 0;
+// crazyhouse drops of every piece in the pocket of the side to move onto the empty target squares
+fn generate_drops(board: &Board, moves: &mut impl MoveSink, allowed_targets: u64) {
+    if board.variant != Variant::Crazyhouse {
+        return;
+    }
+
+    let empty = !(board.white.all | board.black.all) & allowed_targets;
+    for (piece_type, _) in board.pocket(board.turn).pieces() {
+        let targets = if piece_type == PieceType::Pawn {
+            empty & PAWN_DROP_RANKS
+        } else {
+            empty
+        };
+
+        let piece = Piece::new(board.turn, piece_type);
+        for square in Bitboard(targets) {
+            moves.push(Move::new_drop(piece, square));
         }
+    }
+}
+
+// only remembers that there was a move, the generation stops at the first one
+#[derive(Default)]
+struct AnyMove(bool);
+
+impl MoveSink for AnyMove {
+    fn push(&mut self, _: Move) {
+        self.0 = true;
+    }
 
-        if !captures_only {
-            generate_pawn_pushes(board, &mut moves, allowed_targets, !pinned);
+    fn is_full(&self) -> bool {
+        self.0
+    }
+}
+
+/// whether the side to move has a legal move of the given type. this stops at the first move it
+/// finds, so it is cheaper than generating the moves to see if there are any
+pub fn has_moves(board: &mut Board, gen: GenType) -> bool {
+    let mut any = AnyMove::default();
+    generate_moves(board, &mut any, gen);
+    any.0
+}
+
+pub(crate) fn has_pseudo_legal_moves(board: &Board, gen: GenType) -> bool {
+    let mut any = AnyMove::default();
+    generate_pseudo_legal_moves(board, &mut any, gen);
+    any.0
+}
+
+// captures are compulsory in antichess, so quiet moves are only legal when nothing can be
+// captured. there are no checks, every pseudo-legal move is legal otherwise
+fn generate_antichess_moves(board: &Board, moves: &mut impl MoveSink, gen: GenType) {
+    if gen == GenType::Captures {
+        generate_pseudo_legal_moves(board, moves, gen);
+        return;
+    }
+
+    if !has_pseudo_legal_moves(board, GenType::Captures) {
+        generate_pseudo_legal_moves(board, moves, GenType::Quiets);
+    } else if gen == GenType::All {
+        generate_pseudo_legal_moves(board, moves, GenType::Captures);
+    }
+}
+
+// passes on the pseudo-legal moves the own king survives
+struct AtomicLegal<'a, S> {
+    board: &'a Board,
+    moves: &'a mut S,
+}
+
+impl<S: MoveSink> MoveSink for AtomicLegal<'_, S> {
+    fn push(&mut self, m: Move) {
+        if !self.moves.is_full() && is_atomic_legal(self.board, m) {
+            self.moves.push(m);
         }
-        generate_pawn_captures(board, &mut moves, allowed_targets, !pinned);
     }
 
-    if captures_only {
-        moves.retain(|&m| m.capture_piece.is_some());
+    fn is_full(&self) -> bool {
+        self.moves.is_full()
     }
-    //moves.shrink_to_fit();
+}
+
+// an atomic capture can explode pieces that pin or block, so every pseudo-legal move is made on
+// a copy of the board to see whether the own king survives it
+fn generate_atomic_moves(board: &Board, moves: &mut impl MoveSink, gen: GenType) {
+    generate_pseudo_legal_moves(board, &mut AtomicLegal { board, moves }, gen);
+}
 
-    moves
+// kings next to each other cant capture one another, so neither is in check
+fn kings_touch(board: &Board) -> bool {
+    let Some(king) = Bitboard(board.white.king).lsb() else {
+        return false;
+    };
+
+    let (r, f) = king.mailbox();
+    KING_MASKS[r][f] & board.black.king != 0
+}
+
+// whether the king of the color is in check by the rules of atomic chess
+fn atomic_in_check(board: &Board, color: Color) -> bool {
+    match Bitboard(board.bitboards(color).king).lsb() {
+        Some(king) => !kings_touch(board) && is_in_check(board, color, king),
+        None => false,
+    }
+}
+
+// the own king has to survive the move and can only be left in check if the enemy king explodes
+fn is_atomic_legal(board: &Board, m: Move) -> bool {
+    let color = m.piece.color();
+    let mut after = *board;
+    let _ = after.make_move(m);
+
+    after.bitboards(color).king != 0
+        && (after.bitboards(!color).king == 0 || !atomic_in_check(&after, color))
 }
 
 // returns bitboard of pinned pieces
 pub fn generate_pinned_moves(
     board: &mut Board,
-    moves: &mut Vec<Move>,
-    captures_only: bool,
+    moves: &mut impl MoveSink,
+    gen: GenType,
     allowed_targets: u64,
 ) -> u64 {
-    let (our_king_position, our_bitboards, opponent_bitboards) = if board.turn {
-        (board.white_king_position, board.white, board.black)
-    } else {
-        (board.black_king_position, board.black, board.white)
-    };
+    let our_king_position = board.king_square(board.turn);
+    let (our_bitboards, opponent_bitboards) =
+        (*board.bitboards(board.turn), *board.bitboards(!board.turn));
+    let targets = target_mask(board, gen) & allowed_targets;
 
     let mut pinned = 0u64;
-    let mut pinners = orthogonal_xrays(board, our_bitboards.all, our_king_position)
+    let pinners = orthogonal_xrays(board, our_bitboards.all, our_king_position)
         & (opponent_bitboards.queens | opponent_bitboards.rooks); //King Xrays opponent's orthogonal sliders
-    while pinners != 0 {
-        let pinner = pinners.trailing_zeros();
-        let obstructed =
-            board.obstructed[get_bit_index!(our_king_position) as usize][pinner as usize];
+    for pinner in Bitboard(pinners) {
+        let obstructed = OBSTRUCTED[our_king_position.index()][pinner.index()];
+        let pin_ray = obstructed | pinner.bitboard().0;
 
-        let pinned_rooks = obstructed & our_bitboards.rooks;
-        let pinned_queens = obstructed & our_bitboards.queens;
+        let pinned_rooks = Bitboard(obstructed & our_bitboards.rooks);
+        let pinned_queens = Bitboard(obstructed & our_bitboards.queens);
         let pinned_pawns = obstructed & our_bitboards.pawns;
 
-        if pinned_rooks > 0 {
-            let rook = pinned_rooks.trailing_zeros();
-            generate_rook_moves(
-                board,
-                moves,
-                (7 - (rook as u8 / 8), 7 - (rook as u8 % 8)),
-                captures_only,
-                (obstructed | (pinners & (1 << pinner))) & allowed_targets,
-            );
-        } else if pinned_queens > 0 {
-            let queen = pinned_queens.trailing_zeros();
-            generate_queen_moves(
-                board,
-                moves,
-                (7 - (queen as u8 / 8), 7 - (queen as u8 % 8)),
-                captures_only,
-                (obstructed | (pinners & (1 << pinner))) & allowed_targets,
-            );
-        } else if pinned_pawns > 0 {
+        if let Some(rook) = pinned_rooks.lsb() {
+            generate_rook_moves(board, moves, rook, pin_ray & targets);
+        } else if let Some(queen) = pinned_queens.lsb() {
+            generate_queen_moves(board, moves, queen, pin_ray & targets);
+        } else if pinned_pawns > 0 && gen != GenType::Captures {
             // orthogonally pinned pawns cannot capture or promote at all
-            generate_pawn_pushes(
-                board,
-                moves,
-                (obstructed | (pinners & (1 << pinner))) & allowed_targets,
-                pinned_pawns,
-            );
+            generate_pawn_pushes(board, moves, pin_ray & allowed_targets, pinned_pawns);
         }
 
         pinned |= obstructed & our_bitboards.all;
-        pinners ^= 1 << pinner;
     }
 
-    pinners = diagonal_xrays(board, our_bitboards.all, our_king_position)
+    let pinners = diagonal_xrays(board, our_bitboards.all, our_king_position)
         & (opponent_bitboards.queens | opponent_bitboards.bishops);
-    while pinners != 0 {
-        let pinner = pinners.trailing_zeros();
-        let obstructed =
-            board.obstructed[get_bit_index!(our_king_position) as usize][pinner as usize];
-
-        let pinned_bishops = obstructed & our_bitboards.bishops;
-        let pinned_queens = obstructed & our_bitboards.queens;
-        let pinned_pawns = obstructed & our_bitboards.pawns;
-
-        if pinned_bishops > 0 {
-            let bishop = pinned_bishops.trailing_zeros();
-            generate_bishop_moves(
-                board,
-                moves,
-                (7 - (bishop as u8 / 8), 7 - (bishop as u8 % 8)),
-                captures_only,
-                (obstructed | (pinners & (1 << pinner))) & allowed_targets,
-            );
-        } else if pinned_queens > 0 {
-            let queen = pinned_queens.trailing_zeros();
-            generate_queen_moves(
-                board,
-                moves,
-                (7 - (queen as u8 / 8), 7 - (queen as u8 % 8)),
-                captures_only,
-                (obstructed | (pinners & (1 << pinner))) & allowed_targets,
-            );
-        } else if pinned_pawns > 0 {
-            // pawns cannot be pushed or promoted when pinned diagonally, but can capture the
-            // pinning piece
-
-            generate_pawn_captures(board, moves, (1 << pinner) & allowed_targets, pinned_pawns);
+    for pinner in Bitboard(pinners) {
+        let obstructed = OBSTRUCTED[our_king_position.index()][pinner.index()];
+        let pin_ray = obstructed | pinner.bitboard().0;
+
+        let pinned_bishops = Bitboard(obstructed & our_bitboards.bishops);
+        let pinned_queens = Bitboard(obstructed & our_bitboards.queens);
+        let pinned_pawns = Bitboard(obstructed & our_bitboards.pawns);
+
+        if let Some(bishop) = pinned_bishops.lsb() {
+            generate_bishop_moves(board, moves, bishop, pin_ray & targets);
+        } else if let Some(queen) = pinned_queens.lsb() {
+            generate_queen_moves(board, moves, queen, pin_ray & targets);
+        } else if gen != GenType::Quiets {
+            // pawns cannot be pushed when pinned diagonally, but can capture the pinning piece
+            // (and promote if the capture is on the last rank)
+            if let Some(pawn) = pinned_pawns.lsb() {
+                let promotion_rank = if board.turn.is_white() {
+                    WHITE_PROMOTION_RANK
+                } else {
+                    BLACK_PROMOTION_RANK
+                };
+
+                let target = pinner.bitboard().0 & allowed_targets;
+                if pinned_pawns.0 & promotion_rank != 0 {
+                    generate_pawn_promotions(board, moves, pawn, target, gen);
+                } else {
+                    generate_pawn_captures(board, moves, target, pinned_pawns.0, true);
+                }
+            }
         }
 
         pinned |= obstructed & our_bitboards.all;
-        pinners ^= 1 << pinner;
     }
 
     pinned
 }
+// squares attacked by a bishop on the given square, the first blocker in every direction is
+// included in the attacks
+pub fn bishop_attacks(square: Square, occupancy: u64) -> u64 {
+    let (r, f) = square.mailbox();
+    magics::BISHOP_MOVES[magics::magic_index(
+        &magics::BISHOP_MAGICS[r][f],
+        BISHOP_BLOCKER_MASKS[r][f] & occupancy,
+    )]
+}
 
-// use the BLACK / WHITE constants
-pub fn count_attackers(board: &Board, color: u8, square: (u8, u8)) -> (u8, u64) {
-    let ortho_blocker_mask = board.rook_blocker_masks[square.0 as usize][square.1 as usize];
-    let ortho_blockers = ortho_blocker_mask & (board.white.all | board.black.all);
-    let ortho_entry = &magics::ROOK_MAGICS[square.0 as usize][square.1 as usize];
-    let orthogonal_attacks = magics::ROOK_MOVES[magics::magic_index(ortho_entry, ortho_blockers)];
-
-    let diag_blocker_mask = board.bishop_blocker_masks[square.0 as usize][square.1 as usize];
-    let diag_blockers = diag_blocker_mask & (board.white.all | board.black.all);
-    let diag_entry = &magics::BISHOP_MAGICS[square.0 as usize][square.1 as usize];
-    let diagonal_attacks = magics::BISHOP_MOVES[magics::magic_index(diag_entry, diag_blockers)];
-
-    let knight_attacks = board.knight_masks[square.0 as usize][square.1 as usize];
-    let king_attacks = board.king_masks[square.0 as usize][square.1 as usize];
-    let mut east = 0u64;
-    let mut west = 0u64;
-
-    if color == WHITE {
-        if square.1 > 0 {
-            west = (1 << get_bit_index!(square)) << 9;
-        }
+pub fn rook_attacks(square: Square, occupancy: u64) -> u64 {
+    let (r, f) = square.mailbox();
+    magics::ROOK_MOVES[magics::magic_index(
+        &magics::ROOK_MAGICS[r][f],
+        ROOK_BLOCKER_MASKS[r][f] & occupancy,
+    )]
+}
 
-        if square.1 < 7 {
-            east = (1 << get_bit_index!(square)) << 7
-        }
+// squares a pawn of the given color on the square would have to be on to attack it, or
+// the squares a pawn of the other color on the square attacks
+fn pawn_attackers_mask(color: Color, square: Square) -> u64 {
+    let square = square.bitboard();
+    let attackers = if color == Color::White {
+        square.north()
     } else {
-        if square.1 > 0 {
-            west = (1 << get_bit_index!(square)) >> 7
-        }
+        square.south()
+    };
 
-        if square.1 < 7 {
-            east = (1 << get_bit_index!(square)) >> 9
-        }
-    }
+    (attackers.east() | attackers.west()).0
+}
 
-    let pawn_attacks = if (color == WHITE && square.0 > 0) || (color == BLACK && square.0 < 7) {
-        west | east
-    } else {
-        0
-    };
+pub fn count_attackers(board: &Board, color: Color, square: Square) -> (u8, u64) {
+    let occupancy = board.white.all | board.black.all;
+    let orthogonal_attacks = rook_attacks(square, occupancy);
+    let diagonal_attacks = bishop_attacks(square, occupancy);
+
+    let (r, f) = square.mailbox();
+    let knight_attacks = KNIGHT_MASKS[r][f];
+    let king_attacks = KING_MASKS[r][f];
+    let pawn_attacks = pawn_attackers_mask(color, square);
 
     let mut num_attackers = 0;
     let mut attackers = 0u64;
 
-    let (our_bitboards, opponent_bitboards) = if color == WHITE {
-        (board.white, board.black)
-    } else if color == BLACK {
-        (board.black, board.white)
-    } else {
-        panic!("wrong color dumbo");
-    };
+    let (our_bitboards, opponent_bitboards) = (board.bitboards(color), board.bitboards(!color));
 
-    let mut diagonal_attackers =
+    let diagonal_attackers =
         diagonal_attacks & (opponent_bitboards.bishops | opponent_bitboards.queens);
     attackers |= diagonal_attackers;
     num_attackers += diagonal_attackers.count_ones();
 
-    while diagonal_attackers != 0 {
+    for attacker in Bitboard(diagonal_attackers) {
         // add the blocker locations to the mask
-        let attacker = diagonal_attackers.trailing_zeros();
-        let rank = 7 - (attacker / 8) as usize;
-        let file = 7 - (attacker % 8) as usize;
-        let attacker_attacks = magics::BISHOP_MOVES[magics::magic_index(
-            &magics::BISHOP_MAGICS[rank][file],
-            board.bishop_blocker_masks[rank][file] & our_bitboards.all,
-        )];
-
+        let attacker_attacks = bishop_attacks(attacker, our_bitboards.all);
         attackers |= diagonal_attacks & attacker_attacks;
-        diagonal_attackers ^= 1 << attacker;
     }
 
-    let mut orthogonal_attackers =
+    let orthogonal_attackers =
         orthogonal_attacks & (opponent_bitboards.rooks | opponent_bitboards.queens);
     attackers |= orthogonal_attackers;
     num_attackers += orthogonal_attackers.count_ones();
 
-    while orthogonal_attackers != 0 {
+    for attacker in Bitboard(orthogonal_attackers) {
         // add blocker locations to mask
-        let attacker = orthogonal_attackers.trailing_zeros();
-        let rank = 7 - (attacker / 8) as usize;
-        let file = 7 - (attacker % 8) as usize;
-        let attacker_attacks = magics::ROOK_MOVES[magics::magic_index(
-            &magics::ROOK_MAGICS[rank][file],
-            board.rook_blocker_masks[rank][file] & our_bitboards.all,
-        )];
-
+        let attacker_attacks = rook_attacks(attacker, our_bitboards.all);
         attackers |= orthogonal_attacks & attacker_attacks;
-        orthogonal_attackers ^= 1 << attacker;
     }
 
     let knight_attackers = knight_attacks & opponent_bitboards.knights;
@@ -339,289 +561,136 @@ pub fn count_attackers(board: &Board, color: u8, square: (u8, u8)) -> (u8, u64)
     (num_attackers as u8, attackers)
 }
 
-// use the BLACK / WHITE constants
-pub fn is_in_check(board: &Board, color: u8, king_position: (u8, u8)) -> bool {
-    let ortho_blocker_mask =
-        board.rook_blocker_masks[king_position.0 as usize][king_position.1 as usize];
-    let ortho_blockers = ortho_blocker_mask & (board.white.all | board.black.all);
-    let ortho_entry = &magics::ROOK_MAGICS[king_position.0 as usize][king_position.1 as usize];
-    let orthogonal_attacks = magics::ROOK_MOVES[magics::magic_index(ortho_entry, ortho_blockers)];
-
-    let diag_blocker_mask =
-        board.bishop_blocker_masks[king_position.0 as usize][king_position.1 as usize];
-    let diag_blockers = diag_blocker_mask & (board.white.all | board.black.all);
-    let diag_entry = &magics::BISHOP_MAGICS[king_position.0 as usize][king_position.1 as usize];
-    let diagonal_attacks = magics::BISHOP_MOVES[magics::magic_index(diag_entry, diag_blockers)];
-
-    let knight_attacks = board.knight_masks[king_position.0 as usize][king_position.1 as usize];
-    let king_attacks = board.king_masks[king_position.0 as usize][king_position.1 as usize];
-    let mut east = 0u64;
-    let mut west = 0u64;
-
-    if color == WHITE {
-        if king_position.1 > 0 {
-            west = (1 << get_bit_index!(king_position)) << 9;
-        }
-
-        if king_position.1 < 7 {
-            east = (1 << get_bit_index!(king_position)) << 7
-        }
-    } else {
-        if king_position.1 > 0 {
-            west = (1 << get_bit_index!(king_position)) >> 7
-        }
-
-        if king_position.1 < 7 {
-            east = (1 << get_bit_index!(king_position)) >> 9
-        }
-    }
-
-    let pawn_attacks =
-        if (color == WHITE && king_position.0 > 0) || (color == BLACK && king_position.0 < 7) {
-            west | east
-        } else {
-            0
-        };
+pub fn is_in_check(board: &Board, color: Color, king_position: Square) -> bool {
+    let occupancy = board.white.all | board.black.all;
+    let orthogonal_attacks = rook_attacks(king_position, occupancy);
+    let diagonal_attacks = bishop_attacks(king_position, occupancy);
+
+    let (r, f) = king_position.mailbox();
+    let knight_attacks = KNIGHT_MASKS[r][f];
+    let king_attacks = KING_MASKS[r][f];
+    let pawn_attacks = pawn_attackers_mask(color, king_position);
+
+    let opponent = board.bitboards(!color);
+    diagonal_attacks & opponent.bishops > 0
+        || diagonal_attacks & opponent.queens > 0
+        || orthogonal_attacks & opponent.rooks > 0
+        || orthogonal_attacks & opponent.queens > 0
+        || knight_attacks & opponent.knights > 0
+        || pawn_attacks & opponent.pawns > 0
+        || king_attacks & opponent.king > 0
+}
 
-    if color == WHITE {
-        diagonal_attacks & board.black.bishops > 0
-            || diagonal_attacks & board.black.queens > 0
-            || orthogonal_attacks & board.black.rooks > 0
-            || orthogonal_attacks & board.black.queens > 0
-            || knight_attacks & board.black.knights > 0
-            || pawn_attacks & board.black.pawns > 0
-            || king_attacks & board.black.king > 0
-    } else if color == BLACK {
-        diagonal_attacks & board.white.bishops > 0
-            || diagonal_attacks & board.white.queens > 0
-            || orthogonal_attacks & board.white.rooks > 0
-            || orthogonal_attacks & board.white.queens > 0
-            || knight_attacks & board.white.knights > 0
-            || pawn_attacks & board.white.pawns > 0
-            || king_attacks & board.white.king > 0
-    } else {
-        false
-    }
+// whether the pieces of the opponent of color attack the square with the given occupancy,
+// ignoring the opponent's pieces on the removed squares (the ones that were just captured)
+fn is_attacked(board: &Board, color: Color, square: Square, occupancy: u64, removed: u64) -> bool {
+    let (r, f) = square.mailbox();
+    let opponent = board.bitboards(!color);
+    let remaining = !removed;
+
+    bishop_attacks(square, occupancy) & (opponent.bishops | opponent.queens) & remaining > 0
+        || rook_attacks(square, occupancy) & (opponent.rooks | opponent.queens) & remaining > 0
+        || KNIGHT_MASKS[r][f] & opponent.knights & remaining > 0
+        || pawn_attackers_mask(color, square) & opponent.pawns & remaining > 0
+        || KING_MASKS[r][f] & opponent.king > 0
 }
 
 // https://www.chessprogramming.org/X-ray_Attacks_(Bitboards)#ModifyingOccupancy
 // blockers are the pieces to be xrayed through, square is the square of the xraying attacker
-fn orthogonal_xrays(board: &Board, mut blockers: u64, square: (u8, u8)) -> u64 {
-    let attacks = magics::ROOK_MOVES[magics::magic_index(
-        &magics::ROOK_MAGICS[square.0 as usize][square.1 as usize],
-        board.rook_blocker_masks[square.0 as usize][square.1 as usize]
-            & (board.white.all | board.black.all),
-    )];
+fn orthogonal_xrays(board: &Board, mut blockers: u64, square: Square) -> u64 {
+    let occupancy = board.white.all | board.black.all;
+    let attacks = rook_attacks(square, occupancy);
     blockers &= attacks;
-    attacks
-        ^ magics::ROOK_MOVES[magics::magic_index(
-            &magics::ROOK_MAGICS[square.0 as usize][square.1 as usize],
-            board.rook_blocker_masks[square.0 as usize][square.1 as usize]
-                & ((board.white.all | board.black.all) ^ blockers),
-        )]
+    attacks ^ rook_attacks(square, occupancy ^ blockers)
 }
 
-fn diagonal_xrays(board: &Board, mut blockers: u64, square: (u8, u8)) -> u64 {
-    let attacks = magics::BISHOP_MOVES[magics::magic_index(
-        &magics::BISHOP_MAGICS[square.0 as usize][square.1 as usize],
-        board.bishop_blocker_masks[square.0 as usize][square.1 as usize]
-            & (board.white.all | board.black.all),
-    )];
+fn diagonal_xrays(board: &Board, mut blockers: u64, square: Square) -> u64 {
+    let occupancy = board.white.all | board.black.all;
+    let attacks = bishop_attacks(square, occupancy);
     blockers &= attacks;
-    attacks
-        ^ magics::BISHOP_MOVES[magics::magic_index(
-            &magics::BISHOP_MAGICS[square.0 as usize][square.1 as usize],
-            board.bishop_blocker_masks[square.0 as usize][square.1 as usize]
-                & ((board.white.all | board.black.all) ^ blockers),
-        )]
+    attacks ^ bishop_attacks(square, occupancy ^ blockers)
 }
 
 // generates list of moves from a target bitboard, does not check for move legality
 // does not handle promotions and en passant
 fn generate_moves_from_targets(
     board: &Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
+    moves: &mut impl MoveSink,
+    current_square: Square,
     piece: Piece,
-    mut targets: u64,
+    targets: u64,
 ) {
-    while targets != 0 {
-        let target = targets.trailing_zeros();
-
+    for target in Bitboard(targets) {
         moves.push(Move::new(
             current_square,
-            (7 - target as u8 / 8, 7 - target as u8 % 8),
+            target,
             piece,
-            match board.board[7 - target as usize / 8][7 - target as usize % 8] {
-                0 => None,
-                piece => Some(piece),
-            },
+            board.piece_at(target),
             None,
             false,
         ));
-
-        targets ^= 1 << target;
     }
 }
 
+// targets already excludes our own pieces (and quiet or capturing moves, depending on what is
+// generated), the same goes for the other pieces
 fn generate_knight_moves(
     board: &Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
-    captures_only: bool,
-    allowed_targets: u64,
+    moves: &mut impl MoveSink,
+    current_square: Square,
+    targets: u64,
 ) {
-    // bitwise move generation (i hope it works)
-    let mask = board.knight_masks[current_square.0 as usize][current_square.1 as usize];
-    let target_mask = if captures_only {
-        if board.turn {
-            // only generate moves which capture opponent pieces
-            board.black.all
-        } else {
-            board.white.all
-        }
-    } else if board.turn {
-            !board.white.all
-        } else {
-            !board.black.all
-    };
+    let (r, f) = current_square.mailbox();
+    let mask = KNIGHT_MASKS[r][f];
 
     generate_moves_from_targets(
         board,
         moves,
         current_square,
-        if board.turn { WHITE } else { BLACK } | KNIGHT,
-        mask & target_mask & allowed_targets,
+        Piece::new(board.turn, PieceType::Knight),
+        mask & targets,
     )
 }
 
 fn generate_king_moves(
-    board: &mut Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
-    captures_only: bool,
+    board: &Board,
+    moves: &mut impl MoveSink,
+    current_square: Square,
+    gen: GenType,
 ) {
-    let mut mask = board.king_masks[current_square.0 as usize][current_square.1 as usize];
-    let target_mask = if captures_only {
-        if board.turn {
-            // only generate moves which capture opponent pieces
-            board.black.all
-        } else {
-            board.white.all
-        }
-    } else  if board.turn {
-            !board.white.all
-        } else {
-            !board.black.all
-    };
-
-    let our_prev_bitboards = if board.turn { board.white } else { board.black };
-
-    let our_color = if board.turn { WHITE } else { BLACK };
-
-    let our_king_position = if board.turn {
-        get_bit_index!(board.white_king_position)
-    } else {
-        get_bit_index!(board.black_king_position)
-    };
-
-    if board.turn {
-        board.white.king = 0;
-        board.white.all ^= 1 << our_king_position;
-    } else {
-        board.black.king = 0;
-        board.black.all ^= 1 << our_king_position;
-    }
+    let (r, f) = current_square.mailbox();
 
-    let mut new_mask = 0;
-    mask &= target_mask;
-    while mask != 0 {
-        let target = mask.trailing_zeros() as u8;
-
-        if !is_in_check(board, our_color, (7 - (target / 8), 7 - (target % 8))) {
-            new_mask |= 1 << target;
+    // the king is left out of the occupancy, so it cant hide from a slider behind itself
+    let occupancy = (board.white.all | board.black.all) ^ current_square.bitboard().0;
+    let mut targets = 0;
+    for target in Bitboard(KING_MASKS[r][f] & target_mask(board, gen)) {
+        if !is_attacked(board, board.turn, target, occupancy, 0) {
+            targets |= target.bitboard().0;
         }
-
-        mask ^= 1 << target;
-    }
-
-    if board.turn {
-        board.white.king = our_prev_bitboards.king;
-        board.white.all ^= 1 << our_king_position;
-    } else {
-        board.black.king = our_prev_bitboards.king;
-        board.black.all ^= 1 << our_king_position;
     }
 
     generate_moves_from_targets(
         board,
         moves,
         current_square,
-        if board.turn { WHITE } else { BLACK } | KING,
-        new_mask,
+        Piece::new(board.turn, PieceType::King),
+        targets,
     );
 
-    // castling
-    if board.turn {
-        if is_white_kingside!(board.castle_state)
-                && (board.white.all | board.black.all) & WHITE_KINGSIDE_OCCUPANCY_MASK == 0
-                && !is_in_check(board, WHITE, current_square) // disallow castling when in check
-                && !is_in_check(board, WHITE, (current_square.0, current_square.1 + 1))
-                && !is_in_check(board, WHITE, (current_square.0, current_square.1 + 2))
-        // disallow castling through check
-        {
-            moves.push(Move::new(
-                current_square,
-                (7, 6),
-                WHITE | KING,
-                None,
-                None,
-                false,
-            ));
-        }
-
-        if is_white_queenside!(board.castle_state)
-            && (board.white.all | board.black.all) & WHITE_QUEENSIDE_OCCUPANCY_MASK == 0
-            && !is_in_check(board, WHITE, current_square)
-            && !is_in_check(board, WHITE, (current_square.0, current_square.1 - 1))
-            && !is_in_check(board, WHITE, (current_square.0, current_square.1 - 2))
-        {
-            moves.push(Move::new(
-                current_square,
-                (7, 2),
-                WHITE | KING,
-                None,
-                None,
-                false,
-            ));
-        }
-    } else {
-        if is_black_kingside!(board.castle_state)
-            && (board.white.all | board.black.all) & BLACK_KINGSIDE_OCCUPANCY_MASK == 0
-            && !is_in_check(board, BLACK, current_square)
-            && !is_in_check(board, BLACK, (current_square.0, current_square.1 + 1))
-            && !is_in_check(board, BLACK, (current_square.0, current_square.1 + 2))
-        {
-            moves.push(Move::new(
-                current_square,
-                (0, 6),
-                BLACK | KING,
-                None,
-                None,
-                false,
-            ));
-        }
+    if gen != GenType::Captures {
+        generate_castling(board, moves);
+    }
+}
 
-        if is_black_queenside!(board.castle_state)
-            && (board.white.all | board.black.all) & BLACK_QUEENSIDE_OCCUPANCY_MASK == 0
-            && !is_in_check(board, BLACK, current_square)
-            && !is_in_check(board, BLACK, (current_square.0, current_square.1 - 1))
-            && !is_in_check(board, BLACK, (current_square.0, current_square.1 - 2))
-        {
+// the castling moves of the side to move, these are always legal
+fn generate_castling(board: &Board, moves: &mut impl MoveSink) {
+    for castling in CASTLING {
+        let (color, _, from, to, _, _) = castling;
+        if color == board.turn && can_castle(board, castling) {
             moves.push(Move::new(
-                current_square,
-                (0, 2),
-                BLACK | KING,
+                from,
+                to,
+                Piece::new(color, PieceType::King),
                 None,
                 None,
                 false,
@@ -630,305 +699,222 @@ fn generate_king_moves(
     }
 }
 
+// castling is not allowed out of check, through check or into check
+fn can_castle(board: &Board, (color, right, from, to, empty, passed): Castling) -> bool {
+    // in atomic chess a square next to the enemy king cant be attacked, a capture there would
+    // explode that king
+    let attacked = |square: Square| {
+        let (r, f) = square.mailbox();
+        is_in_check(board, color, square)
+            && (board.variant != Variant::Atomic
+                || KING_MASKS[r][f] & board.bitboards(!color).king == 0)
+    };
+
+    board.variant != Variant::Antichess
+        && board.castle_state & right != 0
+        && (board.white.all | board.black.all) & empty == 0
+        && !attacked(from)
+        && !attacked(passed)
+        && !attacked(to)
+}
+
 // not promotions, only generates moves for pawns in nonpinned
 fn generate_pawn_pushes(
     board: &Board,
-    moves: &mut Vec<Move>,
+    moves: &mut impl MoveSink,
     allowed_targets: u64,
     nonpinned: u64,
 ) {
-    if board.turn {
-        // handle promotions elsewhere
-        for i in 8u8..48 {
-            if (1 << i) & nonpinned == 0 {
-                continue;
-            }
-
-            let mut targets: u64 = 0;
-            let current_pawn: u64 = board.white.pawns & (1 << i);
+    let empty = !(board.white.all | board.black.all);
 
-            if current_pawn == 0 {
-                continue;
-            };
+    if board.turn.is_white() {
+        // handle promotions elsewhere
+        for from in Bitboard(board.white.pawns & nonpinned & WHITE_PAWN_RANKS) {
+            let current_pawn = from.bitboard().0;
 
-            targets |= (current_pawn << 8) & !board.black.all & !board.white.all;
+            let mut targets = (current_pawn << 8) & empty;
 
-            if i < 16 && targets > 0 {
-                targets |= (current_pawn << 16) & !board.black.all & !board.white.all;
+            let start = from.rank() == Rank::Second
+                || (from.rank() == Rank::First && board.variant == Variant::Horde);
+            if start && targets > 0 {
+                targets |= (current_pawn << 16) & empty;
             }
 
             generate_moves_from_targets(
                 board,
                 moves,
-                (7 - i / 8, 7 - i % 8),
-                WHITE | PAWN,
+                from,
+                Piece::WhitePawn,
                 targets & allowed_targets,
-            )
+            );
         }
     } else {
         // handle promotions elsewhere
-        for i in 16u8..56 {
-            if (1 << i) & nonpinned == 0 {
-                continue;
-            }
+        for from in Bitboard(board.black.pawns & nonpinned & BLACK_PAWN_RANKS) {
+            let current_pawn = from.bitboard().0;
 
-            let mut targets: u64 = 0;
-            let current_pawn: u64 = board.black.pawns & (1 << i);
-
-            if current_pawn == 0 {
-                continue;
-            };
+            let mut targets = (current_pawn >> 8) & empty;
 
-            targets |= (current_pawn >> 8) & !board.white.all & !board.black.all;
-
-            if i >= 48 && targets > 0 {
-                targets |= (current_pawn >> 16) & !board.white.all & !board.black.all;
+            if from.rank() == Rank::Seventh && targets > 0 {
+                targets |= (current_pawn >> 16) & empty;
             }
 
             generate_moves_from_targets(
                 board,
                 moves,
-                (7 - i / 8, 7 - i % 8),
-                BLACK | PAWN,
+                from,
+                Piece::BlackPawn,
                 targets & allowed_targets,
-            )
+            );
         }
     }
 }
 
+// with legal set, en passant captures that leave the king in check are left out
 fn generate_pawn_captures(
-    board: &mut Board,
-    moves: &mut Vec<Move>,
+    board: &Board,
+    moves: &mut impl MoveSink,
     allowed_targets: u64,
     nonpinned: u64,
+    legal: bool,
 ) {
-    if board.turn {
-        for i in 8u8..48 {
-            if (1 << i) & nonpinned == 0 {
-                continue;
-            }
+    if board.turn.is_white() {
+        for from in Bitboard(board.white.pawns & nonpinned & WHITE_PAWN_RANKS) {
+            let current_pawn = from.bitboard().0;
 
-            let current_pawn: u64 = board.white.pawns & (1 << i);
+            // capturing to the right and to the left
+            let attacks = ((current_pawn & NOT_A_FILE) << 7) | ((current_pawn & NOT_H_FILE) << 9);
 
-            if current_pawn == 0 {
-                continue;
-            }
-
-            let mut targets = 0;
-            if let Some(square) = board.en_passant_square { 
-                if 7 - i % 8 > 0 && (6 - i / 8, 6 - i % 8) == square
-                    || 7 - i % 8 < 7 && (6 - i / 8, 8 - i % 8) == square
-                    // remember the parenthesis around the above condition 
+            if let Some(square) = board.en_passant_square {
+                if attacks & square.bitboard().0 != 0
+                    // remember the parenthesis around the above condition
                     //if this condition is uncommented
 
                     // commented out this condition because it leads to issues
                     // with double pawn push checks (8/8/3p4/1Pp4r/1K3pk1/8/4P1P1/1R6 w - c6 0 3),
                     // en passant is rare enough where it should be fine
-                    // && (1 << get_bit_index!(square)) & allowed_targets > 0
+                    // && square.bitboard().0 & allowed_targets > 0
                 {
-                    // make sure that there are no sneaky checks making the capture illegal
-                    // en passant should be rare enough that this doesnt impact performance
+                    // make sure that there are no sneaky checks making the capture illegal, the
+                    // pin detection does not see the two pawns leaving the rank at once
 
                     let m = Move::new(
-                        (7 - i / 8, 7 - i % 8),
+                        from,
                         square,
-                        WHITE | PAWN,
-                        Some(BLACK | PAWN),
+                        Piece::WhitePawn,
+                        Some(Piece::BlackPawn),
                         None,
                         true,
                     );
 
-                    let undo = board.make_move(m);
-
-                    if !is_in_check(board, WHITE, board.white_king_position) {
+                    if !legal || board.is_legal(m) {
                         moves.push(m);
                     }
-
-                    undo(board);
                 }
             }
 
-            // capturing to the right
-            targets |= ((current_pawn & NOT_A_FILE) << 7) & !board.white.all & board.black.all;
-            // capturing to the left
-            targets |= ((current_pawn & NOT_H_FILE) << 9) & !board.white.all & board.black.all;
+            let targets = attacks & !board.white.all & board.black.all;
 
             generate_moves_from_targets(
                 board,
                 moves,
-                (7 - i / 8, 7 - i % 8),
-                WHITE | PAWN,
+                from,
+                Piece::WhitePawn,
                 targets & allowed_targets,
             );
         }
     } else {
-        for i in 16u8..56 {
-            if (1 << i) & nonpinned == 0 {
-                continue;
-            }
+        for from in Bitboard(board.black.pawns & nonpinned & BLACK_PAWN_RANKS) {
+            let current_pawn = from.bitboard().0;
 
-            let current_pawn: u64 = board.black.pawns & (1 << i);
-
-            if current_pawn == 0 {
-                continue;
-            }
+            // capturing to the right and to the left
+            let attacks = ((current_pawn & NOT_A_FILE) >> 9) | ((current_pawn & NOT_H_FILE) >> 7);
 
-            let mut targets = 0;
             if let Some(square) = board.en_passant_square {
-                if 7 - i % 8 > 0 && (8 - i / 8, 6 - i % 8) == square
-                    || 7 - i % 8 < 7 && (8 - i / 8, 8 - i % 8) == square
+                if attacks & square.bitboard().0 != 0
                     // read the comment on the corresponding white version
-                    //&& (1 << get_bit_index!(square)) & allowed_targets > 0
+                    //&& square.bitboard().0 & allowed_targets > 0
                 {
                     let m = Move::new(
-                        (7 - i / 8, 7 - i % 8),
+                        from,
                         square,
-                        BLACK | PAWN,
-                        Some(WHITE | PAWN),
+                        Piece::BlackPawn,
+                        Some(Piece::WhitePawn),
                         None,
                         true,
                     );
 
-                    let undo = board.make_move(m);
-
-                    if !is_in_check(board, BLACK, board.black_king_position) {
+                    if !legal || board.is_legal(m) {
                         moves.push(m);
                     }
-
-                    undo(board);
                 }
             };
 
-            // capturing to the right
-            targets |= ((current_pawn & NOT_A_FILE) >> 9) & !board.black.all & board.white.all;
-            // capturing to the left
-            targets |= ((current_pawn & NOT_H_FILE) >> 7) & !board.black.all & board.white.all;
+            let targets = attacks & !board.black.all & board.white.all;
 
             generate_moves_from_targets(
                 board,
                 moves,
-                (7 - i / 8, 7 - i % 8),
-                BLACK | PAWN,
+                from,
+                Piece::BlackPawn,
                 targets & allowed_targets,
             );
         }
     }
 }
 
+// pushes to the last rank are quiet moves, captures to the last rank are captures
 fn generate_pawn_promotions(
     board: &Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
+    moves: &mut impl MoveSink,
+    current_square: Square,
     allowed_targets: u64,
+    gen: GenType,
 ) {
-    if board.turn && current_square.0 == 1 {
-        if board.board[current_square.0 as usize - 1][current_square.1 as usize] == 0
-            && (1 << get_bit_index!(current_square.0 - 1, current_square.1)) & allowed_targets > 0
-        {
-            for piece in [KNIGHT, BISHOP, ROOK, QUEEN] {
-                moves.push(Move::new(
-                    current_square,
-                    (current_square.0 - 1, current_square.1),
-                    WHITE | PAWN,
-                    None,
-                    Some(WHITE | piece),
-                    false,
-                ));
-            }
-        }
+    let (color, forward) = if board.turn.is_white() {
+        (Color::White, 1)
+    } else {
+        (Color::Black, -1)
+    };
 
-        // capture to the left
-        if current_square.1 > 0
-            && get_piece_color!(
-                board.board[current_square.0 as usize - 1][current_square.1 as usize - 1]
-            ) == BLACK
-            && (1 << get_bit_index!(current_square.0 - 1, current_square.1 - 1)) & allowed_targets
-                > 0
-        {
-            for piece in [KNIGHT, BISHOP, ROOK, QUEEN] {
-                moves.push(Move::new(
-                    current_square,
-                    (current_square.0 - 1, current_square.1 - 1),
-                    WHITE | PAWN,
-                    Some(board.board[current_square.0 as usize - 1][current_square.1 as usize - 1]),
-                    Some(WHITE | piece),
-                    false,
-                ));
-            }
-        }
+    let promotions: &[PieceType] = if board.variant == Variant::Antichess {
+        &ANTICHESS_PROMOTIONS
+    } else {
+        &PieceType::PROMOTIONS
+    };
 
-        // capture to the right
-        if current_square.1 < 7
-            && get_piece_color!(
-                board.board[current_square.0 as usize - 1][current_square.1 as usize + 1]
-            ) == BLACK
-            && (1 << get_bit_index!(current_square.0 - 1, current_square.1 + 1)) & allowed_targets
-                > 0
-        {
-            for piece in [KNIGHT, BISHOP, ROOK, QUEEN] {
-                moves.push(Move::new(
-                    current_square,
-                    (current_square.0 - 1, current_square.1 + 1),
-                    WHITE | PAWN,
-                    Some(board.board[current_square.0 as usize - 1][current_square.1 as usize + 1]),
-                    Some(WHITE | piece),
-                    false,
-                ));
-            }
-        }
-    } else if !board.turn && current_square.0 == 6 {
-        if board.board[current_square.0 as usize + 1][current_square.1 as usize] == 0
-            && (1 << get_bit_index!(current_square.0 + 1, current_square.1)) & allowed_targets > 0
-        {
-            for piece in [KNIGHT, BISHOP, ROOK, QUEEN] {
-                moves.push(Move::new(
-                    current_square,
-                    (current_square.0 + 1, current_square.1),
-                    BLACK | PAWN,
-                    None,
-                    Some(BLACK | piece),
-                    false,
-                ));
-            }
+    let mut promote = |to: Square, capture_piece: Option<Piece>| {
+        if to.bitboard().0 & allowed_targets == 0 {
+            return;
         }
 
-        // capture to the left
-        if current_square.1 > 0
-            && get_piece_color!(
-                board.board[current_square.0 as usize + 1][current_square.1 as usize - 1]
-            ) == WHITE
-            && (1 << get_bit_index!(current_square.0 + 1, current_square.1 - 1)) & allowed_targets
-                > 0
-        {
-            for piece in [KNIGHT, BISHOP, ROOK, QUEEN] {
-                moves.push(Move::new(
-                    current_square,
-                    (current_square.0 + 1, current_square.1 - 1),
-                    BLACK | PAWN,
-                    Some(board.board[current_square.0 as usize + 1][current_square.1 as usize - 1]),
-                    Some(BLACK | piece),
-                    false,
-                ));
-            }
+        for &piece_type in promotions {
+            moves.push(Move::new(
+                current_square,
+                to,
+                Piece::new(color, PieceType::Pawn),
+                capture_piece,
+                Some(Piece::new(color, piece_type)),
+                false,
+            ));
         }
+    };
+
+    // promoting pawns are one rank away from the last rank, so the square in front is on the board
+    let push = current_square.offset(0, forward).unwrap();
+    if gen != GenType::Captures && board.piece_at(push).is_none() {
+        promote(push, None);
+    }
 
-        // capture to the right
-        if current_square.1 < 7
-            && get_piece_color!(
-                board.board[current_square.0 as usize + 1][current_square.1 as usize + 1]
-            ) == WHITE
-            && (1 << get_bit_index!(current_square.0 + 1, current_square.1 + 1)) & allowed_targets
-                > 0
-        {
-            for piece in [KNIGHT, BISHOP, ROOK, QUEEN] {
-                moves.push(Move::new(
-                    current_square,
-                    (current_square.0 + 1, current_square.1 + 1),
-                    BLACK | PAWN,
-                    Some(board.board[current_square.0 as usize + 1][current_square.1 as usize + 1]),
-                    Some(BLACK | piece),
-                    false,
-                ));
+    if gen == GenType::Quiets {
+        return;
+    }
+
+    // capture to the left, then to the right
+    for files in [-1, 1] {
+        if let Some(to) = current_square.offset(files, forward) {
+            if let Some(piece) = board.piece_at(to).filter(|piece| piece.color() != color) {
+                promote(to, Some(piece));
             }
         }
     }
@@ -936,113 +922,53 @@ fn generate_pawn_promotions(
 
 fn generate_bishop_moves(
     board: &Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
-    captures_only: bool,
-    allowed_targets: u64,
+    moves: &mut impl MoveSink,
+    current_square: Square,
+    targets: u64,
 ) {
-    let blocker_mask =
-        board.bishop_blocker_masks[current_square.0 as usize][current_square.1 as usize];
-    let blockers = blocker_mask & (board.white.all | board.black.all);
-    let entry = &magics::BISHOP_MAGICS[current_square.0 as usize][current_square.1 as usize];
-    let legal_moves = magics::BISHOP_MOVES[magics::magic_index(entry, blockers)];
-
-    let target_mask = if captures_only {
-        if board.turn {
-            // only generate moves which capture opponent pieces
-            board.black.all
-        } else {
-            board.white.all
-        }
-    } else if board.turn {
-            !board.white.all
-        } else {
-            !board.black.all
-        
-    };
+    let legal_moves = bishop_attacks(current_square, board.white.all | board.black.all);
 
     generate_moves_from_targets(
         board,
         moves,
         current_square,
-        if board.turn { WHITE } else { BLACK } | BISHOP,
-        legal_moves & target_mask & allowed_targets,
+        Piece::new(board.turn, PieceType::Bishop),
+        legal_moves & targets,
     )
 }
 
 fn generate_rook_moves(
     board: &Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
-    captures_only: bool,
-    allowed_targets: u64,
+    moves: &mut impl MoveSink,
+    current_square: Square,
+    targets: u64,
 ) {
-    let blocker_mask =
-        board.rook_blocker_masks[current_square.0 as usize][current_square.1 as usize];
-    let blockers = blocker_mask & (board.white.all | board.black.all);
-    let entry = &magics::ROOK_MAGICS[current_square.0 as usize][current_square.1 as usize];
-    let legal_moves = magics::ROOK_MOVES[magics::magic_index(entry, blockers)];
-
-    let target_mask = if captures_only {
-        if board.turn {
-            // only generate moves which capture opponent pieces
-            board.black.all
-        } else {
-            board.white.all
-        }
-    } else if board.turn {
-            !board.white.all
-        } else {
-            !board.black.all
-    };
+    let legal_moves = rook_attacks(current_square, board.white.all | board.black.all);
 
     generate_moves_from_targets(
         board,
         moves,
         current_square,
-        if board.turn { WHITE } else { BLACK } | ROOK,
-        legal_moves & target_mask & allowed_targets,
+        Piece::new(board.turn, PieceType::Rook),
+        legal_moves & targets,
     )
 }
 
 fn generate_queen_moves(
     board: &Board,
-    moves: &mut Vec<Move>,
-    current_square: (u8, u8),
-    captures_only: bool,
-    allowed_targets: u64,
+    moves: &mut impl MoveSink,
+    current_square: Square,
+    targets: u64,
 ) {
-    let ortho_blocker_mask =
-        board.rook_blocker_masks[current_square.0 as usize][current_square.1 as usize];
-    let ortho_blockers = ortho_blocker_mask & (board.white.all | board.black.all);
-    let ortho_entry = &magics::ROOK_MAGICS[current_square.0 as usize][current_square.1 as usize];
-    let ortho_legal_moves = magics::ROOK_MOVES[magics::magic_index(ortho_entry, ortho_blockers)];
-
-    let diag_blocker_mask =
-        board.bishop_blocker_masks[current_square.0 as usize][current_square.1 as usize];
-    let diag_blockers = diag_blocker_mask & (board.white.all | board.black.all);
-    let diag_entry = &magics::BISHOP_MAGICS[current_square.0 as usize][current_square.1 as usize];
-    let diag_legal_moves = magics::BISHOP_MOVES[magics::magic_index(diag_entry, diag_blockers)];
-
-    let target_mask = if captures_only {
-        if board.turn {
-            // only generate moves which capture opponent pieces
-            board.black.all
-        } else {
-            board.white.all
-        }
-    } else if board.turn {
-            !board.white.all
-        } else {
-            !board.black.all
-        
-    };
+    let occupancy = board.white.all | board.black.all;
+    let legal_moves =
+        rook_attacks(current_square, occupancy) | bishop_attacks(current_square, occupancy);
 
     generate_moves_from_targets(
         board,
         moves,
         current_square,
-        if board.turn { WHITE } else { BLACK } | QUEEN,
-        (ortho_legal_moves | diag_legal_moves) & target_mask & allowed_targets,
+        Piece::new(board.turn, PieceType::Queen),
+        legal_moves & targets,
     );
 }
@@ -0,0 +1,95 @@
+// fixed size list of moves on the stack, so generating moves doesnt allocate
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use game::{Move, Piece, Square};
+
+// no standard position has more than 218 legal moves, crazyhouse drops can add a few hundred
+pub const MAX_MOVES: usize = 512;
+
+const EMPTY: Move = Move {
+    from: Square::A8,
+    to: Square::A8,
+    piece: Piece::WhitePawn,
+    capture_piece: None,
+    promotion_piece: None,
+    en_passant: false,
+    drop: false,
+};
+
+#[derive(Clone, Copy)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> MoveList {
+        MoveList {
+            moves: [EMPTY; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, m: Move) {
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+/// where the move generator writes the moves it finds
+pub trait MoveSink {
+    fn push(&mut self, m: Move);
+
+    /// the generator stops once the sink has every move it needs
+    fn is_full(&self) -> bool {
+        false
+    }
+}
+
+impl MoveSink for MoveList {
+    fn push(&mut self, m: Move) {
+        MoveList::push(self, m);
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
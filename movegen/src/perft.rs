@@ -1,35 +1,373 @@
-use super::Board;
+// counts the leaf nodes of the move tree, to check the move generator against known results
+// https://www.chessprogramming.org/Perft
+use std::thread;
 
-pub fn perft(board: &mut Board, depth: u8, depth_from_root: u8) -> u128 {
-    let legal_moves = super::generate_legal_moves(board, false);
-    if depth == 1 {
-        for m in &legal_moves {
-            if depth_from_root == 0 {
-                println!("{}: 1", m.to_uci());
-            }
+use super::{
+    generate_legal_moves, generate_moves, generate_pseudo_legal_moves, Board, GenType, Legality,
+    Move, MoveList, Variant,
+};
+
+// standard positions with their known results for depth 1, 2, ...
+// https://www.chessprogramming.org/Perft_Results
+pub const PERFT_SUITE: [(&str, &[u64]); 20] = [
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8902, 197281, 4865609, 119060324],
+    ),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2039, 97862, 4085603, 193690690],
+    ),
+    (
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2812, 43238, 674624, 11030083],
+    ),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[6, 264, 9467, 422333, 15833292],
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        &[44, 1486, 62379, 2103487, 89941194],
+    ),
+    (
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        &[46, 2079, 89890, 3894594, 164075551],
+    ),
+    // en passant, castling and promotion edge cases
+    (
+        "3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1",
+        &[18, 92, 1670, 10138, 185429, 1134888],
+    ),
+    (
+        "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+        &[13, 102, 1266, 10276, 135655, 1015133],
+    ),
+    (
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        &[15, 126, 1928, 13931, 206379, 1440467],
+    ),
+    (
+        "5k2/8/8/8/8/8/8/4K2R w K - 0 1",
+        &[15, 66, 1198, 6399, 120330, 661072],
+    ),
+    (
+        "3k4/8/8/8/8/8/8/R3K3 w Q - 0 1",
+        &[16, 71, 1286, 7418, 141077, 803711],
+    ),
+    (
+        "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1",
+        &[26, 1141, 27826, 1274206],
+    ),
+    (
+        "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+        &[44, 1494, 50509, 1720476],
+    ),
+    (
+        "2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1",
+        &[11, 133, 1442, 19174, 266199, 3821001],
+    ),
+    (
+        "8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1",
+        &[29, 165, 5160, 31961, 1004658],
+    ),
+    (
+        "4k3/1P6/8/8/8/8/K7/8 w - - 0 1",
+        &[9, 40, 472, 2661, 38983, 217342],
+    ),
+    (
+        "8/P1k5/K7/8/8/8/8/8 w - - 0 1",
+        &[6, 27, 273, 1329, 18135, 92683],
+    ),
+    ("K1k5/8/P7/8/8/8/8/8 w - - 0 1", &[2, 6, 13, 63, 382, 2217]),
+    (
+        "8/k1P5/8/1K6/8/8/8/8 w - - 0 1",
+        &[10, 25, 268, 926, 10857, 43261, 567584],
+    ),
+    ("8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", &[37, 183, 6559, 23527]),
+];
+
+// variant positions with their results for depth 1, 2, ... the small ones are counted by hand
+pub const VARIANT_PERFT_SUITE: [(Variant, &str, &[u64]); 13] = [
+    // a king can't reach the centre in four plies, so these are the standard results
+    (
+        Variant::KingOfTheHill,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8902, 197281],
+    ),
+    // the game ends after the king steps to d4 or e4
+    (
+        Variant::KingOfTheHill,
+        "4k3/8/8/8/8/4K3/8/8 w - - 0 1",
+        &[8, 30],
+    ),
+    // one more check wins for either side
+    (
+        Variant::ThreeCheck,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 1+1 0 1",
+        &[48, 2039, 97848, 4081798],
+    ),
+    (
+        Variant::Antichess,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+        &[20, 400, 8067, 153299, 2732672],
+    ),
+    // the pawns have to take each other, the side that is left without pieces wins
+    (
+        Variant::Antichess,
+        "8/1p6/8/8/8/8/P7/8 w - - 0 1",
+        &[2, 4, 4, 3, 1, 0],
+    ),
+    // the first drops happen at depth 5
+    (
+        Variant::Crazyhouse,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+        &[20, 400, 8902, 197281, 4888832],
+    ),
+    // pawn drops on the 2nd to 7th rank, some of them check the king
+    (
+        Variant::Crazyhouse,
+        "4k3/8/8/8/8/8/8/4K3[P] w - - 0 1",
+        &[53, 255],
+    ),
+    // more moves than a standard position can have
+    (
+        Variant::Crazyhouse,
+        "2k5/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1",
+        &[301],
+    ),
+    // captures that explode a king only count from depth 4
+    (
+        Variant::Atomic,
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8902, 197326, 4864979],
+    ),
+    (
+        Variant::Atomic,
+        "rn2kb1r/1pp1p2p/p2q1pp1/3P4/2P3b1/4PN2/PP3PPP/R2QKB1R b KQkq - 0 1",
+        &[40, 1238, 45237, 1434825],
+    ),
+    (
+        Variant::Atomic,
+        "rn1qkb1r/p5pp/2p5/3p4/N3P3/5P2/PPP4P/R1BQK3 w Qkq - 0 1",
+        &[28, 833, 23353, 714499],
+    ),
+    (
+        Variant::Horde,
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1",
+        &[8, 128, 1274, 23310, 265223],
+    ),
+    // a pawn on the first rank can push one or two squares
+    (Variant::Horde, "4k3/8/8/8/8/8/8/P7 w - - 0 1", &[2, 10]),
+];
+
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    hash: u64,
+    depth: u8,
+    nodes: u64,
+}
+
+/// transposition table for perft, positions reached through different move orders are only
+/// counted once
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+impl PerftTable {
+    pub fn new(size_mb: usize) -> PerftTable {
+        let len = (size_mb * 1024 * 1024 / std::mem::size_of::<PerftEntry>()).max(1);
+
+        PerftTable {
+            entries: vec![PerftEntry::default(); len],
+        }
+    }
+
+    fn index(&self, hash: u64, depth: u8) -> usize {
+        ((hash ^ depth as u64) % self.entries.len() as u64) as usize
+    }
+
+    fn probe(&self, hash: u64, depth: u8) -> Option<u64> {
+        let entry = self.entries[self.index(hash, depth)];
+        (entry.hash == hash && entry.depth == depth).then_some(entry.nodes)
+    }
+
+    // always replaces, the deepest entries are not worth keeping at these depths
+    fn store(&mut self, hash: u64, depth: u8, nodes: u64) {
+        let index = self.index(hash, depth);
+        self.entries[index] = PerftEntry { hash, depth, nodes };
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PerftOptions {
+    pub threads: usize,
+    // size of the transposition table of each thread in MB, 0 for no table
+    pub hash_size: usize,
+    // count with the pseudo-legal generator and the legality test, without a transposition table
+    pub pseudo_legal: bool,
+}
+
+impl Default for PerftOptions {
+    fn default() -> Self {
+        PerftOptions {
+            threads: 1,
+            hash_size: 0,
+            pseudo_legal: false,
         }
-        return legal_moves.len() as u128;
     }
+}
 
-    let mut count: u128 = 0;
+/// number of leaf nodes at the given depth
+pub fn perft(board: &mut Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
 
-    for m in legal_moves {
-        // println!("{}", board);
+    let mut moves = MoveList::new();
+    generate_moves(board, &mut moves, GenType::All);
+
+    // bulk counting, the moves dont have to be made to count them
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for &m in &moves {
         let undo = board.make_move(m);
-        // println!("{}", board);
+        nodes += perft(board, depth - 1);
+        board.unmake_move(m, undo);
+    }
+
+    nodes
+}
 
-        let c = perft(board, depth - 1, depth_from_root + 1);
-        if depth_from_root == 0 {
-            println!("{}: {}", m.to_uci(), c);
+/// perft with the pseudo-legal move generator, every move is checked with `is_legal` before it
+/// is counted. the counts are the same as the ones of `perft`
+pub fn perft_pseudo_legal(board: &mut Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut moves = MoveList::new();
+    generate_pseudo_legal_moves(board, &mut moves, GenType::All);
+
+    if depth == 1 {
+        return moves.iter().filter(|&&m| board.is_legal(m)).count() as u64;
+    }
+
+    let mut nodes = 0;
+    for &m in &moves {
+        if !board.is_legal(m) {
+            continue;
         }
-        count += c;
 
-        undo(board);
+        let undo = board.make_move(m);
+        nodes += perft_pseudo_legal(board, depth - 1);
+        board.unmake_move(m, undo);
+    }
+
+    nodes
+}
+
+/// perft with a transposition table
+pub fn perft_hashed(board: &mut Board, depth: u8, table: &mut PerftTable) -> u64 {
+    if depth <= 1 {
+        return perft(board, depth);
+    }
+
+    if let Some(nodes) = table.probe(board.hash, depth) {
+        return nodes;
+    }
+
+    let mut moves = MoveList::new();
+    generate_moves(board, &mut moves, GenType::All);
+
+    let mut nodes = 0;
+    for &m in &moves {
+        let undo = board.make_move(m);
+        nodes += perft_hashed(board, depth - 1, table);
+        board.unmake_move(m, undo);
+    }
+
+    table.store(board.hash, depth, nodes);
+
+    nodes
+}
+
+/// the number of leaf nodes after every root move, the root moves are split between threads
+pub fn divide(board: &Board, depth: u8, options: PerftOptions) -> Vec<(Move, u64)> {
+    let mut board = *board;
+    let moves = generate_legal_moves(&mut board, false);
+    if depth == 0 || moves.is_empty() {
+        return Vec::new();
     }
 
-    if depth_from_root == 0 {
-        println!("Nodes Searched: {}", count);
+    let chunk_size = moves.len().div_ceil(options.threads.max(1));
+
+    thread::scope(|s| {
+        let handles: Vec<_> = moves
+            .chunks(chunk_size)
+            .map(|chunk| {
+                s.spawn(move || {
+                    let mut board = board;
+                    let mut table =
+                        (options.hash_size > 0).then(|| PerftTable::new(options.hash_size));
+
+                    chunk
+                        .iter()
+                        .map(|&m| {
+                            let undo = board.make_move(m);
+                            let nodes = match &mut table {
+                                _ if options.pseudo_legal => {
+                                    perft_pseudo_legal(&mut board, depth - 1)
+                                }
+                                Some(table) => perft_hashed(&mut board, depth - 1, table),
+                                None => perft(&mut board, depth - 1),
+                            };
+                            board.unmake_move(m, undo);
+
+                            (m, nodes)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
+
+/// the first root move where the counts differ from a reference divide, the reference has one
+/// `<move>: <nodes>` line per move (the output of `divide` or of other engines)
+///
+/// returns the move and both counts, none for a move that is missing from either side
+pub fn first_difference(
+    divide: &[(Move, u64)],
+    reference: &str,
+) -> Option<(String, Option<u64>, Option<u64>)> {
+    let reference: Vec<(String, u64)> = reference
+        .lines()
+        .filter_map(|line| {
+            let (m, nodes) = line.split_once(':')?;
+            Some((m.trim().to_owned(), nodes.trim().parse().ok()?))
+        })
+        .collect();
+
+    for (m, nodes) in divide {
+        let uci = m.to_uci();
+        match reference.iter().find(|(r, _)| *r == uci) {
+            Some((_, expected)) if expected == nodes => {}
+            Some((_, expected)) => return Some((uci, Some(*nodes), Some(*expected))),
+            None => return Some((uci, Some(*nodes), None)),
+        }
     }
 
-    count
+    reference
+        .iter()
+        .find(|(r, _)| !divide.iter().any(|(m, _)| m.to_uci() == *r))
+        .map(|(r, expected)| (r.clone(), None, Some(*expected)))
 }
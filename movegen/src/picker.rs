@@ -0,0 +1,236 @@
+// staged move generation for search, the moves most likely to cause a cutoff are tried first
+// and the rest are only generated when they are needed
+// https://www.chessprogramming.org/Move_Generation#Staged_move_generation
+use game::{move16::Move16, Board, Move, PieceType};
+
+use crate::{generate_moves, movelist::MAX_MOVES, Attacks, GenType, Legality, MoveList};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    HashMove,
+    // captures that dont lose material, most valuable victim first
+    GoodCaptures,
+    Killers,
+    Quiets,
+    // captures of a defended piece with a more valuable piece
+    BadCaptures,
+    Done,
+}
+
+// good captures are always scored above bad ones
+const BAD_CAPTURE: i32 = -1000;
+
+pub struct MovePicker {
+    stage: Stage,
+    // only captures, for quiescence search
+    captures_only: bool,
+    hash_move: Option<Move>,
+    killers: [Option<Move16>; 2],
+
+    captures: MoveList,
+    capture_scores: [i32; MAX_MOVES],
+    captures_generated: bool,
+    quiets: MoveList,
+    quiets_generated: bool,
+
+    // next move to look at in each list
+    capture_index: usize,
+    killer_index: usize,
+    quiet_index: usize,
+}
+
+// only used to order captures
+fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight | PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Queen => 9,
+        PieceType::King => 100,
+    }
+}
+
+impl MovePicker {
+    pub fn new(hash_move: Option<Move>, killers: [Option<Move16>; 2]) -> MovePicker {
+        MovePicker {
+            stage: Stage::HashMove,
+            captures_only: false,
+            hash_move,
+            killers,
+            captures: MoveList::new(),
+            capture_scores: [0; MAX_MOVES],
+            captures_generated: false,
+            quiets: MoveList::new(),
+            quiets_generated: false,
+            capture_index: 0,
+            killer_index: 0,
+            quiet_index: 0,
+        }
+    }
+
+    /// good captures followed by bad captures
+    pub fn captures() -> MovePicker {
+        MovePicker {
+            captures_only: true,
+            ..MovePicker::new(None, [None; 2])
+        }
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// whether there are any legal captures. they are generated here already, so the search can
+    /// ask before it takes the first move
+    pub fn has_captures(&mut self, board: &mut Board) -> bool {
+        self.generate_captures(board);
+        !self.captures.is_empty()
+    }
+
+    /// the next move to search, none once every legal move was returned
+    ///
+    /// the board has to be in the same position every time this is called
+    pub fn next(&mut self, board: &mut Board) -> Option<Move> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = Stage::GoodCaptures;
+
+                    // the hash move may come from a different position with the same hash, so it
+                    // is only returned if it is legal here
+                    if let Some(m) = self.hash_move {
+                        if (m.capture_piece.is_some() || !self.captures_only)
+                            && board.is_pseudo_legal(m)
+                            && board.is_legal(m)
+                        {
+                            return Some(m);
+                        }
+                    }
+                }
+                Stage::GoodCaptures => {
+                    self.generate_captures(board);
+                    match self.pick_capture(false) {
+                        Some(m) => return Some(m),
+                        None => {
+                            self.stage = if self.captures_only {
+                                Stage::BadCaptures
+                            } else {
+                                Stage::Killers
+                            };
+                        }
+                    }
+                }
+                Stage::Killers => {
+                    // killers are quiet moves that caused a cutoff at the same ply somewhere else
+                    // in the tree, they are only returned if they are legal here
+                    while self.killer_index < self.killers.len() {
+                        let killer = self.killers[self.killer_index];
+                        self.killer_index += 1;
+
+                        if let Some(killer) = killer {
+                            if self.hash_move.map(Move16::from) == Some(killer) {
+                                continue;
+                            }
+
+                            if let Some(m) = killer.to_move(board).filter(|&m| {
+                                m.capture_piece.is_none()
+                                    && board.is_pseudo_legal(m)
+                                    && board.is_legal(m)
+                            }) {
+                                return Some(m);
+                            }
+                        }
+                    }
+
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => {
+                    self.generate_quiets(board);
+                    while self.quiet_index < self.quiets.len() {
+                        let m = self.quiets[self.quiet_index];
+                        self.quiet_index += 1;
+
+                        if Some(m) != self.hash_move && !self.killers.contains(&Some(m.into())) {
+                            return Some(m);
+                        }
+                    }
+
+                    self.stage = Stage::BadCaptures;
+                }
+                Stage::BadCaptures => match self.pick_capture(true) {
+                    Some(m) => return Some(m),
+                    None => self.stage = Stage::Done,
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    fn generate_captures(&mut self, board: &mut Board) {
+        if self.captures_generated {
+            return;
+        }
+        self.captures_generated = true;
+
+        generate_moves(board, &mut self.captures, GenType::Captures);
+
+        let occupancy = board.white.all | board.black.all;
+        let defenders = board.bitboards(!board.turn).all;
+
+        for (i, m) in self.captures.iter().enumerate() {
+            let victim = m
+                .capture_piece
+                .map_or(0, |piece| piece_value(piece.piece_type()));
+            let attacker = piece_value(m.piece.piece_type());
+
+            // most valuable victim, least valuable attacker
+            let mut score = 10 * victim - attacker;
+
+            // losing the capturing piece for a less valuable one, if the square is defended
+            if m.promotion_piece.is_none()
+                && attacker > victim
+                && board.attackers_to(m.to, occupancy) & defenders != 0
+            {
+                score += BAD_CAPTURE;
+            }
+
+            self.capture_scores[i] = score;
+        }
+    }
+
+    fn generate_quiets(&mut self, board: &mut Board) {
+        if self.quiets_generated {
+            return;
+        }
+        self.quiets_generated = true;
+
+        generate_moves(board, &mut self.quiets, GenType::Quiets);
+    }
+
+    // selection sort, the captures are only ordered as far as they are searched
+    fn pick_capture(&mut self, include_bad: bool) -> Option<Move> {
+        while self.capture_index < self.captures.len() {
+            let mut best = self.capture_index;
+            for i in self.capture_index + 1..self.captures.len() {
+                if self.capture_scores[i] > self.capture_scores[best] {
+                    best = i;
+                }
+            }
+
+            if !include_bad && self.capture_scores[best] < BAD_CAPTURE / 2 {
+                return None;
+            }
+
+            self.captures.swap(self.capture_index, best);
+            self.capture_scores.swap(self.capture_index, best);
+            let m = self.captures[self.capture_index];
+            self.capture_index += 1;
+
+            if Some(m) != self.hash_move {
+                return Some(m);
+            }
+        }
+
+        None
+    }
+}
@@ -1,70 +1,113 @@
-use crate::get_piece_type;
-
 // standard algebraic notation
-use super::{Board, Move, BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN, ROOK, WHITE};
+use super::{Attacks, Board, Move, Square};
+use game::{File, PieceType};
 // does not modify board, however generate_legal_moves requrires a mutable board
 // this function is probably expensive to call
 // also why is algebraic notation so stupid
 pub fn to_san(m: &Move, board: &mut Board) -> Result<String, String> {
     let mut s = String::new();
 
-    s += match get_piece_type!(m.piece) {
-        PAWN => match m.capture_piece {
-            Some(_) => ["a", "b", "c", "d", "e", "f", "g", "h"][m.from.1 as usize],
-            None => "",
-        },
-        BISHOP => "B",
-        KNIGHT => "N",
-        ROOK => "R",
-        QUEEN => "Q",
-        KING => "K",
-        _ => return Err("invalid piece type! :skull:".to_owned()),
-    };
+    // crazyhouse drops are written like uci, "N@f3"
+    if m.drop {
+        s = m.to_uci();
+    }
+
+    // the king moves two squares when castling
+    let castling = m.piece.piece_type() == PieceType::King
+        && m.from.file().index().abs_diff(m.to.file().index()) == 2;
+    if castling {
+        s += if m.to.file() == File::G {
+            "O-O"
+        } else {
+            "O-O-O"
+        };
+    }
+
+    let from_file = m.from.file().to_string();
+
+    match m.piece.piece_type() {
+        _ if castling || m.drop => {}
+        PieceType::Pawn => {
+            if m.capture_piece.is_some() {
+                s += &from_file;
+            }
+        }
+        piece_type => s.push(piece_type.to_char().to_ascii_uppercase()),
+    }
 
     // for moves such as Rexe4 vs Raxe4
-    let mut possible_origins: Vec<Move> = Vec::new();
+    // pawns dont need this, the file is already there for captures
+    let mut other_origins: Vec<Square> = Vec::new();
 
-    for mv in super::generate_legal_moves(board, false) {
-        if m.to == mv.to && m.piece == mv.piece {
-            possible_origins.push(mv);
+    if m.piece.piece_type() != PieceType::Pawn && !m.drop {
+        for mv in super::generate_legal_moves(board, false) {
+            if m.to == mv.to && m.piece == mv.piece && m.from != mv.from && !mv.drop {
+                other_origins.push(mv.from);
+            }
         }
     }
 
-    if possible_origins.len() == 2 {
-        if possible_origins[0].from.1 != possible_origins[1].from.1 {
-            s += ["a", "b", "c", "d", "e", "f", "g", "h"][m.from.1 as usize];
-        } else {
-            s += ["8", "7", "6", "5", "4", "3", "2", "1"][m.to.0 as usize];
+    if !other_origins.is_empty() {
+        let same_file = other_origins
+            .iter()
+            .any(|from| from.file() == m.from.file());
+        let same_rank = other_origins
+            .iter()
+            .any(|from| from.rank() == m.from.rank());
+
+        // the file if it is enough, otherwise the rank, otherwise both
+        if !same_file || same_rank {
+            s += &from_file;
+        }
+        if same_file {
+            s += &m.from.rank().to_string();
         }
     }
 
-    s += match m.capture_piece {
-        Some(_) => "x",
-        None => "",
-    };
+    if !castling && !m.drop {
+        s += match m.capture_piece {
+            Some(_) => "x",
+            None => "",
+        };
 
-    s += ["a", "b", "c", "d", "e", "f", "g", "h"][m.to.1 as usize];
-    s += ["8", "7", "6", "5", "4", "3", "2", "1"][m.to.0 as usize];
+        s += &m.to.to_string();
+    }
 
-    let undo = board.make_move(*m);
+    if let Some(piece) = m.promotion_piece {
+        s.push('=');
+        s.push(piece.piece_type().to_char().to_ascii_uppercase());
+    }
 
-    if super::is_in_check(
-        board,
-        if board.turn { WHITE } else { BLACK },
-        if board.turn {
-            board.white_king_position
-        } else {
-            board.black_king_position
-        },
-    ) {
+    if board.gives_check(*m) {
+        let undo = board.make_move(*m);
         s += if super::generate_legal_moves(board, false).is_empty() {
             "#"
         } else {
             "+"
-        }
+        };
+        board.unmake_move(*m, undo);
     }
 
-    undo(board);
-
     Ok(s)
 }
+
+// the parts of a san move that dont matter when comparing moves
+fn normalize_san(san: &str) -> String {
+    san.replace('0', "O")
+        .chars()
+        .filter(|c| !matches!(c, '+' | '#' | '!' | '?' | 'x' | '=' | ':' | '-'))
+        .collect()
+}
+
+// finds the legal move written in standard algebraic notation, for example from an epd or pgn
+pub fn from_san(san: &str, board: &mut Board) -> Result<Move, String> {
+    let target = normalize_san(san);
+
+    for m in super::generate_legal_moves(board, false) {
+        if normalize_san(&to_san(&m, board)?) == target || m.to_uci() == san {
+            return Ok(m);
+        }
+    }
+
+    Err(format!("{} is not a legal move", san))
+}
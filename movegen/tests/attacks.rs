@@ -0,0 +1,118 @@
+use game::{Bitboard, Board, Color, Move, Square, KIWIPETE};
+use movegen::{
+    generate_legal_moves, generate_pinned_moves, is_in_check, perft::PERFT_SUITE, Attacks, GenType,
+    MoveList,
+};
+
+// the suite positions and the positions one move after them
+fn positions() -> Vec<Board> {
+    PERFT_SUITE
+        .iter()
+        .flat_map(|&(fen, _)| {
+            let mut board = Board::from_fen(fen).unwrap();
+            let children: Vec<Board> = generate_legal_moves(&mut board, false)
+                .into_iter()
+                .map(|m| {
+                    let mut child = board;
+                    let _ = child.make_move(m);
+                    child
+                })
+                .collect();
+
+            std::iter::once(board).chain(children)
+        })
+        .collect()
+}
+
+#[test]
+fn gives_check_matches_making_the_move() {
+    for mut board in positions() {
+        for m in generate_legal_moves(&mut board, false) {
+            let gives_check = board.gives_check(m);
+
+            let mut after = board;
+            let _ = after.make_move(m);
+            let check = is_in_check(&after, after.turn, after.king_square(after.turn));
+
+            assert_eq!(gives_check, check, "{} {}", board.to_fen(), m);
+        }
+    }
+}
+
+#[test]
+fn checkers_and_pins() {
+    for mut board in positions() {
+        let in_check = is_in_check(&board, board.turn, board.king_square(board.turn));
+        assert_eq!(board.in_check(), in_check);
+        assert_eq!(board.checkers() != 0, in_check);
+        // now from the cached check info
+        assert_eq!(board.in_check(), in_check);
+
+        let mut copy = board;
+        let pinned = generate_pinned_moves(&mut copy, &mut MoveList::new(), GenType::All, !0);
+        assert_eq!(board.pinned(board.turn), pinned, "{}", board.to_fen());
+    }
+
+    // the rook on e1 and the bishop on a4 both give check
+    let mut board = Board::from_fen("4k3/8/8/8/B7/8/3P4/4R1K1 b - - 0 1").unwrap();
+    assert_eq!(
+        board.checkers(),
+        (Square::E1.bitboard() | Square::A4.bitboard()).0
+    );
+
+    let mut board = Board::from_fen("4k3/3n4/8/8/B7/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.pinned(Color::Black), Square::D7.bitboard().0);
+    assert_eq!(board.pinned(Color::White), 0);
+
+    // a white knight in the way of its own bishop is not pinned, but it can give discovered
+    // check
+    let mut board = Board::from_fen("4k3/3N4/8/8/B7/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.pinned(Color::Black), 0);
+    assert_eq!(board.pinned(Color::White), 0);
+    assert!(board.gives_check(Move::from_uci("d7b6", board)));
+}
+
+#[test]
+fn attack_maps() {
+    for board in positions() {
+        let occupancy = board.white.all | board.black.all;
+
+        for color in Color::ALL {
+            let pieces = board.bitboards(color).all;
+            let attacked: Bitboard = Bitboard::FULL
+                .filter(|&square| board.attackers_to(square, occupancy) & pieces != 0)
+                .collect();
+
+            assert_eq!(
+                board.attacked_squares(color),
+                attacked.0,
+                "{}",
+                board.to_fen()
+            );
+        }
+    }
+
+    // with an empty occupancy the sliders see through everything
+    let board = Board::from_fen(KIWIPETE).unwrap();
+    assert_ne!(
+        board.attackers_to(Square::A8, 0),
+        board.attackers_to(Square::A8, board.white.all | board.black.all)
+    );
+}
+
+#[test]
+fn check_info_is_cached_until_the_next_move() {
+    let mut board = Board::from_fen(KIWIPETE).unwrap();
+    assert_eq!(board.check_info, None);
+
+    let info = board.check_info();
+    assert_eq!(board.check_info, Some(info));
+
+    let m = Move::from_uci("e2a6", board);
+    let undo = board.make_move(m);
+    assert_eq!(board.check_info, None);
+    assert_ne!(board.check_info(), info);
+
+    board.unmake_move(m, undo);
+    assert_eq!(board.check_info, Some(info));
+}
@@ -0,0 +1,177 @@
+use game::{move16::Move16, Board, Move, Variant, STARTPOS};
+use movegen::{
+    generate_legal_moves, generate_moves, generate_pseudo_legal_moves, has_moves,
+    perft::{PERFT_SUITE, VARIANT_PERFT_SUITE},
+    GenType, Legality, MoveList,
+};
+
+fn pseudo_legal_moves(board: &Board, gen: GenType) -> Vec<Move> {
+    let mut moves = MoveList::new();
+    generate_pseudo_legal_moves(board, &mut moves, gen);
+    moves.to_vec()
+}
+
+// the suite positions and the positions one move after them
+fn positions() -> Vec<Board> {
+    PERFT_SUITE
+        .iter()
+        .flat_map(|&(fen, _)| {
+            let mut board = Board::from_fen(fen).unwrap();
+            let children: Vec<Board> = generate_legal_moves(&mut board, false)
+                .into_iter()
+                .map(|m| {
+                    let mut child = board;
+                    let _ = child.make_move(m);
+                    child
+                })
+                .collect();
+
+            std::iter::once(board).chain(children)
+        })
+        .collect()
+}
+
+#[test]
+fn legal_pseudo_legal_moves_are_the_legal_moves() {
+    for mut board in positions() {
+        for (gen, captures_only) in [(GenType::All, false), (GenType::Captures, true)] {
+            let pseudo_legal = pseudo_legal_moves(&board, gen);
+            let legal = generate_legal_moves(&mut board, captures_only);
+
+            assert!(pseudo_legal.iter().all(|&m| board.is_pseudo_legal(m)));
+            for &m in &legal {
+                assert!(pseudo_legal.contains(&m), "{} {}", board.to_fen(), m);
+            }
+            for &m in &pseudo_legal {
+                assert_eq!(
+                    board.is_legal(m),
+                    legal.contains(&m),
+                    "{} {}",
+                    board.to_fen(),
+                    m
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn moves_from_other_positions_are_rejected() {
+    let positions = positions();
+    let moves: Vec<Move> = positions
+        .iter()
+        .flat_map(|&board| pseudo_legal_moves(&board, GenType::All))
+        .collect();
+
+    // as if every move came out of a transposition table entry of a different position
+    for mut board in positions.iter().copied().step_by(7) {
+        let pseudo_legal = pseudo_legal_moves(&board, GenType::All);
+        let legal = generate_legal_moves(&mut board, false);
+
+        for &m in &moves {
+            assert_eq!(
+                board.is_pseudo_legal(m),
+                pseudo_legal.contains(&m),
+                "{} {}",
+                board.to_fen(),
+                m
+            );
+            if board.is_pseudo_legal(m) {
+                assert_eq!(board.is_legal(m), legal.contains(&m));
+            }
+        }
+    }
+}
+
+#[test]
+fn castling_through_check_is_not_pseudo_legal() {
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert!(board.is_pseudo_legal(Move::from_uci("e1g1", board)));
+
+    let attacked = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+    assert!(!attacked.is_pseudo_legal(Move::from_uci("e1g1", attacked)));
+    assert!(attacked.is_pseudo_legal(Move::from_uci("e1c1", attacked)));
+
+    let blocked = Board::from_fen(STARTPOS).unwrap();
+    assert!(!blocked.is_pseudo_legal(Move::from_uci("e1c1", blocked)));
+    assert!(!blocked.is_pseudo_legal(Move::from_uci("e1g1", blocked)));
+}
+
+#[test]
+fn pinned_pieces_and_en_passant() {
+    // the knight is pinned, moving it is pseudo-legal but not legal
+    let pinned = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    let m = Move::from_uci("e2c3", pinned);
+    assert!(pinned.is_pseudo_legal(m));
+    assert!(!pinned.is_legal(m));
+
+    // both pawns leave the rank, exposing the king to the rook
+    let en_passant = Board::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1").unwrap();
+    let m = Move::from_uci("b5c6", en_passant);
+    assert!(m.en_passant);
+    assert!(en_passant.is_pseudo_legal(m));
+    assert!(!en_passant.is_legal(m));
+
+    // the en passant square is gone after any other move
+    let mut board = Board::from_fen(STARTPOS).unwrap();
+    let _ = board.make_move(Move::from_uci("e2e4", board));
+    let _ = board.make_move(Move::from_uci("d7d5", board));
+    let _ = board.make_move(Move::from_uci("e4e5", board));
+    let _ = board.make_move(Move::from_uci("f7f5", board));
+    let m = Move::from_uci("e5f6", board);
+    assert!(board.is_pseudo_legal(m) && board.is_legal(m));
+    let _ = board.make_move(Move::from_uci("a2a3", board));
+    let _ = board.make_move(Move::from_uci("a7a6", board));
+    assert!(!board.is_pseudo_legal(m));
+}
+
+// the transposition table and the killers keep moves packed into 16 bits
+#[test]
+fn packed_moves_round_trip() {
+    let variants = VARIANT_PERFT_SUITE
+        .iter()
+        .map(|&(variant, fen, _)| Board::from_variant_fen(variant, fen).unwrap());
+
+    for mut board in positions().into_iter().chain(variants) {
+        for m in generate_legal_moves(&mut board, false) {
+            let packed = Move16::from(m);
+            assert_eq!(packed.to_move(&board), Some(m), "{} {}", board.to_fen(), m);
+            assert_eq!(packed.to_string(), m.to_uci());
+        }
+    }
+}
+
+#[test]
+fn has_moves_stops_at_the_first_move() {
+    let variants = VARIANT_PERFT_SUITE.iter().flat_map(|&(variant, fen, _)| {
+        let mut board = Board::from_variant_fen(variant, fen).unwrap();
+        let children: Vec<Board> = generate_legal_moves(&mut board, false)
+            .into_iter()
+            .map(|m| {
+                let mut child = board;
+                let _ = child.make_move(m);
+                child
+            })
+            .collect();
+        std::iter::once(board).chain(children)
+    });
+    // stalemate, and an antichess side without pieces
+    let no_moves = [
+        Board::from_fen("8/8/8/8/8/7p/5k1P/r5BK w - - 0 1").unwrap(),
+        Board::from_variant_fen(Variant::Antichess, "8/8/8/8/8/8/8/k7 w - - 0 1").unwrap(),
+    ];
+
+    for mut board in positions().into_iter().chain(variants).chain(no_moves) {
+        for gen in [GenType::All, GenType::Captures, GenType::Quiets] {
+            let mut moves = MoveList::new();
+            generate_moves(&mut board, &mut moves, gen);
+            assert_eq!(
+                has_moves(&mut board, gen),
+                !moves.is_empty(),
+                "{} {:?}",
+                board.to_fen(),
+                gen
+            );
+        }
+    }
+}
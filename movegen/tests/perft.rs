@@ -0,0 +1,149 @@
+use game::{Board, STARTPOS};
+use movegen::perft::{
+    divide, first_difference, perft, perft_hashed, perft_pseudo_legal, PerftOptions, PerftTable,
+    PERFT_SUITE,
+};
+
+// the deeper results take too long for a debug build, `rustchess2 perft <depth> --suite` runs
+// all of them
+const MAX_NODES: u64 = 1_000_000;
+
+// (fen, depth, nodes) of every suite result below the node limit
+fn suite(max_nodes: u64) -> impl Iterator<Item = (&'static str, u8, u64)> {
+    PERFT_SUITE.iter().flat_map(move |&(fen, counts)| {
+        counts
+            .iter()
+            .enumerate()
+            .filter(move |(_, &nodes)| nodes <= max_nodes)
+            .map(move |(i, &nodes)| (fen, i as u8 + 1, nodes))
+    })
+}
+
+#[test]
+fn perft_suite() {
+    for (fen, depth, nodes) in suite(MAX_NODES) {
+        assert_eq!(
+            perft(&mut Board::from_fen(fen).unwrap(), depth),
+            nodes,
+            "{} depth {}",
+            fen,
+            depth
+        );
+    }
+}
+
+#[test]
+fn hashed_perft_suite() {
+    let mut table = PerftTable::new(16);
+    for (fen, depth, nodes) in suite(MAX_NODES) {
+        assert_eq!(
+            perft_hashed(&mut Board::from_fen(fen).unwrap(), depth, &mut table),
+            nodes,
+            "{} depth {}",
+            fen,
+            depth
+        );
+    }
+}
+
+#[test]
+fn pseudo_legal_perft_suite() {
+    for (fen, depth, nodes) in suite(MAX_NODES) {
+        assert_eq!(
+            perft_pseudo_legal(&mut Board::from_fen(fen).unwrap(), depth),
+            nodes,
+            "{} depth {}",
+            fen,
+            depth
+        );
+    }
+}
+
+#[test]
+fn threaded_divide_suite() {
+    let options = PerftOptions {
+        threads: 4,
+        hash_size: 1,
+        pseudo_legal: false,
+    };
+
+    for (fen, depth, nodes) in suite(MAX_NODES / 10) {
+        let divide = divide(&Board::from_fen(fen).unwrap(), depth, options);
+        assert_eq!(
+            divide.iter().map(|(_, nodes)| nodes).sum::<u64>(),
+            nodes,
+            "{} depth {}",
+            fen,
+            depth
+        );
+    }
+}
+
+#[test]
+fn perft_does_not_change_the_board() {
+    let mut board = Board::from_fen(PERFT_SUITE[1].0).unwrap();
+    let hash = board.hash;
+    let fen = board.to_fen();
+
+    perft(&mut board, 3);
+
+    assert_eq!(board.hash, hash);
+    assert_eq!(board.to_fen(), fen);
+}
+
+#[test]
+fn divide_matches_reference() {
+    let divide = divide(
+        &Board::from_fen(STARTPOS).unwrap(),
+        2,
+        PerftOptions::default(),
+    );
+    let reference: String = divide
+        .iter()
+        .map(|(m, nodes)| format!("{}: {}\n", m, nodes))
+        .collect();
+
+    assert_eq!(first_difference(&divide, &reference), None);
+}
+
+#[test]
+fn divide_reports_first_difference() {
+    let divide = divide(
+        &Board::from_fen(STARTPOS).unwrap(),
+        2,
+        PerftOptions::default(),
+    );
+    let reference = divide
+        .iter()
+        .map(|(m, nodes)| {
+            // as if the move generator missed a reply after e2e4
+            let nodes = if m.to_uci() == "e2e4" {
+                nodes + 1
+            } else {
+                *nodes
+            };
+            format!("{}: {}\n", m, nodes)
+        })
+        .collect::<String>();
+
+    assert_eq!(
+        first_difference(&divide, &reference),
+        Some(("e2e4".to_owned(), Some(20), Some(21)))
+    );
+}
+
+#[test]
+fn divide_reports_missing_moves() {
+    let divide = divide(
+        &Board::from_fen(STARTPOS).unwrap(),
+        1,
+        PerftOptions::default(),
+    );
+    let reference = "a2a3: 1\n";
+
+    assert_eq!(
+        first_difference(&divide[..0], reference),
+        Some(("a2a3".to_owned(), None, Some(1)))
+    );
+    assert!(first_difference(&divide, reference).is_some());
+}
@@ -0,0 +1,307 @@
+use game::{genkeys::HASH_KEYS, move16::Move16, Board, Color, Move, PieceType, Variant};
+use movegen::{
+    generate_legal_moves,
+    perft::{perft, perft_hashed, perft_pseudo_legal, PerftTable, VARIANT_PERFT_SUITE},
+    san::{from_san, to_san},
+    Attacks, Legality,
+};
+
+// the deeper results take too long for a debug build
+const MAX_NODES: u64 = 1_000_000;
+
+fn legal_moves(board: &mut Board) -> Vec<String> {
+    let mut moves: Vec<String> = generate_legal_moves(board, false)
+        .iter()
+        .map(Move::to_uci)
+        .collect();
+    moves.sort();
+    moves
+}
+
+#[test]
+fn variant_perft_suite() {
+    for &(variant, fen, counts) in &VARIANT_PERFT_SUITE {
+        // the hash does not include the variant, the same position counts differently in each
+        let mut table = PerftTable::new(16);
+        for (i, &nodes) in counts.iter().enumerate() {
+            if nodes > MAX_NODES {
+                continue;
+            }
+
+            let depth = i as u8 + 1;
+            let mut board = Board::from_variant_fen(variant, fen).unwrap();
+            assert_eq!(perft(&mut board, depth), nodes, "{} depth {}", fen, depth);
+            assert_eq!(
+                perft_pseudo_legal(&mut board, depth),
+                nodes,
+                "{} depth {}",
+                fen,
+                depth
+            );
+            assert_eq!(
+                perft_hashed(&mut board, depth, &mut table),
+                nodes,
+                "{} depth {}",
+                fen,
+                depth
+            );
+        }
+    }
+}
+
+#[test]
+fn antichess_captures_are_compulsory() {
+    let mut board = Board::from_variant_fen(
+        Variant::Antichess,
+        "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w - - 0 2",
+    )
+    .unwrap();
+    assert_eq!(legal_moves(&mut board), ["e4d5"]);
+
+    // a quiet move is still pseudo-legal
+    let quiet = Move::from_uci("e4e5", board);
+    assert!(board.is_pseudo_legal(quiet));
+    assert!(!board.is_legal(quiet));
+}
+
+#[test]
+fn antichess_king_is_an_ordinary_piece() {
+    // the king can be captured, walks into attacks and pawns can promote to one
+    let mut board =
+        Board::from_variant_fen(Variant::Antichess, "8/P7/8/8/8/8/1k6/K7 w - - 0 1").unwrap();
+    assert_eq!(legal_moves(&mut board), ["a1b2"]);
+    assert!(!board.in_check());
+
+    let mut board =
+        Board::from_variant_fen(Variant::Antichess, "8/P7/8/8/8/8/2k5/7K w - - 0 1").unwrap();
+    let moves = legal_moves(&mut board);
+    assert!(moves.contains(&"a7a8k".to_owned()));
+    assert!(moves.contains(&"h1g2".to_owned()));
+
+    let promotion = Move::from_uci("a7a8k", board);
+    assert!(board.is_pseudo_legal(promotion));
+    assert!(!board.gives_check(promotion));
+}
+
+#[test]
+fn antichess_has_no_castling() {
+    let mut board =
+        Board::from_variant_fen(Variant::Antichess, "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+    assert!(!legal_moves(&mut board).contains(&"e1g1".to_owned()));
+}
+
+#[test]
+fn king_of_the_hill_ends_in_the_centre() {
+    let mut board =
+        Board::from_variant_fen(Variant::KingOfTheHill, "4k3/8/8/8/8/4K3/8/8 w - - 0 1").unwrap();
+    assert!(!board.is_variant_loss());
+
+    let m = Move::from_uci("e3e4", board);
+    let _ = board.make_move(m);
+    assert!(board.is_variant_loss());
+    assert!(legal_moves(&mut board).is_empty());
+    assert!(!board.is_pseudo_legal(Move::from_uci("e8e7", board)));
+}
+
+#[test]
+fn three_check_counts_checks() {
+    let fen = "4k3/8/8/8/8/8/8/3QK3 w - - 2+3 0 1";
+    let mut board = Board::from_variant_fen(Variant::ThreeCheck, fen).unwrap();
+    assert_eq!(board.to_fen(), fen);
+    assert_eq!(board.checks, [1, 0]);
+
+    let m = Move::from_uci("d1d7", board);
+    let undo = board.make_move(m);
+    assert_eq!(board.checks, [2, 0]);
+    assert!(board.to_fen().contains(" 1+3 "));
+
+    // the check count is part of the hash
+    let mut rehashed = board;
+    rehashed.hash = 0;
+    HASH_KEYS.generate_hash(&mut rehashed);
+    assert_eq!(board.hash, rehashed.hash);
+
+    board.unmake_move(m, undo);
+    assert_eq!(board.to_fen(), fen);
+
+    // the lichess form counts the checks given
+    let lichess =
+        Board::from_variant_fen(Variant::ThreeCheck, "4k3/8/8/8/8/8/8/3QK3 w - - 0 1 +1+0")
+            .unwrap();
+    assert_eq!(lichess.checks, [1, 0]);
+    assert_eq!(
+        lichess.hash,
+        Board::from_variant_fen(Variant::ThreeCheck, fen)
+            .unwrap()
+            .hash
+    );
+}
+
+#[test]
+fn three_check_ends_after_the_third_check() {
+    let mut board =
+        Board::from_variant_fen(Variant::ThreeCheck, "4k3/8/8/8/8/8/8/3QK3 w - - 1+3 0 1").unwrap();
+    let _ = board.make_move(Move::from_uci("d1d7", board));
+    assert!(board.is_variant_loss());
+    assert!(legal_moves(&mut board).is_empty());
+}
+
+#[test]
+fn crazyhouse_drops() {
+    let mut board =
+        Board::from_variant_fen(Variant::Crazyhouse, "4k3/8/8/8/8/8/8/4K3[P] w - - 0 1").unwrap();
+    let moves = legal_moves(&mut board);
+    assert_eq!(moves.len(), 53);
+    assert!(moves.contains(&"P@a2".to_owned()));
+    assert!(!moves.contains(&"P@a1".to_owned()));
+    assert!(!moves.contains(&"P@a8".to_owned()));
+
+    let m = Move::from_uci("P@d7", board);
+    assert!(m.drop);
+    assert_eq!(m.to_uci(), "P@d7");
+    assert!(board.is_pseudo_legal(m) && board.is_legal(m));
+    assert!(board.gives_check(m));
+    assert_eq!(to_san(&m, &mut board).unwrap(), "P@d7+");
+    assert_eq!(from_san("P@d7+", &mut board), Ok(m));
+
+    // the packed move takes the color from the board
+    let packed = Move16::from(m);
+    assert_eq!(packed.to_string(), "P@d7");
+    assert_eq!(packed.to_move(&board), Some(m));
+
+    let fen = board.to_fen();
+    let undo = board.make_move(m);
+    assert_eq!(board.to_fen(), "4k3/3P4/8/8/8/8/8/4K3[] b - - 0 1");
+    assert_eq!(
+        board.hash,
+        Board::from_variant_fen(Variant::Crazyhouse, &board.to_fen())
+            .unwrap()
+            .hash
+    );
+    assert!(!board.is_pseudo_legal(Move::from_uci("P@d6", board)));
+
+    board.unmake_move(m, undo);
+    assert_eq!(board.to_fen(), fen);
+}
+
+#[test]
+fn crazyhouse_drops_block_checks() {
+    let mut board =
+        Board::from_variant_fen(Variant::Crazyhouse, "4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1").unwrap();
+    assert_eq!(
+        legal_moves(&mut board),
+        ["N@b1", "N@c1", "N@d1", "e1d2", "e1e2", "e1f2"]
+    );
+
+    let m = Move::from_uci("N@f3", board);
+    assert!(board.is_pseudo_legal(m));
+    assert!(!board.is_legal(m));
+}
+
+#[test]
+fn crazyhouse_captures_fill_the_pocket() {
+    let mut board =
+        Board::from_variant_fen(Variant::Crazyhouse, "4k3/8/8/8/8/8/3n4/3qK3[] w - - 0 1").unwrap();
+    let _ = board.make_move(Move::from_uci("e1d2", board));
+    assert_eq!(board.pocket(Color::White).count(PieceType::Knight), 1);
+
+    // a promoted piece goes back into the pocket as a pawn
+    let fen = "4k3/8/8/8/8/8/8/3q~K3[] w - - 0 1";
+    let mut board = Board::from_variant_fen(Variant::Crazyhouse, fen).unwrap();
+    assert_eq!(board.to_fen(), fen);
+
+    let m = Move::from_uci("e1d1", board);
+    let undo = board.make_move(m);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/3K4[P] b - - 0 1");
+    board.unmake_move(m, undo);
+    assert_eq!(board.to_fen(), fen);
+
+    // and promoting marks the piece
+    let mut board =
+        Board::from_variant_fen(Variant::Crazyhouse, "4k3/P7/8/8/8/8/8/4K3[Qn] w - - 0 1").unwrap();
+    let _ = board.make_move(Move::from_uci("a7a8q", board));
+    assert_eq!(board.to_fen(), "Q~3k3/8/8/8/8/8/8/4K3[Qn] b - - 0 1");
+}
+
+#[test]
+fn atomic_captures_explode() {
+    // the knight, the queen and the pieces around d5 explode, the pawn next to it survives
+    let fen = "4k3/8/2n1r3/3q4/2P5/4N3/8/4K3 w - - 0 1";
+    let mut board = Board::from_variant_fen(Variant::Atomic, fen).unwrap();
+    let m = Move::from_uci("e3d5", board);
+    assert!(board.is_pseudo_legal(m) && board.is_legal(m));
+
+    let undo = board.make_move(m);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/2P5/8/8/4K3 b - - 0 1");
+    assert_eq!(
+        board.hash,
+        Board::from_variant_fen(Variant::Atomic, &board.to_fen())
+            .unwrap()
+            .hash
+    );
+
+    board.unmake_move(m, undo);
+    assert_eq!(board.to_fen(), fen);
+
+    // a rook exploding in its corner takes the castling right with it
+    let mut board =
+        Board::from_variant_fen(Variant::Atomic, "r3k3/1p6/8/8/8/8/8/1R2K3 w q - 0 1").unwrap();
+    let _ = board.make_move(Move::from_uci("b1b7", board));
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+}
+
+#[test]
+fn atomic_kings() {
+    // the king cant capture the queen checking it
+    let mut board =
+        Board::from_variant_fen(Variant::Atomic, "4k3/8/8/8/8/8/3q4/4K3 w - - 0 1").unwrap();
+    assert_eq!(legal_moves(&mut board), ["e1f1"]);
+    assert!(!board.is_pseudo_legal(Move::from_uci("e1d2", board)));
+
+    // kings next to each other cant be checked
+    let mut board =
+        Board::from_variant_fen(Variant::Atomic, "8/8/8/8/8/8/3k4/3K3r w - - 0 1").unwrap();
+    assert!(!board.in_check());
+    assert_eq!(legal_moves(&mut board), ["d1c1", "d1c2", "d1e1", "d1e2"]);
+
+    // a capture next to the own king is never legal
+    let board =
+        Board::from_variant_fen(Variant::Atomic, "8/8/8/8/8/8/3p4/3KB2k w - - 0 1").unwrap();
+    let m = Move::from_uci("e1d2", board);
+    assert!(board.is_pseudo_legal(m));
+    assert!(!board.is_legal(m));
+
+    // exploding the enemy king wins even when the own king is in check
+    let mut board =
+        Board::from_variant_fen(Variant::Atomic, "4k3/3p4/8/8/8/4r3/8/3QK3 w - - 0 1").unwrap();
+    assert!(board.in_check());
+    let m = Move::from_uci("d1d7", board);
+    assert!(legal_moves(&mut board).contains(&m.to_uci()));
+    assert!(!board.gives_check(m));
+
+    let _ = board.make_move(m);
+    assert!(board.is_variant_loss());
+    assert!(legal_moves(&mut board).is_empty());
+}
+
+#[test]
+fn horde_pawns() {
+    // the double push from the first rank leaves no en passant square
+    let mut board =
+        Board::from_variant_fen(Variant::Horde, "4k3/8/8/8/8/8/8/P7 w - - 0 1").unwrap();
+    assert!(board.validate().is_ok());
+    assert!(!board.in_check());
+    assert_eq!(legal_moves(&mut board), ["a1a2", "a1a3"]);
+
+    let m = Move::from_uci("a1a3", board);
+    assert!(board.is_pseudo_legal(m));
+    let _ = board.make_move(m);
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/P7/8/8 b - - 0 1");
+
+    // white loses with its last piece
+    let mut board =
+        Board::from_variant_fen(Variant::Horde, "4k3/8/8/8/8/8/8/Pr6 b - - 0 1").unwrap();
+    let _ = board.make_move(Move::from_uci("b1a1", board));
+    assert!(board.is_variant_loss());
+    assert!(legal_moves(&mut board).is_empty());
+}
@@ -0,0 +1,758 @@
+// plays matches between two engines, to check if a change actually gains elo
+//
+// usage: rustchess2 match --engine <spec> --engine <spec> [options]
+//   --engine <spec>       name=<name> [cmd=<path>] [option.<name>=<value>]...
+//                         engines without a cmd are this engine, configured with its uci options
+//                         (for example option.PVS=false or option.EvalParams=tuned.txt). engines
+//                         with a cmd are uci executables run as a subprocess
+//   --openings <file>     FEN/EPD lines, every opening is played twice with the colors swapped.
+//                         defaults to the starting position
+//   --games <n>           defaults to 100
+//   --tc <time+inc>       time control in seconds, defaults to 10+0.1
+//   --nodes <n>           nodes per move instead of a time control
+//   --depth <n>           depth per move instead of a time control
+//   --sprt <elo0> <elo1>  stops once the sequential probability ratio test accepts one of the
+//                         hypotheses
+//   --alpha <a>           false positive rate of the sprt, defaults to 0.05
+//   --beta <b>            false negative rate of the sprt, defaults to 0.05
+//   --pgn <file>          where the games are written, defaults to match.pgn
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    iter::Peekable,
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use movegen::{generate_legal_moves, san::to_san, Attacks};
+use rustchess2::{
+    game::{Board, Color, Move, PieceType, STARTPOS},
+    stats::{Sprt, Stats},
+};
+
+use crate::uci::UciEngine;
+
+// the time controls of the uci protocol assume this many moves until the next time control
+const MOVES: u32 = 40;
+// external engines get this much extra time before they are considered to have hung
+const GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy)]
+struct TimeControl {
+    time: Duration,
+    increment: Duration,
+}
+
+#[derive(Clone, Copy)]
+struct Limits {
+    time_control: Option<TimeControl>,
+    nodes: Option<u64>,
+    depth: Option<u8>,
+}
+
+// remaining time of both sides for the current move
+#[derive(Clone, Copy)]
+struct Clock {
+    white: Duration,
+    black: Duration,
+}
+
+trait Player {
+    fn name(&self) -> &str;
+
+    fn new_game(&mut self) -> Result<(), Box<dyn Error>>;
+
+    // returns the move in uci notation, position is a uci position command
+    fn go(
+        &mut self,
+        position: &str,
+        limits: &Limits,
+        clock: &Clock,
+        turn: Color,
+    ) -> Result<String, Box<dyn Error>>;
+}
+
+/// this engine, running in the same process
+struct Internal {
+    name: String,
+    uci: UciEngine,
+}
+
+impl Internal {
+    fn new(name: String, options: &[(String, String)]) -> Internal {
+        let mut uci = UciEngine::new();
+        for (option, value) in options {
+            uci.setoption_command(&format!("setoption name {} value {}", option, value));
+        }
+
+        Internal { name, uci }
+    }
+}
+
+impl Player for Internal {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn go(
+        &mut self,
+        position: &str,
+        limits: &Limits,
+        clock: &Clock,
+        turn: Color,
+    ) -> Result<String, Box<dyn Error>> {
+        self.uci.position_command(position);
+
+        let engine = &mut self.uci.engine;
+        engine.options = self.uci.options;
+        engine.quiet = true;
+        engine.node_limit = limits.nodes;
+
+        // same time management as the go command
+        let alloted_time = if turn.is_white() {
+            clock.white
+        } else {
+            clock.black
+        } / MOVES;
+        let best_move = engine.iterative_deepening_search(
+            limits.depth.unwrap_or(100),
+            limits.time_control.is_some(),
+            Instant::now(),
+            alloted_time,
+            None,
+        );
+
+        let best_move = match best_move {
+            Some(m) => m,
+            None => *generate_legal_moves(&mut engine.board, false)
+                .first()
+                .ok_or("no legal moves")?,
+        };
+
+        Ok(best_move.to_uci())
+    }
+}
+
+/// a uci engine running as a subprocess
+struct External {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl External {
+    fn new(
+        name: String,
+        command: &str,
+        options: &[(String, String)],
+    ) -> Result<External, Box<dyn Error>> {
+        let mut child = Command::new(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("failed to start {}: {}", command, e))?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        // reading happens on another thread so the engine can be timed out
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut external = External {
+            name,
+            child,
+            stdin,
+            lines,
+        };
+
+        external.send("uci")?;
+        external.wait_for("uciok", GRACE)?;
+        for (option, value) in options {
+            external.send(&format!("setoption name {} value {}", option, value))?;
+        }
+        external.send("isready")?;
+        external.wait_for("readyok", GRACE)?;
+
+        Ok(external)
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), Box<dyn Error>> {
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()?;
+
+        Ok(())
+    }
+
+    // returns the first line starting with the token
+    fn wait_for(&mut self, token: &str, timeout: Duration) -> Result<String, Box<dyn Error>> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(remaining) {
+                Ok(line) => {
+                    if line.split_whitespace().next() == Some(token) {
+                        return Ok(line);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(format!("{} did not send {}", self.name, token).into())
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!("{} disconnected", self.name).into())
+                }
+            }
+        }
+    }
+}
+
+impl Player for External {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn new_game(&mut self) -> Result<(), Box<dyn Error>> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        self.wait_for("readyok", GRACE)?;
+
+        Ok(())
+    }
+
+    fn go(
+        &mut self,
+        position: &str,
+        limits: &Limits,
+        clock: &Clock,
+        turn: Color,
+    ) -> Result<String, Box<dyn Error>> {
+        self.send(position)?;
+
+        let mut go = String::from("go");
+        let mut timeout = GRACE;
+        if let Some(tc) = limits.time_control {
+            go += &format!(
+                " wtime {} btime {} winc {} binc {}",
+                clock.white.as_millis(),
+                clock.black.as_millis(),
+                tc.increment.as_millis(),
+                tc.increment.as_millis()
+            );
+            timeout += if turn.is_white() {
+                clock.white
+            } else {
+                clock.black
+            };
+        } else {
+            // no way to know how long a fixed node or depth search takes
+            timeout = Duration::from_secs(3600);
+        }
+        if let Some(nodes) = limits.nodes {
+            go += &format!(" nodes {}", nodes);
+        }
+        if let Some(depth) = limits.depth {
+            go += &format!(" depth {}", depth);
+        }
+        self.send(&go)?;
+
+        let line = match self.wait_for("bestmove", timeout) {
+            Ok(line) => line,
+            Err(_) => {
+                // out of time, but the move is still needed to keep the engine in sync
+                self.send("stop")?;
+                self.wait_for("bestmove", GRACE)?
+            }
+        };
+
+        Ok(line
+            .split_whitespace()
+            .nth(1)
+            .ok_or(format!("{} sent an empty bestmove", self.name))?
+            .to_owned())
+    }
+}
+
+impl Drop for External {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+struct Game {
+    white: String,
+    black: String,
+    opening: String,
+    round: usize,
+    moves: Vec<String>,
+    // 1.0 if white won, 0.5 for a draw and 0.0 if black won
+    result: f64,
+    reason: String,
+}
+
+impl Game {
+    fn result_string(&self) -> &'static str {
+        if self.result == 1.0 {
+            "1-0"
+        } else if self.result == 0.0 {
+            "0-1"
+        } else {
+            "1/2-1/2"
+        }
+    }
+
+    fn write_pgn(&self, out: &mut impl Write, limits: &Limits) -> Result<(), Box<dyn Error>> {
+        writeln!(out, "[Event \"rustchess2 match\"]")?;
+        writeln!(out, "[Site \"?\"]")?;
+        writeln!(out, "[Date \"{}\"]", date())?;
+        writeln!(out, "[Round \"{}\"]", self.round)?;
+        writeln!(out, "[White \"{}\"]", self.white)?;
+        writeln!(out, "[Black \"{}\"]", self.black)?;
+        writeln!(out, "[Result \"{}\"]", self.result_string())?;
+        if self.opening != STARTPOS {
+            writeln!(out, "[FEN \"{}\"]", self.opening)?;
+            writeln!(out, "[SetUp \"1\"]")?;
+        }
+        match limits.time_control {
+            Some(tc) => writeln!(
+                out,
+                "[TimeControl \"{}+{}\"]",
+                tc.time.as_secs_f64(),
+                tc.increment.as_secs_f64()
+            )?,
+            None => writeln!(out, "[TimeControl \"-\"]")?,
+        }
+        writeln!(out, "[PlyCount \"{}\"]", self.moves.len())?;
+        writeln!(out)?;
+
+        let mut fields = self.opening.split_whitespace().skip(1);
+        let mut white = fields.next() == Some("w");
+        let mut fullmoves: u32 = fields.nth(3).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        let mut tokens = Vec::new();
+        for (i, m) in self.moves.iter().enumerate() {
+            if white {
+                tokens.push(format!("{}.", fullmoves));
+            } else if i == 0 {
+                tokens.push(format!("{}...", fullmoves));
+            }
+            tokens.push(m.clone());
+
+            if !white {
+                fullmoves += 1;
+            }
+            white = !white;
+        }
+        tokens.push(format!("{{{}}}", self.reason));
+        tokens.push(self.result_string().to_owned());
+
+        // lines are at most 80 characters
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() + 1 > 80 {
+                writeln!(out, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &token;
+        }
+        writeln!(out, "{}\n", line)?;
+
+        Ok(())
+    }
+}
+
+// today in yyyy.mm.dd, the format of the pgn date tag
+// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn date() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86400) as i64
+        + 719468;
+
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+// king against king, king and minor piece against king, or only bishops on one color
+fn insufficient_material(board: &Board) -> bool {
+    let mut minors = 0;
+    let mut bishop_colors = [false; 2];
+
+    for (r, rank) in board.board.iter().enumerate() {
+        for (f, &piece) in rank.iter().enumerate() {
+            match piece.map(|piece| piece.piece_type()) {
+                None | Some(PieceType::King) => {}
+                Some(PieceType::Knight) => minors += 1,
+                Some(PieceType::Bishop) => {
+                    minors += 1;
+                    bishop_colors[(r + f) % 2] = true;
+                }
+                _ => return false,
+            }
+        }
+    }
+
+    let only_bishops = board.white.knights == 0 && board.black.knights == 0;
+    minors <= 1 || (only_bishops && !(bishop_colors[0] && bishop_colors[1]))
+}
+
+// uci position command for the moves played after the opening
+fn position_command(opening: &str, moves: &[Move]) -> String {
+    let mut command = if opening == STARTPOS {
+        String::from("position startpos")
+    } else {
+        format!("position fen {}", opening)
+    };
+
+    if !moves.is_empty() {
+        command += " moves";
+        for m in moves {
+            command += " ";
+            command += &m.to_uci();
+        }
+    }
+
+    command
+}
+
+fn play_game(
+    white: &mut dyn Player,
+    black: &mut dyn Player,
+    opening: &str,
+    round: usize,
+    limits: &Limits,
+) -> Result<Game, Box<dyn Error>> {
+    white.new_game()?;
+    black.new_game()?;
+
+    let mut board = Board::from_fen(opening)?;
+    let mut hashes = vec![board.hash];
+
+    let mut clock = match limits.time_control {
+        Some(tc) => Clock {
+            white: tc.time,
+            black: tc.time,
+        },
+        None => Clock {
+            white: Duration::ZERO,
+            black: Duration::ZERO,
+        },
+    };
+
+    let mut moves = Vec::new();
+    let mut sans = Vec::new();
+
+    let (result, reason) = loop {
+        let side = if board.turn.is_white() {
+            "White"
+        } else {
+            "Black"
+        };
+        let legal_moves = generate_legal_moves(&mut board, false);
+
+        if legal_moves.is_empty() {
+            break if !board.in_check() {
+                (0.5, String::from("Draw by stalemate"))
+            } else if board.turn == Color::White {
+                (0.0, String::from("Black mates"))
+            } else {
+                (1.0, String::from("White mates"))
+            };
+        }
+        if hashes.iter().filter(|&&hash| hash == board.hash).count() >= 3 {
+            break (0.5, String::from("Draw by 3-fold repetition"));
+        }
+        if board.halfmoves >= 100 {
+            break (0.5, String::from("Draw by fifty moves rule"));
+        }
+        if insufficient_material(&board) {
+            break (0.5, String::from("Draw by insufficient mating material"));
+        }
+
+        let loss = if board.turn.is_white() { 0.0 } else { 1.0 };
+        let player: &mut dyn Player = if board.turn.is_white() { white } else { black };
+
+        let start = Instant::now();
+        let uci = match player.go(
+            &position_command(opening, &moves),
+            limits,
+            &clock,
+            board.turn,
+        ) {
+            Ok(uci) => uci,
+            Err(e) => break (loss, format!("{} disconnects: {}", side, e)),
+        };
+        let elapsed = start.elapsed();
+
+        if let Some(tc) = limits.time_control {
+            let remaining = if board.turn == Color::White {
+                &mut clock.white
+            } else {
+                &mut clock.black
+            };
+
+            if elapsed > *remaining {
+                break (loss, format!("{} loses on time", side));
+            }
+            *remaining = *remaining - elapsed + tc.increment;
+        }
+
+        let Some(&m) = legal_moves.iter().find(|m| m.to_uci() == uci) else {
+            break (loss, format!("{} makes an illegal move: {}", side, uci));
+        };
+
+        sans.push(to_san(&m, &mut board)?);
+        let _ = board.make_move(m);
+        hashes.push(board.hash);
+        moves.push(m);
+    };
+
+    Ok(Game {
+        white: white.name().to_owned(),
+        black: black.name().to_owned(),
+        opening: opening.to_owned(),
+        round,
+        moves: sans,
+        result,
+        reason,
+    })
+}
+
+// the first 4 fields of a fen or epd, with the move counters if there are any. every opening is
+// checked here, a bad one would otherwise stop the match once its game comes up
+fn load_openings(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut openings = Vec::new();
+
+    for (number, line) in fs::read_to_string(path)?.lines().enumerate() {
+        let invalid = |reason: &str| format!("{} line {}: {}", path, number + 1, reason);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() || tokens[0].starts_with('#') {
+            continue;
+        }
+        if tokens.len() < 4 {
+            return Err(invalid(&format!("not a fen or epd: {}", line)).into());
+        }
+
+        let counters: Vec<&str> = tokens[4..]
+            .iter()
+            .take(2)
+            .take_while(|tok| tok.parse::<u16>().is_ok())
+            .copied()
+            .collect();
+
+        let opening = format!(
+            "{} {} {} {} {} {}",
+            tokens[0],
+            tokens[1],
+            tokens[2],
+            tokens[3],
+            counters.first().unwrap_or(&"0"),
+            counters.get(1).unwrap_or(&"1"),
+        );
+        Board::from_fen(&opening).map_err(|e| invalid(&e))?;
+        openings.push(opening);
+    }
+
+    if openings.is_empty() {
+        return Err(format!("no openings in {}", path).into());
+    }
+
+    Ok(openings)
+}
+
+// name=<name> [cmd=<path>] [option.<name>=<value>]..., until the next option of the match
+fn parse_engine(
+    args: &mut Peekable<impl Iterator<Item = String>>,
+    index: usize,
+) -> Result<Box<dyn Player>, Box<dyn Error>> {
+    let mut name = format!("engine{}", index + 1);
+    let mut command = None;
+    let mut options = Vec::new();
+
+    while let Some(arg) = args.next_if(|arg| !arg.starts_with("--")) {
+        let (key, value) = arg
+            .split_once('=')
+            .ok_or(format!("invalid engine setting {}", arg))?;
+
+        match key {
+            "name" => name = value.to_owned(),
+            "cmd" => command = Some(value.to_owned()),
+            _ => match key.strip_prefix("option.") {
+                Some(option) => options.push((option.to_owned(), value.to_owned())),
+                None => return Err(format!("unknown engine setting {}", key).into()),
+            },
+        }
+    }
+
+    Ok(match command {
+        Some(command) => Box::new(External::new(name, &command, &options)?),
+        None => Box::new(Internal::new(name, &options)),
+    })
+}
+
+fn parse_time_control(tc: &str) -> Result<TimeControl, Box<dyn Error>> {
+    let (time, increment) = tc.split_once('+').unwrap_or((tc, "0"));
+
+    Ok(TimeControl {
+        time: Duration::from_secs_f64(time.parse()?),
+        increment: Duration::from_secs_f64(increment.parse()?),
+    })
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut engines: Vec<Box<dyn Player>> = Vec::new();
+    let mut openings = vec![STARTPOS.to_owned()];
+    let mut games = 100;
+    let mut limits = Limits {
+        time_control: None,
+        nodes: None,
+        depth: None,
+    };
+    let mut sprt = None;
+    let mut alpha = 0.05;
+    let mut beta = 0.05;
+    let mut pgn = String::from("match.pgn");
+
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--engine" {
+            engines.push(parse_engine(&mut args, engines.len())?);
+            continue;
+        }
+
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--openings" => openings = load_openings(&value()?)?,
+            "--games" => games = value()?.parse()?,
+            "--tc" => limits.time_control = Some(parse_time_control(&value()?)?),
+            "--nodes" => limits.nodes = Some(value()?.parse()?),
+            "--depth" => limits.depth = Some(value()?.parse()?),
+            "--sprt" => sprt = Some((value()?.parse()?, value()?.parse()?)),
+            "--alpha" => alpha = value()?.parse()?,
+            "--beta" => beta = value()?.parse()?,
+            "--pgn" => pgn = value()?,
+            _ => return Err(format!("unknown option {}", arg).into()),
+        }
+    }
+
+    if engines.len() != 2 {
+        return Err("a match needs exactly two engines".into());
+    }
+    if limits.time_control.is_none() && limits.nodes.is_none() && limits.depth.is_none() {
+        limits.time_control = Some(parse_time_control("10+0.1")?);
+    }
+    let sprt = sprt.map(|(elo0, elo1)| Sprt {
+        elo0,
+        elo1,
+        alpha,
+        beta,
+    });
+
+    let mut out = BufWriter::new(File::create(&pgn)?);
+    let mut stats = Stats::default();
+    let (first, second) = engines.split_at_mut(1);
+    let (first, second) = (first[0].as_mut(), second[0].as_mut());
+
+    for round in 0..games {
+        // every opening is played with both colors
+        let opening = &openings[round / 2 % openings.len()];
+        let first_is_white = round % 2 == 0;
+        let game = if first_is_white {
+            play_game(first, second, opening, round + 1, &limits)?
+        } else {
+            play_game(second, first, opening, round + 1, &limits)?
+        };
+
+        game.write_pgn(&mut out, &limits)?;
+        out.flush()?;
+
+        let score = if first_is_white {
+            game.result
+        } else {
+            1.0 - game.result
+        };
+        if score == 1.0 {
+            stats.wins += 1;
+        } else if score == 0.0 {
+            stats.losses += 1;
+        } else {
+            stats.draws += 1;
+        }
+
+        println!(
+            "Finished game {} ({} vs {}): {} {{{}}}",
+            round + 1,
+            game.white,
+            game.black,
+            game.result_string(),
+            game.reason
+        );
+        println!(
+            "Score of {} vs {}: {} - {} - {}  [{:.3}] {}",
+            first.name(),
+            second.name(),
+            stats.wins,
+            stats.losses,
+            stats.draws,
+            stats.score(),
+            stats.games()
+        );
+        let (elo, error) = stats.elo();
+        println!("Elo difference: {:.1} +/- {:.1}", elo, error);
+
+        if let Some(sprt) = &sprt {
+            let llr = stats.llr(sprt.elo0, sprt.elo1);
+            let (lower, upper) = sprt.bounds();
+            println!(
+                "SPRT: llr {:.2} ({:.2}, {:.2}) [{}, {}]",
+                llr, lower, upper, sprt.elo0, sprt.elo1
+            );
+
+            if llr >= upper {
+                println!("SPRT: H1 was accepted");
+                break;
+            }
+            if llr <= lower {
+                println!("SPRT: H0 was accepted");
+                break;
+            }
+        }
+    }
+
+    println!("games written to {}", pgn);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// texel tuner for the evaluation parameters
+//
+// usage: tune [options] <data files>...
+//   --params <file>      parameters to start from, defaults to the built in values
+//   --output <file>      where the tuned parameters are written, defaults to tuned.txt
+//   --threads <n>        defaults to the number of cpus
+//   --iterations <n>     maximum number of local search passes, defaults to 100
+//   --step <n>           how much a parameter is changed at once, defaults to 1
+//   --limit <n>          only use the first n positions
+//
+// the data files contain one position per line, annotated with the result of the game:
+// `<fen> [1.0]`, `<fen> [1-0]`, `<epd> c9 "1/2-1/2";` or `<fen> | <score> | <result>`
+use std::{env, error::Error, thread, time::Instant};
+
+use engine::{
+    eval::EvalParams,
+    tune::{load_positions, Tuner},
+};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut params = EvalParams::default();
+    let mut output = String::from("tuned.txt");
+    let mut threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut iterations = 100;
+    let mut step = 1;
+    let mut limit = usize::MAX;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--params" => params = EvalParams::load(value()?)?,
+            "--output" => output = value()?,
+            "--threads" => threads = value()?.parse()?,
+            "--iterations" => iterations = value()?.parse()?,
+            "--step" => step = value()?.parse()?,
+            "--limit" => limit = value()?.parse()?,
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        return Err("no data files given".into());
+    }
+
+    let mut positions = Vec::new();
+    for file in &files {
+        positions.append(&mut load_positions(file)?);
+    }
+    positions.truncate(limit);
+    println!("loaded {} positions", positions.len());
+
+    let mut tuner = Tuner::new(positions, threads);
+    let k = tuner.optimise_k(&params);
+    println!("k = {:.4}, error = {:.6}", k, tuner.error(&params));
+
+    for iteration in 1..=iterations {
+        let start = Instant::now();
+        let (error, improved) = tuner.local_search(&mut params, step);
+        params.save(&output)?;
+
+        println!(
+            "iteration {}: error = {:.6}, {} parameters changed ({:.2?})",
+            iteration,
+            error,
+            improved,
+            start.elapsed()
+        );
+
+        if improved == 0 {
+            break;
+        }
+    }
+
+    println!("tuned parameters written to {}", output);
+
+    Ok(())
+}
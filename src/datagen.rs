@@ -0,0 +1,358 @@
+// generates training data for the tuner or a network by playing fixed node games against itself
+//
+// usage: rustchess2 datagen [options]
+//   --games <n>          number of games to play, defaults to 1000
+//   --threads <n>        defaults to the number of cpus
+//   --nodes <n>          nodes searched per move, defaults to 5000
+//   --seed <n>           the same seed always gives the same games, defaults to 0
+//   --random-plies <n>   random moves played at the start of every game, defaults to 8
+//   --output <file>      defaults to data.txt (or data.bin for the binary format)
+//   --format <format>    text or binary, defaults to text
+//
+// text format, one position per line: `<fen> | <score> | <result>`, which the tuner can read.
+// the score is the search score in centipawns and the result is 1.0, 0.5 or 0.0, both from
+// white's perspective
+//
+// binary format, 32 bytes per position:
+// * occupancy: u64, bit 0 is a1 and bit 63 is h8
+// * pieces: 16 bytes, one nibble per occupied square in the order of the occupancy bits (low
+//   nibble first). the nibble is the piece type (pawn, knight, bishop, rook, queen, king = 0..5),
+//   plus 8 for black pieces
+// * score: i16
+// * result: u8, 2 for a white win, 1 for a draw and 0 for a black win
+// * side to move: u8, 0 for white and 1 for black
+// * en passant square: u8, 64 if there is none
+// * castling rights: u8, white kingside, white queenside, black kingside, black queenside from
+//   the lowest bit
+// * fullmove number: u16
+// all numbers are little endian
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use engine::{search::MATE_THRESHOLD, Engine};
+use movegen::{generate_legal_moves, Attacks};
+use rustchess2::game::{
+    is_black_kingside, is_black_queenside, is_white_kingside, is_white_queenside, rand::Rng, Board,
+    Color, Move, PieceType, BLACK_KINGSIDE, BLACK_QUEENSIDE, STARTPOS, WHITE_KINGSIDE,
+    WHITE_QUEENSIDE,
+};
+
+// openings which are already lost after the random moves are thrown away
+const MAX_OPENING_SCORE: i32 = 1000;
+// a game is adjudicated as a win once the score stays this high for a few moves
+const WIN_SCORE: i32 = 2500;
+const WIN_PLIES: u32 = 8;
+// games that go on for too long are drawn
+const MAX_PLIES: u32 = 400;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Binary,
+}
+
+#[derive(Clone, Copy)]
+struct Settings {
+    games: usize,
+    threads: usize,
+    nodes: u64,
+    seed: u64,
+    random_plies: u32,
+}
+
+// a position as it is written out: the fen of the text format and the record of the binary one
+struct Position {
+    fen: String,
+    // the binary record, without the result
+    packed: [u8; 32],
+    // from white's perspective
+    score: i32,
+}
+
+pub fn datagen(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut settings = Settings {
+        games: 1000,
+        threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        nodes: 5000,
+        seed: 0,
+        random_plies: 8,
+    };
+    let mut output = None;
+    let mut format = Format::Text;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--games" => settings.games = value()?.parse()?,
+            "--threads" => settings.threads = value()?.parse::<usize>()?.max(1),
+            "--nodes" => settings.nodes = value()?.parse()?,
+            "--seed" => settings.seed = value()?.parse()?,
+            "--random-plies" => settings.random_plies = value()?.parse()?,
+            "--output" => output = Some(value()?),
+            "--format" => {
+                format = match value()?.as_str() {
+                    "text" => Format::Text,
+                    "binary" => Format::Binary,
+                    other => return Err(format!("unknown format {}", other).into()),
+                }
+            }
+            _ => return Err(format!("unknown option {}", arg).into()),
+        }
+    }
+
+    let output = output.unwrap_or(match format {
+        Format::Text => "data.txt".to_owned(),
+        Format::Binary => "data.bin".to_owned(),
+    });
+    let mut writer = BufWriter::new(File::create(&output)?);
+
+    // every game starts from a copy of this board
+    let template = Board::from_fen(STARTPOS)?;
+
+    let start = Instant::now();
+    let next_game = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|s| -> Result<(), Box<dyn Error>> {
+        for _ in 0..settings.threads {
+            let tx = tx.clone();
+            let next_game = &next_game;
+            s.spawn(move || loop {
+                let index = next_game.fetch_add(1, Ordering::Relaxed);
+                if index >= settings.games {
+                    break;
+                }
+
+                let game = play_game(template, settings, index);
+                if tx.send((index, game)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        // games finish out of order, they are written in order so the output only depends on
+        // the seed and not on the number of threads
+        let mut finished = BTreeMap::new();
+        let mut next_to_write = 0;
+        let mut positions = 0;
+        for (index, game) in rx {
+            finished.insert(index, game);
+
+            while let Some((data, result)) = finished.remove(&next_to_write) {
+                for position in &data {
+                    match format {
+                        Format::Text => writeln!(
+                            writer,
+                            "{} | {} | {:.1}",
+                            position.fen, position.score, result
+                        )?,
+                        Format::Binary => {
+                            let mut packed = position.packed;
+                            packed[26] = (result * 2.0) as u8;
+                            writer.write_all(&packed)?
+                        }
+                    }
+                }
+
+                positions += data.len();
+                next_to_write += 1;
+
+                if next_to_write % 100 == 0 || next_to_write == settings.games {
+                    println!(
+                        "{} games, {} positions ({:.2?})",
+                        next_to_write,
+                        positions,
+                        start.elapsed()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    })?;
+
+    writer.flush()?;
+    println!("training data written to {}", output);
+
+    Ok(())
+}
+
+// plays one game, returns the recorded positions and the result from white's perspective
+fn play_game(template: Board, settings: Settings, index: usize) -> (Vec<Position>, f64) {
+    let mut rng = Rng::from_seed(settings.seed ^ (index as u64).wrapping_mul(0x9E3779B97F4A7C15));
+
+    let mut engine = Engine::new(template);
+    engine.quiet = true;
+    engine.node_limit = Some(settings.nodes);
+
+    random_opening(&mut engine, &mut rng, template, settings);
+
+    let mut positions = Vec::new();
+    let mut winning_plies = 0;
+    let mut plies = 0;
+
+    let result = loop {
+        let moves = generate_legal_moves(&mut engine.board, false);
+        let in_check = engine.board.in_check();
+        let white = engine.board.turn == Color::White;
+
+        if moves.is_empty() {
+            break match (in_check, white) {
+                (false, _) => 0.5,
+                (true, true) => 0.0,
+                (true, false) => 1.0,
+            };
+        }
+
+        if engine.board.halfmoves >= 100
+            || plies >= MAX_PLIES
+            || repetitions(&engine) >= 3
+            || only_kings(&engine.board)
+        {
+            break 0.5;
+        }
+
+        let best_move = search(&mut engine).unwrap_or(moves[0]);
+        let score = if white {
+            engine.best_eval
+        } else {
+            -engine.best_eval
+        };
+
+        if score.abs() >= WIN_SCORE && score.abs() < MATE_THRESHOLD {
+            winning_plies += 1;
+            if winning_plies >= WIN_PLIES {
+                break if score > 0 { 1.0 } else { 0.0 };
+            }
+        } else {
+            winning_plies = 0;
+        }
+
+        // the score of noisy positions says more about the tactics than about the position
+        let quiet = !in_check
+            && best_move.capture_piece.is_none()
+            && best_move.promotion_piece.is_none()
+            && score.abs() < MATE_THRESHOLD;
+        if quiet {
+            positions.push(Position {
+                fen: engine.board.to_fen(),
+                packed: pack(&engine.board, score),
+                score,
+            });
+        }
+
+        plies += 1;
+
+        let _ = engine.board.make_move(best_move);
+        engine.repetition_table.push(engine.board.hash);
+    };
+
+    (positions, result)
+}
+
+// plays random moves until it finds an opening where the game isnt over or already decided
+fn random_opening(engine: &mut Engine, rng: &mut Rng, template: Board, settings: Settings) {
+    loop {
+        engine.board = template;
+        engine.repetition_table.clear();
+        engine.repetition_table.push(engine.board.hash);
+
+        let mut finished = true;
+        for _ in 0..settings.random_plies {
+            let moves = generate_legal_moves(&mut engine.board, false);
+            if moves.is_empty() {
+                finished = false;
+                break;
+            }
+
+            let m = moves[(rng.next_u64() % moves.len() as u64) as usize];
+            let _ = engine.board.make_move(m);
+            engine.repetition_table.push(engine.board.hash);
+        }
+
+        if finished
+            && !generate_legal_moves(&mut engine.board, false).is_empty()
+            && search(engine).is_some()
+            && engine.best_eval.abs() < MAX_OPENING_SCORE
+        {
+            return;
+        }
+    }
+}
+
+fn search(engine: &mut Engine) -> Option<Move> {
+    let best_move =
+        engine.iterative_deepening_search(100, false, Instant::now(), Duration::from_secs(0), None);
+    engine.transposition_table.clear();
+
+    best_move
+}
+
+// the last entry of the repetition table is the current position
+fn repetitions(engine: &Engine) -> usize {
+    engine
+        .repetition_table
+        .iter()
+        .filter(|&&hash| hash == engine.board.hash)
+        .count()
+}
+
+fn only_kings(board: &Board) -> bool {
+    board
+        .board
+        .iter()
+        .flatten()
+        .flatten()
+        .all(|piece| piece.piece_type() == PieceType::King)
+}
+
+fn pack(board: &Board, score: i32) -> [u8; 32] {
+    let mut bytes = [0; 32];
+
+    let mut occupancy = 0u64;
+    let mut nibbles = 0;
+    for square in 0..64 {
+        // a1 is square 0, the board array starts at a8
+        let Some(piece) = board.board[7 - square / 8][square % 8] else {
+            continue;
+        };
+
+        occupancy |= 1 << square;
+        let mut nibble = piece.piece_type().index() as u8;
+        if piece.color() == Color::Black {
+            nibble |= 8;
+        }
+
+        bytes[8 + nibbles / 2] |= nibble << (4 * (nibbles % 2));
+        nibbles += 1;
+    }
+    bytes[0..8].copy_from_slice(&occupancy.to_le_bytes());
+
+    let score = score.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    bytes[24..26].copy_from_slice(&score.to_le_bytes());
+    bytes[27] = board.turn.index() as u8;
+    bytes[28] = match board.en_passant_square {
+        // a1 is 0 here as well
+        Some(square) => (square.rank().index() * 8 + square.file().index()) as u8,
+        None => 64,
+    };
+    bytes[29] = is_white_kingside!(board.castle_state) as u8
+        | (is_white_queenside!(board.castle_state) as u8) << 1
+        | (is_black_kingside!(board.castle_state) as u8) << 2
+        | (is_black_queenside!(board.castle_state) as u8) << 3;
+    bytes[30..32].copy_from_slice(&board.fullmoves.to_le_bytes());
+
+    bytes
+}
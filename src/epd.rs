@@ -0,0 +1,156 @@
+// runs a test suite like WAC or STS, checking the best move of the engine against the bm and am
+// operations of every position
+//
+// usage: rustchess2 epd [options] <files>...
+//   --time <ms>    search time per position, defaults to 1000
+//   --depth <n>    search depth per position instead of a time limit
+use std::{
+    error::Error,
+    fs,
+    time::{Duration, Instant},
+};
+
+use engine::Engine;
+use movegen::san::{from_san, to_san};
+use rustchess2::game::{epd::Epd, Move};
+
+struct Failure {
+    id: String,
+    fen: String,
+    found: String,
+    expected: String,
+}
+
+// the moves of an operation, moves that arent legal in the position are reported and skipped
+fn parse_moves(epd: &Epd, sans: &[String]) -> Vec<Move> {
+    let mut board = epd.board();
+    sans.iter()
+        .filter_map(|san| match from_san(san, &mut board) {
+            Ok(m) => Some(m),
+            Err(e) => {
+                println!("{}: {}", epd.id().unwrap_or(&epd.fen), e);
+                None
+            }
+        })
+        .collect()
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut depth = None;
+    let mut time = Duration::from_millis(1000);
+    let mut files = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--time" => time = Duration::from_millis(value()?.parse()?),
+            "--depth" => depth = Some(value()?.parse()?),
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        return Err("no epd files given".into());
+    }
+
+    let mut positions = Vec::new();
+    for file in &files {
+        for line in fs::read_to_string(file)?.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            positions.push(line.parse::<Epd>()?);
+        }
+    }
+
+    let mut solved = 0;
+    let mut total = 0;
+    let mut solution_time = Duration::ZERO;
+    let mut failures = Vec::new();
+
+    for (i, epd) in positions.iter().enumerate() {
+        let best_moves = parse_moves(epd, epd.best_moves());
+        let avoid_moves = parse_moves(epd, epd.avoid_moves());
+        if best_moves.is_empty() && avoid_moves.is_empty() {
+            continue;
+        }
+        total += 1;
+
+        let good = |m: &Option<Move>| match m {
+            Some(m) => {
+                (best_moves.is_empty() || best_moves.contains(m)) && !avoid_moves.contains(m)
+            }
+            None => false,
+        };
+
+        let mut engine = Engine::new(epd.board());
+        engine.quiet = true;
+        let best_move = engine.iterative_deepening_search(
+            depth.unwrap_or(100),
+            depth.is_none(),
+            Instant::now(),
+            time,
+            None,
+        );
+
+        let id = epd
+            .id()
+            .map_or_else(|| format!("position {}", i + 1), str::to_owned);
+        let found = match best_move {
+            Some(m) => to_san(&m, &mut epd.board())?,
+            None => String::from("none"),
+        };
+
+        if good(&best_move) {
+            // the position counts as solved from the first iteration after which the engine
+            // never changed its mind
+            let time = engine
+                .iterations
+                .iter()
+                .rev()
+                .take_while(|iteration| good(&iteration.best_move))
+                .last()
+                .map_or(engine.start_time.elapsed(), |iteration| iteration.time);
+
+            solved += 1;
+            solution_time += time;
+            println!("{}: solved {} in {:.2?}", id, found, time);
+        } else {
+            let mut expected = Vec::new();
+            if !epd.best_moves().is_empty() {
+                expected.push(format!("bm {}", epd.best_moves().join(" ")));
+            }
+            if !epd.avoid_moves().is_empty() {
+                expected.push(format!("am {}", epd.avoid_moves().join(" ")));
+            }
+
+            println!("{}: failed, found {} ({})", id, found, expected.join(", "));
+            failures.push(Failure {
+                id,
+                fen: epd.fen.clone(),
+                found,
+                expected: expected.join(", "),
+            });
+        }
+    }
+
+    println!();
+    println!("solved {}/{}", solved, total);
+    if solved > 0 {
+        println!("average time to solution {:.2?}", solution_time / solved);
+    }
+
+    if !failures.is_empty() {
+        println!();
+        println!("failures:");
+        for failure in failures {
+            println!(
+                "{}: found {}, expected {} ({})",
+                failure.id, failure.found, failure.expected, failure.fen
+            );
+        }
+    }
+
+    Ok(())
+}
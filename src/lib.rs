@@ -1,2 +1,4 @@
 pub use game;
 pub use game::rand;
+
+pub mod stats;
@@ -1,8 +1,15 @@
+mod arena;
+mod datagen;
+mod epd;
+mod perft;
 mod uci;
 
-use std::{error::Error, io};
+use std::{env, error::Error, io};
 
-use engine::Engine;
+use engine::{
+    bench::{bench, BENCH_DEPTH},
+    Engine,
+};
 use movegen::generate_legal_moves;
 use rustchess2::game::{Board, BoardBuilder, Move};
 
@@ -23,6 +30,24 @@ macro_rules! measure {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // modes that dont talk over stdin are picked with the command line arguments
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("datagen") => return datagen::datagen(args),
+        Some("match") => return arena::run(args),
+        Some("epd") => return epd::run(args),
+        Some("perft") => return perft::run(args),
+        Some("bench") => {
+            let depth = match args.next() {
+                Some(depth) => depth.parse()?,
+                None => BENCH_DEPTH,
+            };
+            println!("{}", bench(depth));
+            return Ok(());
+        }
+        _ => {}
+    }
+
     let mut input: String = String::new();
     io::stdin()
         .read_line(&mut input)
@@ -0,0 +1,172 @@
+// perft tool for debugging the move generator
+//
+// usage: rustchess2 perft <depth> [options]
+//   --fen <fen>           position to count, defaults to the starting position
+//   --variant <name>      the UCI_Variant name of the rules to count with, defaults to chess
+//   --threads <n>         root moves are split between the threads, defaults to 1
+//   --hash <mb>           size of the transposition table of each thread, defaults to 0 (none)
+//   --pseudo-legal        counts with the pseudo-legal generator and the legality test instead
+//                         of the legal generator, ignores --hash
+//   --divide              prints the node count after every root move
+//   --reference <file>    compares the divide with a `<move>: <nodes>` file from another engine
+//                         and prints the first move that differs
+//   --suite [<file>]      runs every position of the built in suite (or of an epd file with
+//                         D1..Dn operations) up to the given depth
+use std::{error::Error, fs, time::Instant};
+
+use movegen::perft::{divide, first_difference, PerftOptions, PERFT_SUITE, VARIANT_PERFT_SUITE};
+use rustchess2::game::{epd::Epd, BoardBuilder, Move, Variant, STARTPOS};
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), Box<dyn Error>> {
+    let mut depth = None;
+    let mut fen = STARTPOS.to_owned();
+    let mut variant = Variant::Standard;
+    let mut options = PerftOptions::default();
+    let mut print_divide = false;
+    let mut reference = None;
+    let mut suite = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+
+        match arg.as_str() {
+            "--fen" => fen = value()?,
+            "--variant" => variant = value()?.parse()?,
+            "--threads" => options.threads = value()?.parse()?,
+            "--hash" => options.hash_size = value()?.parse()?,
+            "--pseudo-legal" => options.pseudo_legal = true,
+            "--divide" => print_divide = true,
+            "--reference" => reference = Some(fs::read_to_string(value()?)?),
+            // the file is optional
+            "--suite" => suite = Some(None),
+            _ if suite == Some(None) && arg.parse::<u8>().is_err() => suite = Some(Some(arg)),
+            _ => depth = Some(arg.parse::<u8>()?),
+        }
+    }
+
+    let depth = depth.ok_or("no depth given")?;
+
+    if let Some(file) = suite {
+        return run_suite(file, variant, depth, options);
+    }
+
+    let board = BoardBuilder::new()
+        .set_variant(variant)
+        .set_position(fen)
+        .build();
+    let start = Instant::now();
+    let divide = divide(&board, depth, options);
+    let elapsed = start.elapsed();
+    let nodes: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+
+    if print_divide || reference.is_some() {
+        for (m, nodes) in &divide {
+            println!("{}: {}", m, nodes);
+        }
+        println!();
+    }
+
+    println!(
+        "{} nodes in {:.2?} ({} nps)",
+        nodes.max(if depth == 0 { 1 } else { 0 }),
+        elapsed,
+        (nodes as f64 / elapsed.as_secs_f64().max(1e-9)) as u64
+    );
+
+    if let Some(reference) = reference {
+        match first_difference(&divide, &reference) {
+            None => println!("no differences"),
+            Some((m, ours, theirs)) => {
+                let count =
+                    |nodes: Option<u64>| nodes.map_or("missing".to_owned(), |n| n.to_string());
+                println!(
+                    "first difference: {} ({} here, {} in the reference)",
+                    m,
+                    count(ours),
+                    count(theirs)
+                );
+
+                // the position to divide next, to narrow the bug down
+                if ours.is_some() {
+                    let mut board = board;
+                    let _ = board.make_move(Move::from_uci(&m, board));
+                    println!("position after {}: {}", m, board.to_fen());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// a position with its (depth, nodes) results
+type SuitePosition = (Variant, String, Vec<(u8, u64)>);
+
+fn run_suite(
+    file: Option<String>,
+    variant: Variant,
+    max_depth: u8,
+    options: PerftOptions,
+) -> Result<(), Box<dyn Error>> {
+    let counts = |counts: &[u64]| {
+        counts
+            .iter()
+            .enumerate()
+            .map(|(i, &nodes)| (i as u8 + 1, nodes))
+            .collect()
+    };
+
+    // an epd file is counted with the given variant, the built in suite has its own
+    let positions: Vec<SuitePosition> = match file {
+        Some(file) => fs::read_to_string(file)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let epd: Epd = line.parse()?;
+                Ok((variant, epd.fen.clone(), epd.perft()))
+            })
+            .collect::<Result<_, String>>()?,
+        None => PERFT_SUITE
+            .iter()
+            .map(|&(fen, nodes)| (Variant::Standard, fen, nodes))
+            .chain(VARIANT_PERFT_SUITE)
+            .map(|(variant, fen, nodes)| (variant, fen.to_string(), counts(nodes)))
+            .collect(),
+    };
+
+    let start = Instant::now();
+    let mut failures = 0;
+    for (variant, fen, counts) in positions {
+        let board = BoardBuilder::new()
+            .set_variant(variant)
+            .set_position(fen.clone())
+            .build();
+
+        for (depth, expected) in counts {
+            if depth > max_depth {
+                continue;
+            }
+
+            let nodes: u64 = divide(&board, depth, options)
+                .iter()
+                .map(|(_, nodes)| nodes)
+                .sum();
+            if nodes == expected {
+                println!("{} depth {}: {}", fen, depth, nodes);
+            } else {
+                failures += 1;
+                println!(
+                    "{} depth {}: {}, expected {} FAILED",
+                    fen, depth, nodes, expected
+                );
+            }
+        }
+    }
+
+    println!("finished in {:.2?}", start.elapsed());
+    if failures > 0 {
+        return Err(format!("{} perft results are wrong", failures).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+// match statistics, the elo difference of two engines and the sequential probability ratio test
+// that decides when a match can stop
+
+/// wins, draws and losses of the first engine
+#[derive(Default)]
+pub struct Stats {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+/// expected score given an elo difference
+pub fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+/// infinite for a score of 0 or 1, the bounds of the confidence interval can be past them
+pub fn elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score.clamp(0.0, 1.0) - 1.0).log10()
+}
+
+impl Stats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    // variance of the score of a single game
+    fn variance(&self) -> f64 {
+        let score = self.score();
+        let games = self.games() as f64;
+
+        (self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games
+    }
+
+    /// elo difference with the error margin of the 95% confidence interval
+    pub fn elo(&self) -> (f64, f64) {
+        let score = self.score();
+        let deviation = (self.variance() / self.games() as f64).sqrt();
+
+        let low = elo(score - 1.96 * deviation);
+        let high = elo(score + 1.96 * deviation);
+        // the margin is unbounded once the interval reaches a score of 0 or 1
+        let margin = if low.is_finite() && high.is_finite() {
+            (high - low) / 2.0
+        } else {
+            f64::INFINITY
+        };
+
+        (elo(score), margin)
+    }
+
+    /// log likelihood ratio of elo1 against elo0, using a normal approximation of the trinomial
+    /// distribution of the results
+    /// https://www.chessprogramming.org/Sequential_Probability_Ratio_Test
+    pub fn llr(&self, elo0: f64, elo1: f64) -> f64 {
+        let variance = self.variance();
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        let s0 = expected_score(elo0);
+        let s1 = expected_score(elo1);
+
+        (s1 - s0) * (2.0 * self.score() - s0 - s1) / (2.0 * variance) * self.games() as f64
+    }
+}
+
+/// the elo hypotheses of the test and its false positive and false negative rates
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    /// the llr below which elo0 is accepted and above which elo1 is
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+}
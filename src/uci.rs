@@ -3,6 +3,7 @@ use std::{
     fmt::Display,
     io,
     process::exit,
+    str::FromStr,
     sync::{
         mpsc::{self, Receiver},
         Arc, Mutex,
@@ -11,29 +12,69 @@ use std::{
     time::{Duration, Instant},
 };
 
-use game::{
-    get_piece_color, get_piece_type, BoardBuilder, Move, BISHOP, BLACK, KING, KNIGHT, PAWN, QUEEN,
-    ROOK, STARTPOS, WHITE,
-};
+use game::{render::RenderOptions, Board, BoardBuilder, Color, Move, Variant};
 
-use engine::Engine;
+use engine::{
+    bench::{bench, BENCH_DEPTH},
+    eval::EvalParams,
+    Engine, SearchOptions,
+};
+#[cfg(feature = "nnue")]
+use game::nnue::Network;
+use movegen::perft::{divide, PerftOptions};
 
 const NAME: &str = "ThinnGopher";
 const AUTHOR: &str = "1ngopher";
 
 pub struct UciEngine {
     pub engine: Engine,
+    // kept outside of the engine since the engine is recreated for every position command
+    pub options: SearchOptions,
+    pub eval_params: EvalParams,
+    pub variant: Variant,
+    // the last move of the position command, highlighted by the "d" command
+    pub last_move: Option<Move>,
+    #[cfg(feature = "nnue")]
+    pub network: Option<&'static Network>,
+    #[cfg(feature = "nnue")]
+    pub use_nnue: bool,
+}
+
+// a bad value from the gui is reported and the option keeps its old value
+fn parse_option<T: FromStr>(name: &str, value: &str) -> Option<T> {
+    let parsed = value.parse().ok();
+    if parsed.is_none() {
+        println!("info string invalid value \"{}\" for {}", value, name);
+    }
+    parsed
 }
 
 impl UciEngine {
+    pub fn new() -> UciEngine {
+        UciEngine {
+            engine: Engine::new(
+                BoardBuilder::new()
+                    .set_position(Variant::Standard.start_fen().to_owned())
+                    .build(),
+            ),
+            options: SearchOptions::default(),
+            eval_params: EvalParams::default(),
+            variant: Variant::Standard,
+            last_move: None,
+            #[cfg(feature = "nnue")]
+            network: None,
+            #[cfg(feature = "nnue")]
+            use_nnue: false,
+        }
+    }
+
     pub fn uci() -> Result<(), Box<dyn Error>> {
         println!("id name {}\nid author {}", NAME, AUTHOR);
         // "fearless concurrency" lmao
-        let mut uciengine: Arc<Mutex<UciEngine>> = Arc::new(Mutex::new(UciEngine {
-            engine: Engine::new(BoardBuilder::new().build()),
-        }));
+        let uciengine: Arc<Mutex<UciEngine>> = Arc::new(Mutex::new(UciEngine::new()));
         let mut prev_input: Option<String> = None;
 
+        UciEngine::print_options();
         println!("uciok");
         loop {
             uciengine.lock().unwrap().engine.canceled = false;
@@ -53,10 +94,16 @@ impl UciEngine {
             match input.split(' ').nth(0).unwrap() {
                 "isready" => println!("readyok"),
                 "ucinewgame" => {
-                    uciengine = Arc::new(Mutex::new(UciEngine {
-                        engine: Engine::new(BoardBuilder::new().build()),
-                    }))
+                    let mut uciengine = uciengine.lock().unwrap();
+                    let variant = uciengine.variant;
+                    uciengine.reset_engine(
+                        BoardBuilder::new()
+                            .set_variant(variant)
+                            .set_position(variant.start_fen().to_owned())
+                            .build(),
+                    );
                 }
+                "setoption" => uciengine.lock().unwrap().setoption_command(&input),
                 "position" => uciengine.lock().unwrap().position_command(&input),
                 "go" => {
                     thread::spawn(move || {
@@ -72,41 +119,153 @@ impl UciEngine {
                     }
                 }
                 "quit" => exit(0),
-                "eval" => println!(
-                    "Static evaluation: {}",
-                    uciengine.lock().unwrap().engine.evaluate()
-                ),
+                "bench" => {
+                    let depth = input
+                        .split(' ')
+                        .nth(1)
+                        .and_then(|depth| depth.parse().ok())
+                        .unwrap_or(BENCH_DEPTH);
+                    println!("{}", bench(depth));
+                }
+                "eval" => println!("{}", uciengine.lock().unwrap().engine.trace_evaluation()),
                 "d" => println!("{}", uciengine.lock().unwrap()),
                 _ => {}
             }
         }
     }
 
-    fn position_command(&mut self, command: &str) {
+    fn print_options() {
+        let defaults = SearchOptions::default();
+        println!(
+            "option name NullMovePruning type check default {}",
+            defaults.null_move_pruning
+        );
+        println!(
+            "option name NullMoveBaseReduction type spin default {} min 0 max 6",
+            defaults.null_move_base_reduction
+        );
+        println!(
+            "option name NullMoveDepthDivisor type spin default {} min 1 max 12",
+            defaults.null_move_depth_divisor
+        );
+        println!(
+            "option name PVS type check default {}",
+            defaults.principal_variation_search
+        );
+        // parameter file written by the tuner, empty for the built in values
+        println!("option name EvalParams type string default <empty>");
+        let variants: String = Variant::ALL
+            .iter()
+            .map(|variant| format!(" var {}", variant))
+            .collect();
+        println!(
+            "option name UCI_Variant type combo default {}{}",
+            Variant::Standard,
+            variants
+        );
+        #[cfg(feature = "nnue")]
+        {
+            println!("option name EvalFile type string default <empty>");
+            println!("option name UseNNUE type check default false");
+        }
+    }
+
+    // setoption name <id> [value <x>]
+    pub fn setoption_command(&mut self, command: &str) {
+        let tokens: Vec<&str> = command.split(' ').collect();
+        let Some(name_index) = tokens.iter().position(|&tok| tok == "name") else {
+            return;
+        };
+        let value_index = tokens
+            .iter()
+            .position(|&tok| tok == "value")
+            .unwrap_or(tokens.len());
+
+        let name = tokens[name_index + 1..value_index].join(" ");
+        let value = tokens.get(value_index + 1..).unwrap_or(&[]).join(" ");
+
+        match name.to_lowercase().as_str() {
+            "nullmovepruning" => {
+                if let Some(value) = parse_option(&name, &value) {
+                    self.options.null_move_pruning = value;
+                }
+            }
+            "nullmovebasereduction" => {
+                if let Some(value) = parse_option(&name, &value) {
+                    self.options.null_move_base_reduction = value;
+                }
+            }
+            "nullmovedepthdivisor" => {
+                if let Some(value) = parse_option(&name, &value) {
+                    self.options.null_move_depth_divisor = value;
+                }
+            }
+            "pvs" => {
+                if let Some(value) = parse_option(&name, &value) {
+                    self.options.principal_variation_search = value;
+                }
+            }
+            "evalparams" => {
+                let params = if value.is_empty() || value == "<empty>" {
+                    Ok(EvalParams::default())
+                } else {
+                    EvalParams::load(&value)
+                };
+
+                match params {
+                    Ok(params) => {
+                        self.eval_params = params;
+                        self.engine.eval_params = self.eval_params.clone();
+                    }
+                    Err(e) => println!("info string failed to load {}: {}", value, e),
+                }
+            }
+            "uci_variant" => match value.parse() {
+                Ok(variant) => self.variant = variant,
+                Err(e) => println!("info string {}", e),
+            },
+            #[cfg(feature = "nnue")]
+            "evalfile" => match Network::load(&value) {
+                Ok(network) => {
+                    self.network = Some(network);
+                    self.set_network();
+                }
+                Err(e) => println!("info string failed to load {}: {}", value, e),
+            },
+            #[cfg(feature = "nnue")]
+            "usennue" => {
+                if let Some(value) = parse_option(&name, &value) {
+                    self.use_nnue = value;
+                    self.set_network();
+                }
+            }
+            _ => println!("info string unknown option {}", name),
+        }
+    }
+
+    pub fn position_command(&mut self, command: &str) {
         let mut builder = BoardBuilder::new();
-        // ill fully implement later
+        builder.set_variant(self.variant);
         if command.contains("startpos") {
-            builder.set_position(STARTPOS.to_owned());
+            builder.set_position(self.variant.start_fen().to_owned());
         }
-        self.engine = Engine::new(builder.build());
-
-        for (i, tok) in command.split(' ').enumerate() {
-            if tok == "fen" {
-                self.engine = Engine::new(
-                    BoardBuilder::new()
-                        .set_position(format!(
-                            "{} {} {} {} {} {}",
-                            command.split(' ').nth(i + 1).unwrap(),
-                            command.split(' ').nth(i + 2).unwrap(),
-                            command.split(' ').nth(i + 3).unwrap(),
-                            command.split(' ').nth(i + 4).unwrap(),
-                            command.split(' ').nth(i + 5).unwrap(),
-                            command.split(' ').nth(i + 6).unwrap(),
-                        ))
-                        .build(),
-                );
+
+        // the fen goes up to the moves, three-check fens have an extra field
+        let tokens: Vec<&str> = command.split(' ').collect();
+        if let Some(i) = tokens.iter().position(|&tok| tok == "fen") {
+            let end = tokens
+                .iter()
+                .position(|&tok| tok == "moves")
+                .unwrap_or(tokens.len());
+            // a bad fen from the gui keeps the old position
+            if let Err(e) = builder.try_set_position(&tokens[i + 1..end].join(" ")) {
+                println!("info string {}", e);
+                return;
             }
         }
+        let mut board = builder.build();
+        let mut last_move = None;
+        let mut repetition_table = Vec::new();
 
         if command.contains("moves") {
             let mut found = false;
@@ -117,15 +276,35 @@ impl UciEngine {
                 }
 
                 if found {
-                    let _ = self
-                        .engine
-                        .board
-                        .make_move(Move::from_uci(tok, self.engine.board));
+                    let m = Move::from_uci(tok, board);
+                    let _ = board.make_move(m);
+                    last_move = Some(m);
 
-                    self.engine.repetition_table.push(self.engine.board.hash);
+                    repetition_table.push(board.hash);
                 }
             }
         }
+
+        self.reset_engine(board);
+        self.engine.repetition_table = repetition_table;
+        self.last_move = last_move;
+    }
+
+    // a new engine for the board, with the evaluation the options picked
+    fn reset_engine(&mut self, board: Board) {
+        self.engine = Engine::new(board);
+        self.last_move = None;
+        self.engine.eval_params = self.eval_params.clone();
+        #[cfg(feature = "nnue")]
+        self.set_network();
+    }
+
+    // the network is only given to the engine when it should be used, so the evaluation can just
+    // check if the engine has one
+    #[cfg(feature = "nnue")]
+    fn set_network(&mut self) {
+        let network = if self.use_nnue { self.network } else { None };
+        self.engine.set_network(network);
     }
 
     fn go_command(&mut self, command: &str, rx: Receiver<bool>) {
@@ -134,10 +313,12 @@ impl UciEngine {
         // self.engine.minimax(4, 0, MIN, MAX, PvNode::new(None), &mut Vec::new());
         //self.engine.negamax(6, 0, MIN, MAX);
         let tokens: Vec<&str> = command.split(' ').collect();
+        self.engine.options = self.options;
 
         let mut btime: Option<i32> = None;
         let mut wtime: Option<i32> = None;
         let mut search_depth = 100; // default depth is infinite
+        self.engine.node_limit = None;
         for i in 0..tokens.len() {
             if tokens[i] == "btime" {
                 btime = Some(tokens[i + 1].parse().expect("failed to parse"));
@@ -151,6 +332,10 @@ impl UciEngine {
                 search_depth = tokens[i + 1].parse().expect("failed to parse");
             }
 
+            if tokens[i] == "nodes" {
+                self.engine.node_limit = Some(tokens[i + 1].parse().expect("failed to parse"));
+            }
+
             if tokens[i] == "movetime" {
                 wtime = Some(tokens[i + 1].parse::<i32>().expect("failed to parse") * MOVES as i32);
                 btime = Some(tokens[i + 1].parse::<i32>().expect("failed to parse") * MOVES as i32);
@@ -161,10 +346,14 @@ impl UciEngine {
             }
 
             if tokens[i] == "perft" {
-                movegen::perft::perft(
-                    &mut self.engine.board,
-                    tokens[i + 1].parse::<u8>().expect("failed to parse"),
-                    0,
+                let depth = tokens[i + 1].parse::<u8>().expect("failed to parse");
+                let divide = divide(&self.engine.board, depth, PerftOptions::default());
+                for (m, nodes) in &divide {
+                    println!("{}: {}", m, nodes);
+                }
+                println!(
+                    "Nodes Searched: {}",
+                    divide.iter().map(|(_, nodes)| nodes).sum::<u64>()
                 );
                 return;
             }
@@ -173,7 +362,7 @@ impl UciEngine {
         let best_move = if let Some(b) = btime {
             if let Some(w) = wtime {
                 let curr_time = Instant::now();
-                let alloted_time = if self.engine.board.turn {
+                let alloted_time = if self.engine.board.turn == Color::White {
                     Duration::from_millis(w.try_into().unwrap()) / MOVES
                 } else {
                     Duration::from_millis(b.try_into().unwrap()) / MOVES
@@ -207,66 +396,12 @@ impl UciEngine {
 
 impl Display for UciEngine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "+---+---+---+---+---+---+---+---+")?;
-        for (index, rank) in self.engine.board.board.into_iter().enumerate() {
-            write!(f, "|")?;
-            for square in rank {
-                write!(
-                    f,
-                    " {} |",
-                    match get_piece_type!(square) {
-                        PAWN => match get_piece_color!(square) {
-                            WHITE => "P",
-                            BLACK => "p",
-                            _ => "",
-                        },
-                        BISHOP => match get_piece_color!(square) {
-                            WHITE => "B",
-                            BLACK => "b",
-                            _ => "",
-                        },
-                        KNIGHT => match get_piece_color!(square) {
-                            WHITE => "N",
-                            BLACK => "n",
-                            _ => "",
-                        },
-                        ROOK => match get_piece_color!(square) {
-                            WHITE => "R",
-                            BLACK => "r",
-                            _ => "",
-                        },
-                        QUEEN => match get_piece_color!(square) {
-                            WHITE => "Q",
-                            BLACK => "q",
-                            _ => "",
-                        },
-                        KING => match get_piece_color!(square) {
-                            WHITE => "K",
-                            BLACK => "k",
-                            _ => "",
-                        },
-                        _ => " ",
-                    }
-                )?;
-            }
-            writeln!(f, " {}\n+---+---+---+---+---+---+---+---+", 8 - index)?;
-        }
-        writeln!(f, "  a   b   c   d   e   f   g   h")?;
-
-        writeln!(
-            f,
-            "\n {} to move.\n {}",
-            if self.engine.board.turn {
-                "White"
-            } else {
-                "Black"
-            },
-            match self.engine.board.en_passant_square {
-                Some(x) => format!("{:?}", x),
-                None => "No en passant square".to_string(),
-            }
-        )?;
-
-        Ok(())
+        let options = RenderOptions {
+            last_move: self.last_move,
+            ..RenderOptions::default()
+        };
+        f.write_str(&self.engine.board.render_text(&options))?;
+        writeln!(f, "Fen: {}", self.engine.board.to_fen())?;
+        write!(f, "Key: {:016x}", self.engine.board.hash)
     }
 }
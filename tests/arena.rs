@@ -0,0 +1,88 @@
+use std::{env, fs, process::Command};
+
+use rustchess2::stats::{elo, expected_score, Stats};
+
+fn stats(wins: u32, draws: u32, losses: u32) -> Stats {
+    Stats {
+        wins,
+        draws,
+        losses,
+    }
+}
+
+fn assert_close(value: f64, expected: f64) {
+    assert!((value - expected).abs() < 0.01, "{} != {}", value, expected);
+}
+
+#[test]
+fn elo_of_a_score() {
+    assert_eq!(elo(0.5), 0.0);
+    assert_close(elo(0.75), 190.85);
+    assert_close(elo(0.25), -190.85);
+    assert_close(expected_score(elo(0.9)), 0.9);
+    assert_eq!(elo(1.0), f64::INFINITY);
+    assert_eq!(elo(0.0), -f64::INFINITY);
+    assert_eq!(elo(1.2), f64::INFINITY);
+    assert_eq!(elo(-0.2), -f64::INFINITY);
+}
+
+#[test]
+fn elo_with_error_margin() {
+    let (elo, margin) = stats(49, 3, 6).elo();
+    assert_close(elo, 331.29);
+    assert_close(margin, 144.56);
+
+    // the upper bound of the interval is past a score of 1
+    let (elo, margin) = stats(9, 0, 1).elo();
+    assert_close(elo, 381.70);
+    assert_eq!(margin, f64::INFINITY);
+
+    // a perfect score has no finite elo
+    assert_eq!(stats(7, 0, 0).elo(), (f64::INFINITY, f64::INFINITY));
+    assert_eq!(stats(0, 0, 7).elo(), (-f64::INFINITY, f64::INFINITY));
+}
+
+#[test]
+fn log_likelihood_ratio() {
+    assert_close(stats(49, 3, 6).llr(0.0, 10.0), 3.04);
+    // an even score is as likely with -5 as with 5
+    assert_close(stats(30, 40, 30).llr(-5.0, 5.0), 0.0);
+    assert!(stats(30, 40, 30).llr(0.0, 5.0) < 0.0);
+    // nothing to go on without a spread in the results
+    assert_eq!(stats(7, 0, 0).llr(0.0, 5.0), 0.0);
+    assert_eq!(stats(0, 7, 0).llr(0.0, 5.0), 0.0);
+}
+
+#[test]
+fn bad_openings_are_reported_before_the_match() {
+    let path = env::temp_dir().join(format!("rustchess2-openings-{}.epd", std::process::id()));
+    fs::write(
+        &path,
+        "# a comment\n\n\
+         rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 id \"e4\";\n\
+         rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBN b KQkq e3 id \"bad\";\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rustchess2"))
+        .args([
+            "match",
+            "--engine",
+            "name=a",
+            "--engine",
+            "name=b",
+            "--openings",
+        ])
+        .arg(&path)
+        .args(["--depth", "1", "--pgn"])
+        .arg(path.with_extension("pgn"))
+        .output()
+        .unwrap();
+    let _ = fs::remove_file(&path);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 4: invalid fen"), "{}", stderr);
+    // no game was played
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,93 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+use movegen::Attacks;
+use rustchess2::game::{Board, Color, File, Rank, Square};
+
+// runs the datagen command and returns what it wrote
+fn datagen(format: &str, threads: &str) -> Vec<u8> {
+    let output: PathBuf = env::temp_dir().join(format!(
+        "rustchess2-datagen-{}-{}.{}",
+        std::process::id(),
+        threads,
+        format
+    ));
+    let status = Command::new(env!("CARGO_BIN_EXE_rustchess2"))
+        .args(["datagen", "--games", "2", "--nodes", "200", "--seed", "3"])
+        .args(["--format", format, "--threads", threads, "--output"])
+        .arg(&output)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+
+    let data = fs::read(&output).unwrap();
+    let _ = fs::remove_file(&output);
+    data
+}
+
+// a text line split into the board, the score and the result
+fn parse_line(line: &str) -> (Board, i16, f64) {
+    let fields: Vec<&str> = line.split(" | ").collect();
+    assert_eq!(fields.len(), 3, "{}", line);
+
+    let board = Board::from_fen(fields[0]).unwrap();
+    (
+        board,
+        fields[1].parse().unwrap(),
+        fields[2].parse().unwrap(),
+    )
+}
+
+#[test]
+fn text_and_binary_records_match() {
+    let text = String::from_utf8(datagen("text", "1")).unwrap();
+    let binary = datagen("binary", "1");
+
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(!lines.is_empty());
+    assert_eq!(binary.len(), 32 * lines.len());
+
+    for (line, record) in lines.iter().zip(binary.chunks_exact(32)) {
+        let (board, score, result) = parse_line(line);
+        assert!([0.0, 0.5, 1.0].contains(&result), "{}", line);
+        assert!(board.halfmoves < 100, "{}", line);
+        // quiet positions only
+        assert!(!board.in_check(), "{}", line);
+
+        // the pieces in the order of the occupancy bits, a1 first
+        let occupancy = u64::from_le_bytes(record[0..8].try_into().unwrap());
+        let mut nibbles = record[8..24].iter().flat_map(|byte| [byte & 15, byte >> 4]);
+        for index in 0..64 {
+            let piece = board.piece_at(Square::new(File::ALL[index % 8], Rank::ALL[index / 8]));
+            if occupancy & 1 << index == 0 {
+                assert_eq!(piece, None, "{}", line);
+                continue;
+            }
+
+            let piece = piece.unwrap();
+            let mut nibble = piece.piece_type().index() as u8;
+            if piece.color() == Color::Black {
+                nibble |= 8;
+            }
+            assert_eq!(nibbles.next(), Some(nibble), "{}", line);
+        }
+
+        assert_eq!(i16::from_le_bytes([record[24], record[25]]), score);
+        assert_eq!(record[26] as f64, result * 2.0);
+        assert_eq!(record[27], board.turn.index() as u8);
+        let en_passant = board.en_passant_square.map_or(64, |square| {
+            (square.rank().index() * 8 + square.file().index()) as u8
+        });
+        assert_eq!(record[28], en_passant);
+        assert_eq!(record[29], board.castle_state.reverse_bits() >> 4);
+        assert_eq!(
+            u16::from_le_bytes([record[30], record[31]]),
+            board.fullmoves
+        );
+    }
+}
+
+#[test]
+fn output_does_not_depend_on_the_threads() {
+    assert_eq!(datagen("text", "1"), datagen("text", "2"));
+}
@@ -0,0 +1,64 @@
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// sends the commands to the uci loop and returns everything it printed
+fn uci(commands: &[&str]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rustchess2"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    for command in ["uci"].iter().chain(commands).chain(&["quit"]) {
+        writeln!(stdin, "{}", command).unwrap();
+    }
+
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// the fen printed by the "d" command
+fn fen(output: &str) -> &str {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Fen: "))
+        .unwrap()
+}
+
+#[test]
+fn startpos_of_the_variant() {
+    let output = uci(&[
+        "setoption name UCI_Variant value horde",
+        "position startpos",
+        "d",
+    ]);
+    let fen = fen(&output);
+    assert_eq!(
+        fen,
+        "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+    );
+    assert_eq!(fen.matches('P').count(), 36);
+
+    // ucinewgame starts from the same position
+    let output = uci(&["setoption name UCI_Variant value horde", "ucinewgame", "d"]);
+    assert_eq!(self::fen(&output), fen);
+}
+
+#[test]
+fn ucinewgame_keeps_the_eval_params() {
+    let path = env::temp_dir().join(format!("rustchess2-uci-{}.params", std::process::id()));
+    fs::write(&path, "tempo 77 33\n").unwrap();
+    let setoption = format!("setoption name EvalParams value {}", path.display());
+
+    let output = uci(&[&setoption, "ucinewgame", "eval"]);
+    let _ = fs::remove_file(&path);
+    assert!(
+        output.contains("Final evaluation: 77 cp (side to move: white)"),
+        "{}",
+        output
+    );
+}